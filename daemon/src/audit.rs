@@ -0,0 +1,391 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, SystemTime};
+
+use serde_json::{json, Value};
+use tracing::{info, warn};
+
+use crate::capabilities::{CapabilityProvider, Progress, ToolResult};
+use crate::config::{expand_home, AuditSettings};
+use crate::error::DaemonError;
+use crate::mcp::types::{CallToolResult, Tool, ToolAnnotations};
+
+/// Capability of the tool that searches the log, for permissions.toml.
+pub const CAPABILITY: &str = "audit";
+
+/// Name of the tool that searches the log.
+pub const QUERY_TOOL: &str = "daemon_audit_query";
+
+/// Most entries a query returns.
+const MAX_QUERY_LIMIT: u64 = 500;
+
+/// Append-only record of every `tools/call`, and of the tools prompt
+/// templates call, one JSON object per line: when, which client, which
+/// tool with which (redacted) arguments, how it ended and how long it took. The file is rotated by size, keeping
+/// `max_files` older files as `<path>.1` (newest) to `<path>.<max_files>`.
+pub struct AuditLog {
+    settings: AuditSettings,
+    path: PathBuf,
+    /// The open log and its size, opened on the first write.
+    file: Mutex<Option<(File, u64)>>,
+}
+
+/// How a tool call ended, for the log's `status`.
+pub fn status(result: &ToolResult, dry_run: bool) -> &'static str {
+    match result {
+        Ok(r) if r.is_error == Some(true) => "error",
+        Ok(_) if dry_run => "dry_run",
+        Ok(_) => "ok",
+        Err(e) => e.kind(),
+    }
+}
+
+impl AuditLog {
+    pub fn new(settings: AuditSettings) -> Self {
+        let path = expand_home(&settings.path);
+        if settings.enabled {
+            info!(path = %path.display(), "audit log enabled");
+        }
+        Self {
+            settings,
+            path,
+            file: Mutex::new(None),
+        }
+    }
+
+    /// Append a tool call that took `duration` to the log. `status` is
+    /// `ok`, `error` for a result with `isError`, or the `DaemonError` kind
+    /// (e.g. `permission_denied`, `timeout`). Failing to write is logged,
+    /// never fatal to the call.
+    pub fn record(&self, client: &str, tool: &str, arguments: &Value, status: &str, duration: Duration) {
+        if !self.settings.enabled {
+            return;
+        }
+        let mut arguments = arguments.clone();
+        redact(&mut arguments, &self.settings.redact);
+        let entry = json!({
+            "timestamp": humantime::format_rfc3339_millis(SystemTime::now() - duration).to_string(),
+            "client": client,
+            "tool": tool,
+            "arguments": arguments,
+            "status": status,
+            "duration_ms": duration.as_millis() as u64,
+        });
+        let mut line = entry.to_string();
+        line.push('\n');
+
+        if let Err(e) = self.append(line.as_bytes()) {
+            warn!(path = %self.path.display(), error = %e, "failed to write audit log");
+        }
+    }
+
+    fn append(&self, line: &[u8]) -> std::io::Result<()> {
+        let mut file = self.lock();
+        if file.is_none() {
+            *file = Some(self.open()?);
+        }
+        let max_size = self.settings.max_size_mb * 1024 * 1024;
+        if let Some((_, size)) = file.as_ref()
+            && *size > 0
+            && *size + line.len() as u64 > max_size
+        {
+            *file = None;
+            self.rotate()?;
+            *file = Some(self.open()?);
+        }
+        let (f, size) = file.as_mut().expect("opened above");
+        f.write_all(line)?;
+        *size += line.len() as u64;
+        Ok(())
+    }
+
+    fn open(&self) -> std::io::Result<(File, u64)> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let file = OpenOptions::new().create(true).append(true).open(&self.path)?;
+        let size = file.metadata()?.len();
+        Ok((file, size))
+    }
+
+    /// Shift `<path>.N` to `<path>.N+1`, dropping the oldest, and move the
+    /// current file to `<path>.1`.
+    fn rotate(&self) -> std::io::Result<()> {
+        let max_files = self.settings.max_files;
+        if max_files == 0 {
+            return fs::remove_file(&self.path);
+        }
+        let _ = fs::remove_file(self.rotated(max_files));
+        for n in (1..max_files).rev() {
+            let _ = fs::rename(self.rotated(n), self.rotated(n + 1));
+        }
+        fs::rename(&self.path, self.rotated(1))
+    }
+
+    fn rotated(&self, n: usize) -> PathBuf {
+        let mut name = self.path.clone().into_os_string();
+        name.push(format!(".{n}"));
+        name.into()
+    }
+
+    /// Entries matching `arguments` (see `query_tool`), newest first.
+    pub fn query(&self, arguments: &Value) -> Result<Value, DaemonError> {
+        let since = timestamp_argument(arguments, "since")?;
+        let until = timestamp_argument(arguments, "until")?;
+        let tool = arguments["tool"].as_str();
+        let client = arguments["client"].as_str();
+        let limit = arguments["limit"].as_u64().unwrap_or(50).min(MAX_QUERY_LIMIT) as usize;
+
+        // Hold the lock so the files aren't rotated while being read
+        let _file = self.lock();
+        let mut entries = Vec::new();
+        let files = (1..=self.settings.max_files).map(|n| self.rotated(n));
+        for path in std::iter::once(self.path.clone()).chain(files) {
+            let mut matching: Vec<Value> = read_entries(&path)
+                .filter(|e| tool.is_none_or(|t| e["tool"] == t))
+                .filter(|e| client.is_none_or(|c| e["client"] == c))
+                .filter(|e| {
+                    let at = e["timestamp"].as_str().and_then(|t| humantime::parse_rfc3339_weak(t).ok());
+                    at.is_some_and(|at| since.is_none_or(|s| at >= s) && until.is_none_or(|u| at <= u))
+                })
+                .collect();
+            matching.reverse();
+            entries.extend(matching);
+            if entries.len() >= limit {
+                break;
+            }
+        }
+        entries.truncate(limit);
+
+        Ok(json!({
+            "count": entries.len(),
+            "entries": entries,
+        }))
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Option<(File, u64)>> {
+        self.file.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// The log's lines as JSON, skipping any that don't parse. A missing file
+/// has no entries.
+fn read_entries(path: &Path) -> impl Iterator<Item = Value> {
+    File::open(path)
+        .into_iter()
+        .flat_map(|f| BufReader::new(f).lines())
+        .map_while(Result::ok)
+        .filter_map(|line| serde_json::from_str(&line).ok())
+}
+
+fn timestamp_argument(arguments: &Value, field: &str) -> Result<Option<SystemTime>, DaemonError> {
+    arguments[field]
+        .as_str()
+        .map(|t| {
+            humantime::parse_rfc3339_weak(t).map_err(|e| {
+                DaemonError::invalid_argument(field, &format!("not an RFC 3339 timestamp: {e}"))
+            })
+        })
+        .transpose()
+}
+
+/// Replace the values of `fields`, in any object within `value`.
+fn redact(value: &mut Value, fields: &[String]) {
+    match value {
+        Value::Object(map) => {
+            for (key, v) in map.iter_mut() {
+                if fields.contains(key) {
+                    *v = json!("[redacted]");
+                } else {
+                    redact(v, fields);
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(|v| redact(v, fields)),
+        _ => {}
+    }
+}
+
+/// The log's query tool, as a capability: permissions, rate limits and
+/// dry runs apply to it like to any other tool.
+struct AuditProvider {
+    log: Arc<AuditLog>,
+}
+
+impl CapabilityProvider for AuditProvider {
+    fn id(&self) -> &str {
+        CAPABILITY
+    }
+
+    fn name(&self) -> &str {
+        "Audit Log"
+    }
+
+    fn tools(&self) -> Vec<Tool> {
+        vec![query_tool()]
+    }
+
+    fn call(&self, tool_name: &str, arguments: &Value, _progress: &Progress) -> Option<ToolResult> {
+        match tool_name {
+            QUERY_TOOL => Some(self.log.query(arguments).map(|entries| CallToolResult::json(&entries))),
+            _ => None,
+        }
+    }
+}
+
+pub fn provider(log: Arc<AuditLog>) -> Box<dyn CapabilityProvider> {
+    Box::new(AuditProvider { log })
+}
+
+fn query_tool() -> Tool {
+    Tool {
+        name: QUERY_TOOL.into(),
+        description: "Search the daemon's audit log of tool calls, newest first. Each entry has the timestamp, client, tool, arguments (sensitive fields redacted), status and duration.".into(),
+        input_schema: json!({
+            "type": "object",
+            "properties": {
+                "tool": {
+                    "type": "string",
+                    "description": "Only calls of this tool"
+                },
+                "client": {
+                    "type": "string",
+                    "description": "Only calls from this client (its name from initialize)"
+                },
+                "since": {
+                    "type": "string",
+                    "description": "Only calls at or after this RFC 3339 timestamp (e.g. \"2025-01-31T09:00:00Z\")"
+                },
+                "until": {
+                    "type": "string",
+                    "description": "Only calls at or before this RFC 3339 timestamp"
+                },
+                "limit": {
+                    "type": "integer",
+                    "minimum": 1,
+                    "maximum": MAX_QUERY_LIMIT,
+                    "default": 50,
+                    "description": "Most entries to return"
+                }
+            }
+        }),
+        output_schema: Some(json!({
+            "type": "object",
+            "properties": {
+                "count": { "type": "integer" },
+                "entries": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "timestamp": { "type": "string" },
+                            "client": { "type": "string" },
+                            "tool": { "type": "string" },
+                            "arguments": { "type": "object" },
+                            "status": { "type": "string" },
+                            "duration_ms": { "type": "integer" }
+                        }
+                    }
+                }
+            },
+            "required": ["count", "entries"]
+        })),
+        annotations: ToolAnnotations::read_only(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn log(dir: &tempfile::TempDir, max_files: usize) -> AuditLog {
+        AuditLog::new(AuditSettings {
+            path: dir.path().join("audit.jsonl").to_string_lossy().into_owned(),
+            max_size_mb: 1,
+            max_files,
+            ..AuditSettings::default()
+        })
+    }
+
+    /// Record `count` calls of about 300 KB each.
+    fn record_large(log: &AuditLog, count: usize) {
+        let blob = "x".repeat(300 * 1024);
+        for i in 0..count {
+            log.record("client", &format!("tool{i}"), &json!({ "blob": blob }), "ok", Duration::ZERO);
+        }
+    }
+
+    #[test]
+    fn rotates_by_size_keeping_max_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let log = log(&dir, 2);
+        record_large(&log, 10);
+
+        for path in [log.path.clone(), log.rotated(1), log.rotated(2)] {
+            let size = fs::metadata(&path).unwrap().len();
+            assert!(size > 0 && size <= 1024 * 1024, "{}: {size}", path.display());
+        }
+        assert!(!log.rotated(3).exists());
+
+        // Three calls fit in a file: the oldest three went with the dropped file
+        let entries = &log.query(&json!({ "limit": 500 })).unwrap()["entries"];
+        assert_eq!(entries[0]["tool"], "tool9");
+        assert_eq!(entries[1]["tool"], "tool8");
+        assert_eq!(entries.as_array().unwrap().len(), 7);
+    }
+
+    #[test]
+    fn without_max_files_rotation_starts_over() {
+        let dir = tempfile::tempdir().unwrap();
+        let log = log(&dir, 0);
+        record_large(&log, 4);
+        assert!(!log.rotated(1).exists());
+        let entries = &log.query(&json!({ "limit": 500 })).unwrap()["entries"];
+        assert_eq!(entries.as_array().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn redacts_sensitive_fields_at_any_depth() {
+        let dir = tempfile::tempdir().unwrap();
+        let log = log(&dir, 1);
+        let arguments = json!({ "text": "secret", "steps": [{ "arguments": { "value": 1, "keep": 2 } }] });
+        log.record("client", "clipboard_write", &arguments, "ok", Duration::from_millis(5));
+
+        let entry = &log.query(&json!({})).unwrap()["entries"][0];
+        assert_eq!(
+            entry["arguments"],
+            json!({ "text": "[redacted]", "steps": [{ "arguments": { "value": "[redacted]", "keep": 2 } }] })
+        );
+        assert_eq!(entry["duration_ms"], 5);
+    }
+
+    #[test]
+    fn query_filters_by_tool_client_and_time() {
+        let dir = tempfile::tempdir().unwrap();
+        let log = log(&dir, 1);
+        log.record("a", "one", &json!({}), "ok", Duration::ZERO);
+        log.record("b", "two", &json!({}), "error", Duration::ZERO);
+        log.record("a", "two", &json!({}), "ok", Duration::ZERO);
+
+        let count = |query: Value| log.query(&query).unwrap()["count"].clone();
+        assert_eq!(count(json!({ "tool": "two" })), 2);
+        assert_eq!(count(json!({ "client": "a" })), 2);
+        assert_eq!(count(json!({ "client": "a", "tool": "two" })), 1);
+        assert_eq!(count(json!({ "limit": 1 })), 1);
+        assert_eq!(count(json!({ "since": "2000-01-01T00:00:00Z" })), 3);
+        assert_eq!(count(json!({ "until": "2000-01-01T00:00:00Z" })), 0);
+        assert_eq!(log.query(&json!({ "since": "yesterday" })).unwrap_err().kind(), "invalid_argument");
+    }
+
+    #[test]
+    fn disabled_log_records_nothing() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut settings = log(&dir, 1).settings;
+        settings.enabled = false;
+        let log = AuditLog::new(settings);
+        log.record("a", "one", &json!({}), "ok", Duration::ZERO);
+        assert!(!log.path.exists());
+    }
+}
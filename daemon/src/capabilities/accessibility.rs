@@ -1,6 +1,10 @@
 use serde_json::{json, Value};
-use crate::mcp::types::{CallToolResult, Tool};
-use super::CapabilityProvider;
+#[cfg(not(target_os = "macos"))]
+use crate::error::DaemonError;
+#[cfg(target_os = "macos")]
+use crate::platform::macos::ax_helpers::ensure_trusted;
+use crate::mcp::types::{Tool, ToolAnnotations};
+use super::{CapabilityProvider, Progress, ToolResult};
 
 pub struct AccessibilityProvider;
 
@@ -28,16 +32,21 @@ impl CapabilityProvider for AccessibilityProvider {
                     "type": "object",
                     "properties": {
                         "pid": {
-                            "type": "number",
-                            "description": "Process ID of the target application"
+                            "type": "integer",
+                            "description": "Process ID of the target application",
+                            "minimum": 1
                         },
                         "depth": {
-                            "type": "number",
-                            "description": "Maximum depth to traverse (default: 3)"
+                            "type": "integer",
+                            "default": 3,
+                            "description": "Maximum depth to traverse (default: 3)",
+                            "minimum": 0
                         }
                     },
                     "required": ["pid"]
                 }),
+                output_schema: None,
+                annotations: ToolAnnotations::read_only(),
             },
             Tool {
                 name: "ax_focused_element".into(),
@@ -46,6 +55,8 @@ impl CapabilityProvider for AccessibilityProvider {
                     "type": "object",
                     "properties": {},
                 }),
+                output_schema: None,
+                annotations: ToolAnnotations::read_only(),
             },
             Tool {
                 name: "ax_click".into(),
@@ -54,8 +65,9 @@ impl CapabilityProvider for AccessibilityProvider {
                     "type": "object",
                     "properties": {
                         "pid": {
-                            "type": "number",
-                            "description": "Process ID of the target application"
+                            "type": "integer",
+                            "description": "Process ID of the target application",
+                            "minimum": 1
                         },
                         "role": {
                             "type": "string",
@@ -68,6 +80,8 @@ impl CapabilityProvider for AccessibilityProvider {
                     },
                     "required": ["pid", "role"]
                 }),
+                output_schema: None,
+                annotations: ToolAnnotations::destructive(),
             },
             Tool {
                 name: "ax_set_value".into(),
@@ -76,8 +90,9 @@ impl CapabilityProvider for AccessibilityProvider {
                     "type": "object",
                     "properties": {
                         "pid": {
-                            "type": "number",
-                            "description": "Process ID of the target application"
+                            "type": "integer",
+                            "description": "Process ID of the target application",
+                            "minimum": 1
                         },
                         "role": {
                             "type": "string",
@@ -94,6 +109,8 @@ impl CapabilityProvider for AccessibilityProvider {
                     },
                     "required": ["pid", "role", "value"]
                 }),
+                output_schema: None,
+                annotations: ToolAnnotations::destructive().idempotent(),
             },
             Tool {
                 name: "ax_element_info".into(),
@@ -102,8 +119,9 @@ impl CapabilityProvider for AccessibilityProvider {
                     "type": "object",
                     "properties": {
                         "pid": {
-                            "type": "number",
-                            "description": "Process ID of the target application"
+                            "type": "integer",
+                            "description": "Process ID of the target application",
+                            "minimum": 1
                         },
                         "role": {
                             "type": "string",
@@ -116,6 +134,8 @@ impl CapabilityProvider for AccessibilityProvider {
                     },
                     "required": ["pid", "role"]
                 }),
+                output_schema: None,
+                annotations: ToolAnnotations::read_only(),
             },
             Tool {
                 name: "ax_find_element".into(),
@@ -124,8 +144,9 @@ impl CapabilityProvider for AccessibilityProvider {
                     "type": "object",
                     "properties": {
                         "pid": {
-                            "type": "number",
-                            "description": "Process ID of the target application"
+                            "type": "integer",
+                            "description": "Process ID of the target application",
+                            "minimum": 1
                         },
                         "role": {
                             "type": "string",
@@ -140,16 +161,49 @@ impl CapabilityProvider for AccessibilityProvider {
                             "description": "Substring to match in element value (case-insensitive)"
                         },
                         "max_results": {
-                            "type": "number",
-                            "description": "Maximum number of results to return (default: 10)"
+                            "type": "integer",
+                            "default": 10,
+                            "description": "Maximum number of results to return (default: 10)",
+                            "minimum": 1
                         },
                         "max_depth": {
-                            "type": "number",
-                            "description": "Maximum depth to search (default: 10)"
+                            "type": "integer",
+                            "default": 10,
+                            "description": "Maximum depth to search (default: 10)",
+                            "minimum": 0
                         }
                     },
                     "required": ["pid"]
                 }),
+                output_schema: Some(json!({
+                    "type": "object",
+                    "properties": {
+                        "elements": {
+                            "type": "array",
+                            "items": {
+                                "type": "object",
+                                "properties": {
+                                    "role": { "type": "string" },
+                                    "title": { "type": "string" },
+                                    "value": { "type": "string" },
+                                    "position": {
+                                        "type": "object",
+                                        "properties": { "x": { "type": "number" }, "y": { "type": "number" } }
+                                    },
+                                    "size": {
+                                        "type": "object",
+                                        "properties": { "width": { "type": "number" }, "height": { "type": "number" } }
+                                    },
+                                    "enabled": { "type": "boolean" },
+                                    "depth": { "type": "integer" }
+                                },
+                                "required": ["role", "depth"]
+                            }
+                        }
+                    },
+                    "required": ["elements"]
+                })),
+                annotations: ToolAnnotations::read_only(),
             },
             Tool {
                 name: "ax_get_actions".into(),
@@ -158,8 +212,9 @@ impl CapabilityProvider for AccessibilityProvider {
                     "type": "object",
                     "properties": {
                         "pid": {
-                            "type": "number",
-                            "description": "Process ID of the target application"
+                            "type": "integer",
+                            "description": "Process ID of the target application",
+                            "minimum": 1
                         },
                         "role": {
                             "type": "string",
@@ -172,6 +227,8 @@ impl CapabilityProvider for AccessibilityProvider {
                     },
                     "required": ["pid", "role"]
                 }),
+                output_schema: None,
+                annotations: ToolAnnotations::read_only(),
             },
             Tool {
                 name: "ax_perform_action".into(),
@@ -180,8 +237,9 @@ impl CapabilityProvider for AccessibilityProvider {
                     "type": "object",
                     "properties": {
                         "pid": {
-                            "type": "number",
-                            "description": "Process ID of the target application"
+                            "type": "integer",
+                            "description": "Process ID of the target application",
+                            "minimum": 1
                         },
                         "role": {
                             "type": "string",
@@ -198,6 +256,8 @@ impl CapabilityProvider for AccessibilityProvider {
                     },
                     "required": ["pid", "role", "action"]
                 }),
+                output_schema: None,
+                annotations: ToolAnnotations::destructive(),
             },
             Tool {
                 name: "ax_scroll".into(),
@@ -206,11 +266,13 @@ impl CapabilityProvider for AccessibilityProvider {
                     "type": "object",
                     "properties": {
                         "pid": {
-                            "type": "number",
-                            "description": "Process ID of the target application"
+                            "type": "integer",
+                            "description": "Process ID of the target application",
+                            "minimum": 1
                         },
                         "role": {
                             "type": "string",
+                            "default": "AXScrollArea",
                             "description": "AX role of the scroll area (default: \"AXScrollArea\")"
                         },
                         "title": {
@@ -219,24 +281,28 @@ impl CapabilityProvider for AccessibilityProvider {
                         },
                         "direction": {
                             "type": "string",
+                            "enum": ["up", "down", "left", "right"],
                             "description": "Scroll direction: \"up\", \"down\", \"left\", \"right\""
                         },
                         "amount": {
-                            "type": "number",
+                            "type": "integer",
+                            "default": 5,
                             "description": "Number of scroll lines (default: 5)"
                         }
                     },
                     "required": ["pid", "direction"]
                 }),
+                output_schema: None,
+                annotations: ToolAnnotations::additive(),
             },
         ]
     }
 
-    fn call(&self, tool_name: &str, arguments: &Value) -> Option<CallToolResult> {
+    fn call(&self, tool_name: &str, arguments: &Value, _progress: &Progress) -> Option<ToolResult> {
         match tool_name {
             "ax_read_tree" => {
                 let pid = arguments["pid"].as_i64().unwrap_or(0) as i32;
-                let depth = arguments["depth"].as_u64().unwrap_or(3) as usize;
+                let depth = arguments["depth"].as_u64()? as usize;
                 Some(ax_read_tree(pid, depth))
             }
             "ax_focused_element" => Some(ax_focused_element()),
@@ -264,8 +330,8 @@ impl CapabilityProvider for AccessibilityProvider {
                 let role = arguments["role"].as_str();
                 let title_pattern = arguments["title_pattern"].as_str();
                 let value_pattern = arguments["value_pattern"].as_str();
-                let max_results = arguments["max_results"].as_u64().unwrap_or(10) as usize;
-                let max_depth = arguments["max_depth"].as_u64().unwrap_or(10) as usize;
+                let max_results = arguments["max_results"].as_u64()? as usize;
+                let max_depth = arguments["max_depth"].as_u64()? as usize;
                 Some(ax_find_element(pid, role, title_pattern, value_pattern, max_results, max_depth))
             }
             "ax_get_actions" => {
@@ -283,10 +349,10 @@ impl CapabilityProvider for AccessibilityProvider {
             }
             "ax_scroll" => {
                 let pid = arguments["pid"].as_i64().unwrap_or(0) as i32;
-                let role = arguments["role"].as_str().unwrap_or("AXScrollArea");
+                let role = arguments["role"].as_str()?;
                 let title = arguments["title"].as_str();
                 let direction = arguments["direction"].as_str().unwrap_or("down");
-                let amount = arguments["amount"].as_i64().unwrap_or(5) as i32;
+                let amount = arguments["amount"].as_i64()? as i32;
                 Some(ax_scroll(pid, role, title, direction, amount))
             }
             _ => None,
@@ -297,93 +363,102 @@ impl CapabilityProvider for AccessibilityProvider {
 // ── Platform dispatch ──────────────────────────────────────────────────────
 
 #[cfg(target_os = "macos")]
-fn ax_read_tree(pid: i32, depth: usize) -> CallToolResult {
-    crate::platform::macos::accessibility::read_tree(pid, depth)
+fn ax_read_tree(pid: i32, depth: usize) -> ToolResult {
+    ensure_trusted()?;
+    Ok(crate::platform::macos::accessibility::read_tree(pid, depth))
 }
 
 #[cfg(target_os = "macos")]
-fn ax_focused_element() -> CallToolResult {
-    crate::platform::macos::accessibility::focused_element()
+fn ax_focused_element() -> ToolResult {
+    ensure_trusted()?;
+    Ok(crate::platform::macos::accessibility::focused_element())
 }
 
 #[cfg(target_os = "macos")]
-fn ax_click(pid: i32, role: &str, title: Option<&str>) -> CallToolResult {
-    crate::platform::macos::accessibility::click_element(pid, role, title)
+fn ax_click(pid: i32, role: &str, title: Option<&str>) -> ToolResult {
+    ensure_trusted()?;
+    Ok(crate::platform::macos::accessibility::click_element(pid, role, title))
 }
 
 #[cfg(target_os = "macos")]
-fn ax_set_value(pid: i32, role: &str, title: Option<&str>, value: &str) -> CallToolResult {
-    crate::platform::macos::accessibility::set_value(pid, role, title, value)
+fn ax_set_value(pid: i32, role: &str, title: Option<&str>, value: &str) -> ToolResult {
+    ensure_trusted()?;
+    Ok(crate::platform::macos::accessibility::set_value(pid, role, title, value))
 }
 
 #[cfg(target_os = "macos")]
-fn ax_element_info(pid: i32, role: &str, title: Option<&str>) -> CallToolResult {
-    crate::platform::macos::accessibility::element_info(pid, role, title)
+fn ax_element_info(pid: i32, role: &str, title: Option<&str>) -> ToolResult {
+    ensure_trusted()?;
+    Ok(crate::platform::macos::accessibility::element_info(pid, role, title))
 }
 
 #[cfg(not(target_os = "macos"))]
-fn ax_read_tree(_pid: i32, _depth: usize) -> CallToolResult {
-    CallToolResult::error("Accessibility not implemented on this platform")
+fn ax_read_tree(_pid: i32, _depth: usize) -> ToolResult {
+    Err(DaemonError::NotSupportedOnPlatform("Accessibility".into()))
 }
 
 #[cfg(not(target_os = "macos"))]
-fn ax_focused_element() -> CallToolResult {
-    CallToolResult::error("Accessibility not implemented on this platform")
+fn ax_focused_element() -> ToolResult {
+    Err(DaemonError::NotSupportedOnPlatform("Accessibility".into()))
 }
 
 #[cfg(not(target_os = "macos"))]
-fn ax_click(_pid: i32, _role: &str, _title: Option<&str>) -> CallToolResult {
-    CallToolResult::error("Accessibility not implemented on this platform")
+fn ax_click(_pid: i32, _role: &str, _title: Option<&str>) -> ToolResult {
+    Err(DaemonError::NotSupportedOnPlatform("Accessibility".into()))
 }
 
 #[cfg(not(target_os = "macos"))]
-fn ax_set_value(_pid: i32, _role: &str, _title: Option<&str>, _value: &str) -> CallToolResult {
-    CallToolResult::error("Accessibility not implemented on this platform")
+fn ax_set_value(_pid: i32, _role: &str, _title: Option<&str>, _value: &str) -> ToolResult {
+    Err(DaemonError::NotSupportedOnPlatform("Accessibility".into()))
 }
 
 #[cfg(target_os = "macos")]
-fn ax_find_element(pid: i32, role: Option<&str>, title_pattern: Option<&str>, value_pattern: Option<&str>, max_results: usize, max_depth: usize) -> CallToolResult {
-    crate::platform::macos::accessibility::find_elements(pid, role, title_pattern, value_pattern, max_results, max_depth)
+fn ax_find_element(pid: i32, role: Option<&str>, title_pattern: Option<&str>, value_pattern: Option<&str>, max_results: usize, max_depth: usize) -> ToolResult {
+    ensure_trusted()?;
+    Ok(crate::platform::macos::accessibility::find_elements(pid, role, title_pattern, value_pattern, max_results, max_depth))
 }
 
 #[cfg(target_os = "macos")]
-fn ax_get_actions(pid: i32, role: &str, title: Option<&str>) -> CallToolResult {
-    crate::platform::macos::accessibility::get_actions(pid, role, title)
+fn ax_get_actions(pid: i32, role: &str, title: Option<&str>) -> ToolResult {
+    ensure_trusted()?;
+    Ok(crate::platform::macos::accessibility::get_actions(pid, role, title))
 }
 
 #[cfg(target_os = "macos")]
-fn ax_perform_action(pid: i32, role: &str, title: Option<&str>, action: &str) -> CallToolResult {
-    crate::platform::macos::accessibility::perform_action(pid, role, title, action)
+fn ax_perform_action(pid: i32, role: &str, title: Option<&str>, action: &str) -> ToolResult {
+    ensure_trusted()?;
+    Ok(crate::platform::macos::accessibility::perform_action(pid, role, title, action))
 }
 
 #[cfg(target_os = "macos")]
-fn ax_scroll(pid: i32, role: &str, title: Option<&str>, direction: &str, amount: i32) -> CallToolResult {
-    crate::platform::macos::accessibility::scroll_element(pid, role, title, direction, amount)
+fn ax_scroll(pid: i32, role: &str, title: Option<&str>, direction: &str, amount: i32) -> ToolResult {
+    ensure_trusted()?;
+    Ok(crate::platform::macos::accessibility::scroll_element(pid, role, title, direction, amount))
 }
 
 #[cfg(not(target_os = "macos"))]
-fn ax_element_info(_pid: i32, _role: &str, _title: Option<&str>) -> CallToolResult {
-    CallToolResult::error("Accessibility not implemented on this platform")
+fn ax_element_info(_pid: i32, _role: &str, _title: Option<&str>) -> ToolResult {
+    Err(DaemonError::NotSupportedOnPlatform("Accessibility".into()))
 }
 
 #[cfg(not(target_os = "macos"))]
-fn ax_find_element(_pid: i32, _role: Option<&str>, _title_pattern: Option<&str>, _value_pattern: Option<&str>, _max_results: usize, _max_depth: usize) -> CallToolResult {
-    CallToolResult::error("Accessibility not implemented on this platform")
+fn ax_find_element(_pid: i32, _role: Option<&str>, _title_pattern: Option<&str>, _value_pattern: Option<&str>, _max_results: usize, _max_depth: usize) -> ToolResult {
+    Err(DaemonError::NotSupportedOnPlatform("Accessibility".into()))
 }
 
 #[cfg(not(target_os = "macos"))]
-fn ax_get_actions(_pid: i32, _role: &str, _title: Option<&str>) -> CallToolResult {
-    CallToolResult::error("Accessibility not implemented on this platform")
+fn ax_get_actions(_pid: i32, _role: &str, _title: Option<&str>) -> ToolResult {
+    Err(DaemonError::NotSupportedOnPlatform("Accessibility".into()))
 }
 
 #[cfg(not(target_os = "macos"))]
-fn ax_perform_action(_pid: i32, _role: &str, _title: Option<&str>, _action: &str) -> CallToolResult {
-    CallToolResult::error("Accessibility not implemented on this platform")
+fn ax_perform_action(_pid: i32, _role: &str, _title: Option<&str>, _action: &str) -> ToolResult {
+    Err(DaemonError::NotSupportedOnPlatform("Accessibility".into()))
 }
 
 #[cfg(not(target_os = "macos"))]
-fn ax_scroll(_pid: i32, _role: &str, _title: Option<&str>, _direction: &str, _amount: i32) -> CallToolResult {
-    CallToolResult::error("Accessibility not implemented on this platform")
+fn ax_scroll(_pid: i32, _role: &str, _title: Option<&str>, _direction: &str, _amount: i32) -> ToolResult {
+    Err(DaemonError::NotSupportedOnPlatform("Accessibility".into()))
 }
 
 pub fn provider() -> Box<dyn CapabilityProvider> {
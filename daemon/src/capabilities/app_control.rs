@@ -1,7 +1,8 @@
 use serde_json::{json, Value};
 
-use crate::mcp::types::{CallToolResult, Tool};
-use super::CapabilityProvider;
+use crate::error::DaemonError;
+use crate::mcp::types::{Tool, ToolAnnotations};
+use super::{matching, observed, planned, CapabilityProvider, Progress, ToolResult};
 
 pub struct AppControlProvider;
 
@@ -29,6 +30,26 @@ impl CapabilityProvider for AppControlProvider {
                     "type": "object",
                     "properties": {},
                 }),
+                output_schema: Some(json!({
+                    "type": "object",
+                    "properties": {
+                        "apps": {
+                            "type": "array",
+                            "items": {
+                                "type": "object",
+                                "properties": {
+                                    "name": { "type": "string" },
+                                    "pid": { "type": "integer" },
+                                    "bundle_id": { "type": "string" },
+                                    "frontmost": { "type": "boolean" }
+                                },
+                                "required": ["name", "pid", "bundle_id", "frontmost"]
+                            }
+                        }
+                    },
+                    "required": ["apps"]
+                })),
+                annotations: ToolAnnotations::read_only(),
             },
             Tool {
                 name: "app_launch".into(),
@@ -43,6 +64,8 @@ impl CapabilityProvider for AppControlProvider {
                     },
                     "required": ["name"]
                 }),
+                output_schema: None,
+                annotations: ToolAnnotations::additive().idempotent(),
             },
             Tool {
                 name: "app_quit".into(),
@@ -56,11 +79,14 @@ impl CapabilityProvider for AppControlProvider {
                         },
                         "force": {
                             "type": "boolean",
+                            "default": false,
                             "description": "Force kill the application (default: false)"
                         }
                     },
                     "required": ["name"]
                 }),
+                output_schema: None,
+                annotations: ToolAnnotations::destructive().idempotent(),
             },
             Tool {
                 name: "app_info".into(),
@@ -75,78 +101,125 @@ impl CapabilityProvider for AppControlProvider {
                     },
                     "required": ["name"]
                 }),
+                output_schema: None,
+                annotations: ToolAnnotations::read_only(),
             },
         ]
     }
 
-    fn call(&self, tool_name: &str, arguments: &Value) -> Option<CallToolResult> {
+    fn call(&self, tool_name: &str, arguments: &Value, _progress: &Progress) -> Option<ToolResult> {
         match tool_name {
             "app_list" => Some(app_list()),
             "app_launch" => {
                 let name = arguments["name"].as_str().unwrap_or("");
                 if name.is_empty() {
-                    return Some(CallToolResult::error("Missing required parameter: name"));
+                    return Some(Err(DaemonError::invalid_argument("name", "is required")));
                 }
                 Some(app_launch(name))
             }
             "app_quit" => {
                 let name = arguments["name"].as_str().unwrap_or("");
                 if name.is_empty() {
-                    return Some(CallToolResult::error("Missing required parameter: name"));
+                    return Some(Err(DaemonError::invalid_argument("name", "is required")));
                 }
-                let force = arguments["force"].as_bool().unwrap_or(false);
+                let force = arguments["force"].as_bool()?;
                 Some(app_quit(name, force))
             }
             "app_info" => {
                 let name = arguments["name"].as_str().unwrap_or("");
                 if name.is_empty() {
-                    return Some(CallToolResult::error("Missing required parameter: name"));
+                    return Some(Err(DaemonError::invalid_argument("name", "is required")));
                 }
                 Some(app_info(name))
             }
             _ => None,
         }
     }
+
+    /// Look the app up among the running ones by its name, which is how
+    /// both quitting and launching find it.
+    fn plan(&self, tool_name: &str, arguments: &Value) -> Option<ToolResult> {
+        let name = arguments["name"].as_str().unwrap_or("");
+        if name.is_empty() {
+            return Some(Err(DaemonError::invalid_argument("name", "is required")));
+        }
+        let apps = match observed(app_list()) {
+            Ok(apps) => apps,
+            Err(failed) => return Some(failed),
+        };
+        let app = apps["apps"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .find(|app| app["name"].as_str().is_some_and(|n| n.eq_ignore_ascii_case(name)))
+            .cloned();
+
+        let effect = match (tool_name, &app) {
+            ("app_launch", None) => format!("Would launch '{name}'"),
+            ("app_launch", Some(_)) => format!("'{name}' is already running; it would be brought to the front"),
+            ("app_quit", None) => format!("'{name}' is not running; nothing would quit"),
+            ("app_quit", Some(app)) if arguments["force"].as_bool() == Some(true) => {
+                format!("Would force-kill '{name}' (pid {}), losing unsaved changes", app["pid"])
+            }
+            ("app_quit", Some(app)) => format!("Would ask '{name}' (pid {}) to quit", app["pid"]),
+            _ => return None,
+        };
+        Some(planned(effect, app.unwrap_or(Value::Null)))
+    }
+
+    fn complete(&self, tool_name: &str, argument: &str, prefix: &str) -> Vec<String> {
+        if !matches!(tool_name, "app_quit" | "app_info") || argument != "name" {
+            return Vec::new();
+        }
+        let apps = app_list().ok().and_then(|r| r.structured_content).unwrap_or_default();
+        let names = apps["apps"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|app| app["name"].as_str())
+            .map(str::to_string);
+        matching(names, prefix)
+    }
 }
 
 #[cfg(target_os = "macos")]
-fn app_list() -> CallToolResult {
+fn app_list() -> ToolResult {
     crate::platform::macos::app_control::list_apps()
 }
 
 #[cfg(target_os = "macos")]
-fn app_launch(name: &str) -> CallToolResult {
+fn app_launch(name: &str) -> ToolResult {
     crate::platform::macos::app_control::launch_app(name)
 }
 
 #[cfg(target_os = "macos")]
-fn app_quit(name: &str, force: bool) -> CallToolResult {
+fn app_quit(name: &str, force: bool) -> ToolResult {
     crate::platform::macos::app_control::quit_app(name, force)
 }
 
 #[cfg(target_os = "macos")]
-fn app_info(name: &str) -> CallToolResult {
+fn app_info(name: &str) -> ToolResult {
     crate::platform::macos::app_control::app_info(name)
 }
 
 #[cfg(not(target_os = "macos"))]
-fn app_list() -> CallToolResult {
-    CallToolResult::error("App control not implemented on this platform")
+fn app_list() -> ToolResult {
+    Err(DaemonError::NotSupportedOnPlatform("App control".into()))
 }
 
 #[cfg(not(target_os = "macos"))]
-fn app_launch(_name: &str) -> CallToolResult {
-    CallToolResult::error("App control not implemented on this platform")
+fn app_launch(_name: &str) -> ToolResult {
+    Err(DaemonError::NotSupportedOnPlatform("App control".into()))
 }
 
 #[cfg(not(target_os = "macos"))]
-fn app_quit(_name: &str, _force: bool) -> CallToolResult {
-    CallToolResult::error("App control not implemented on this platform")
+fn app_quit(_name: &str, _force: bool) -> ToolResult {
+    Err(DaemonError::NotSupportedOnPlatform("App control".into()))
 }
 
 #[cfg(not(target_os = "macos"))]
-fn app_info(_name: &str) -> CallToolResult {
-    CallToolResult::error("App control not implemented on this platform")
+fn app_info(_name: &str) -> ToolResult {
+    Err(DaemonError::NotSupportedOnPlatform("App control".into()))
 }
 
 pub fn provider() -> Box<dyn CapabilityProvider> {
@@ -1,6 +1,8 @@
 use serde_json::{json, Value};
-use crate::mcp::types::{CallToolResult, Tool};
-use super::CapabilityProvider;
+#[cfg(not(target_os = "macos"))]
+use crate::error::DaemonError;
+use crate::mcp::types::{Tool, ToolAnnotations};
+use super::{observed, planned, CapabilityProvider, Progress, ToolResult};
 
 pub struct AudioProvider;
 
@@ -28,6 +30,8 @@ impl CapabilityProvider for AudioProvider {
                     "type": "object",
                     "properties": {},
                 }),
+                output_schema: None,
+                annotations: ToolAnnotations::read_only(),
             },
             Tool {
                 name: "audio_set_volume".into(),
@@ -36,7 +40,7 @@ impl CapabilityProvider for AudioProvider {
                     "type": "object",
                     "properties": {
                         "level": {
-                            "type": "number",
+                            "type": "integer",
                             "description": "Volume level from 0 to 100",
                             "minimum": 0,
                             "maximum": 100
@@ -44,6 +48,8 @@ impl CapabilityProvider for AudioProvider {
                     },
                     "required": ["level"]
                 }),
+                output_schema: None,
+                annotations: ToolAnnotations::additive().idempotent(),
             },
             Tool {
                 name: "audio_mute".into(),
@@ -58,6 +64,8 @@ impl CapabilityProvider for AudioProvider {
                     },
                     "required": ["muted"]
                 }),
+                output_schema: None,
+                annotations: ToolAnnotations::additive().idempotent(),
             },
             Tool {
                 name: "audio_devices".into(),
@@ -66,11 +74,13 @@ impl CapabilityProvider for AudioProvider {
                     "type": "object",
                     "properties": {},
                 }),
+                output_schema: None,
+                annotations: ToolAnnotations::read_only(),
             },
         ]
     }
 
-    fn call(&self, tool_name: &str, arguments: &Value) -> Option<CallToolResult> {
+    fn call(&self, tool_name: &str, arguments: &Value, _progress: &Progress) -> Option<ToolResult> {
         match tool_name {
             "audio_get_volume" => Some(audio_get_volume()),
             "audio_set_volume" => {
@@ -85,46 +95,68 @@ impl CapabilityProvider for AudioProvider {
             _ => None,
         }
     }
+
+    /// Compare the change with the current volume settings.
+    fn plan(&self, tool_name: &str, arguments: &Value) -> Option<ToolResult> {
+        let settings = match observed(audio_get_volume()) {
+            Ok(settings) => settings,
+            Err(failed) => return Some(failed),
+        };
+        let effect = match tool_name {
+            "audio_set_volume" => {
+                let level = (arguments["level"].as_u64().unwrap_or(50) as u8).min(100);
+                format!("Would set the output volume from {} to {level}", settings["output_volume"])
+            }
+            "audio_mute" => match (arguments["muted"].as_bool().unwrap_or(true), settings["output_muted"].as_bool()) {
+                (true, Some(true)) => "Output is already muted; nothing would change".to_string(),
+                (false, Some(false)) => "Output is already unmuted; nothing would change".to_string(),
+                (true, _) => "Would mute the output".to_string(),
+                (false, _) => "Would unmute the output".to_string(),
+            },
+            _ => return None,
+        };
+        Some(planned(effect, settings))
+    }
 }
 
 #[cfg(target_os = "macos")]
-fn audio_get_volume() -> CallToolResult {
+fn audio_get_volume() -> ToolResult {
     crate::platform::macos::audio::get_volume()
 }
 
 #[cfg(target_os = "macos")]
-fn audio_set_volume(level: u8) -> CallToolResult {
+fn audio_set_volume(level: u8) -> ToolResult {
     crate::platform::macos::audio::set_volume(level)
 }
 
 #[cfg(target_os = "macos")]
-fn audio_mute(muted: bool) -> CallToolResult {
+fn audio_mute(muted: bool) -> ToolResult {
     crate::platform::macos::audio::mute(muted)
 }
 
 #[cfg(target_os = "macos")]
-fn audio_devices() -> CallToolResult {
+fn audio_devices() -> ToolResult {
     crate::platform::macos::audio::devices()
 }
 
 #[cfg(not(target_os = "macos"))]
-fn audio_get_volume() -> CallToolResult {
-    CallToolResult::error("Audio not implemented on this platform")
+fn audio_get_volume() -> ToolResult {
+    Err(DaemonError::NotSupportedOnPlatform("Audio".into()))
 }
 
 #[cfg(not(target_os = "macos"))]
-fn audio_set_volume(_level: u8) -> CallToolResult {
-    CallToolResult::error("Audio not implemented on this platform")
+fn audio_set_volume(_level: u8) -> ToolResult {
+    Err(DaemonError::NotSupportedOnPlatform("Audio".into()))
 }
 
 #[cfg(not(target_os = "macos"))]
-fn audio_mute(_muted: bool) -> CallToolResult {
-    CallToolResult::error("Audio not implemented on this platform")
+fn audio_mute(_muted: bool) -> ToolResult {
+    Err(DaemonError::NotSupportedOnPlatform("Audio".into()))
 }
 
 #[cfg(not(target_os = "macos"))]
-fn audio_devices() -> CallToolResult {
-    CallToolResult::error("Audio not implemented on this platform")
+fn audio_devices() -> ToolResult {
+    Err(DaemonError::NotSupportedOnPlatform("Audio".into()))
 }
 
 pub fn provider() -> Box<dyn CapabilityProvider> {
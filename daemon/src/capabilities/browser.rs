@@ -1,6 +1,8 @@
 use serde_json::{json, Value};
-use crate::mcp::types::{CallToolResult, Tool};
-use super::CapabilityProvider;
+#[cfg(not(target_os = "macos"))]
+use crate::error::DaemonError;
+use crate::mcp::types::{Resource, ResourceContents, Tool, ToolAnnotations};
+use super::{tool_resource, CapabilityProvider, Progress, ToolResult};
 
 pub struct BrowserProvider;
 
@@ -38,6 +40,8 @@ impl CapabilityProvider for BrowserProvider {
                     },
                     "required": ["url"]
                 }),
+                output_schema: None,
+                annotations: ToolAnnotations::additive().open_world(),
             },
             Tool {
                 name: "browser_tabs".into(),
@@ -51,6 +55,8 @@ impl CapabilityProvider for BrowserProvider {
                         }
                     },
                 }),
+                output_schema: None,
+                annotations: ToolAnnotations::read_only(),
             },
             Tool {
                 name: "browser_active_tab".into(),
@@ -64,11 +70,13 @@ impl CapabilityProvider for BrowserProvider {
                         }
                     },
                 }),
+                output_schema: None,
+                annotations: ToolAnnotations::read_only(),
             },
         ]
     }
 
-    fn call(&self, tool_name: &str, arguments: &Value) -> Option<CallToolResult> {
+    fn call(&self, tool_name: &str, arguments: &Value, _progress: &Progress) -> Option<ToolResult> {
         match tool_name {
             "browser_open" => {
                 let url = arguments["url"].as_str().unwrap_or("");
@@ -86,36 +94,53 @@ impl CapabilityProvider for BrowserProvider {
             _ => None,
         }
     }
+
+    fn resources(&self) -> Vec<Resource> {
+        vec![Resource {
+            uri: "familiar://browser/active-tab".into(),
+            name: "browser_active_tab".into(),
+            description: "Title and URL of the active browser tab.".into(),
+            mime_type: "application/json".into(),
+            tool: "browser_active_tab",
+        }]
+    }
+
+    fn read_resource(&self, uri: &str) -> Option<Result<ResourceContents, String>> {
+        match uri {
+            "familiar://browser/active-tab" => Some(tool_resource(uri, "application/json", browser_active_tab(None))),
+            _ => None,
+        }
+    }
 }
 
 #[cfg(target_os = "macos")]
-fn browser_open(url: &str, browser: Option<&str>) -> CallToolResult {
+fn browser_open(url: &str, browser: Option<&str>) -> ToolResult {
     crate::platform::macos::browser::open_url(url, browser)
 }
 
 #[cfg(target_os = "macos")]
-fn browser_tabs(browser: Option<&str>) -> CallToolResult {
+fn browser_tabs(browser: Option<&str>) -> ToolResult {
     crate::platform::macos::browser::get_tabs(browser)
 }
 
 #[cfg(target_os = "macos")]
-fn browser_active_tab(browser: Option<&str>) -> CallToolResult {
+fn browser_active_tab(browser: Option<&str>) -> ToolResult {
     crate::platform::macos::browser::get_active_tab(browser)
 }
 
 #[cfg(not(target_os = "macos"))]
-fn browser_open(_url: &str, _browser: Option<&str>) -> CallToolResult {
-    CallToolResult::error("Browser not implemented on this platform")
+fn browser_open(_url: &str, _browser: Option<&str>) -> ToolResult {
+    Err(DaemonError::NotSupportedOnPlatform("Browser automation".into()))
 }
 
 #[cfg(not(target_os = "macos"))]
-fn browser_tabs(_browser: Option<&str>) -> CallToolResult {
-    CallToolResult::error("Browser not implemented on this platform")
+fn browser_tabs(_browser: Option<&str>) -> ToolResult {
+    Err(DaemonError::NotSupportedOnPlatform("Browser automation".into()))
 }
 
 #[cfg(not(target_os = "macos"))]
-fn browser_active_tab(_browser: Option<&str>) -> CallToolResult {
-    CallToolResult::error("Browser not implemented on this platform")
+fn browser_active_tab(_browser: Option<&str>) -> ToolResult {
+    Err(DaemonError::NotSupportedOnPlatform("Browser automation".into()))
 }
 
 pub fn provider() -> Box<dyn CapabilityProvider> {
@@ -1,6 +1,8 @@
 use serde_json::{json, Value};
-use crate::mcp::types::{CallToolResult, Tool};
-use super::CapabilityProvider;
+#[cfg(not(target_os = "macos"))]
+use crate::error::DaemonError;
+use crate::mcp::types::{Resource, ResourceContents, Tool, ToolAnnotations};
+use super::{tool_resource, CapabilityProvider, Progress, ToolResult};
 
 pub struct ClipboardProvider;
 
@@ -28,6 +30,8 @@ impl CapabilityProvider for ClipboardProvider {
                     "type": "object",
                     "properties": {},
                 }),
+                output_schema: None,
+                annotations: ToolAnnotations::read_only(),
             },
             Tool {
                 name: "clipboard_write".into(),
@@ -42,11 +46,13 @@ impl CapabilityProvider for ClipboardProvider {
                     },
                     "required": ["text"]
                 }),
+                output_schema: None,
+                annotations: ToolAnnotations::destructive().idempotent(),
             },
         ]
     }
 
-    fn call(&self, tool_name: &str, arguments: &Value) -> Option<CallToolResult> {
+    fn call(&self, tool_name: &str, arguments: &Value, _progress: &Progress) -> Option<ToolResult> {
         match tool_name {
             "clipboard_read" => Some(clipboard_read()),
             "clipboard_write" => {
@@ -56,26 +62,43 @@ impl CapabilityProvider for ClipboardProvider {
             _ => None,
         }
     }
+
+    fn resources(&self) -> Vec<Resource> {
+        vec![Resource {
+            uri: "familiar://clipboard".into(),
+            name: "clipboard".into(),
+            description: "Current clipboard contents (text only).".into(),
+            mime_type: "text/plain".into(),
+            tool: "clipboard_read",
+        }]
+    }
+
+    fn read_resource(&self, uri: &str) -> Option<Result<ResourceContents, String>> {
+        match uri {
+            "familiar://clipboard" => Some(tool_resource(uri, "text/plain", clipboard_read())),
+            _ => None,
+        }
+    }
 }
 
 #[cfg(target_os = "macos")]
-fn clipboard_read() -> CallToolResult {
+fn clipboard_read() -> ToolResult {
     crate::platform::macos::clipboard::read()
 }
 
 #[cfg(target_os = "macos")]
-fn clipboard_write(text: &str) -> CallToolResult {
+fn clipboard_write(text: &str) -> ToolResult {
     crate::platform::macos::clipboard::write(text)
 }
 
 #[cfg(not(target_os = "macos"))]
-fn clipboard_read() -> CallToolResult {
-    CallToolResult::error("Clipboard not implemented on this platform")
+fn clipboard_read() -> ToolResult {
+    Err(DaemonError::NotSupportedOnPlatform("Clipboard".into()))
 }
 
 #[cfg(not(target_os = "macos"))]
-fn clipboard_write(_text: &str) -> CallToolResult {
-    CallToolResult::error("Clipboard not implemented on this platform")
+fn clipboard_write(_text: &str) -> ToolResult {
+    Err(DaemonError::NotSupportedOnPlatform("Clipboard".into()))
 }
 
 pub fn provider() -> Box<dyn CapabilityProvider> {
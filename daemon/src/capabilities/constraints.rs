@@ -0,0 +1,286 @@
+use std::collections::BTreeMap;
+
+use serde_json::Value;
+
+use crate::error::DaemonError;
+use crate::permissions::{Constraint, ValueKind};
+use crate::platform::keys;
+
+/// Modifier spellings the input backend accepts, by canonical name, in
+/// the order a normalized combo lists them.
+const MODIFIERS: &[(&str, &[&str])] = &[
+    ("cmd", &["cmd", "command", "meta", "super", "⌘"]),
+    ("ctrl", &["ctrl", "control", "⌃"]),
+    ("alt", &["alt", "option", "opt", "⌥"]),
+    ("shift", &["shift", "⇧"]),
+    ("fn", &["fn", "function"]),
+];
+
+/// Check the arguments of a tool call against its constraints from
+/// permissions.toml, failing on the first argument that breaks one.
+pub fn check(constraints: &BTreeMap<String, Constraint>, arguments: &Value) -> Result<(), DaemonError> {
+    for (field, constraint) in constraints {
+        let value = match &arguments[field] {
+            Value::Null => continue,
+            Value::String(s) => s.clone(),
+            other => other.to_string(),
+        };
+        let violation = |rule, patterns: &[String], value: &str| DaemonError::PolicyViolation {
+            field: field.clone(),
+            what: what(constraint.kind),
+            rule,
+            patterns: patterns.to_vec(),
+            value: value.into(),
+        };
+
+        // A value that can't be read as its kind passes no rule
+        let Some(values) = read(constraint.kind, &value) else {
+            let (rule, patterns) = match &constraint.allowed {
+                Some(allowed) => ("in", allowed.as_slice()),
+                None => ("not_in", constraint.not_in.as_slice()),
+            };
+            return Err(violation(rule, patterns, &value));
+        };
+        for value in &values {
+            if let Some(pattern) = constraint.not_in.iter().find(|p| matches(constraint.kind, p, value)) {
+                return Err(violation("not_in", std::slice::from_ref(pattern), value));
+            }
+            if let Some(allowed) = &constraint.allowed
+                && !allowed.iter().any(|p| matches(constraint.kind, p, value))
+            {
+                return Err(violation("in", allowed, value));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// How a tool's constraints read at the end of its description.
+pub fn describe(constraints: &BTreeMap<String, Constraint>) -> Option<String> {
+    let rules: Vec<String> = constraints
+        .iter()
+        .flat_map(|(field, c)| {
+            let what = what(c.kind);
+            let allowed = c
+                .allowed
+                .as_ref()
+                .map(|a| format!("{field} must be a {what} matching {}", a.join(", ")));
+            let denied = (!c.not_in.is_empty())
+                .then(|| format!("{field} may not be a {what} matching {}", c.not_in.join(", ")));
+            allowed.into_iter().chain(denied)
+        })
+        .collect();
+    (!rules.is_empty()).then(|| format!("Restricted: {}.", rules.join("; ")))
+}
+
+fn what(kind: ValueKind) -> &'static str {
+    match kind {
+        ValueKind::Text => "value",
+        ValueKind::Domain => "domain",
+        ValueKind::KeyCombo => "key combo",
+        ValueKind::Program => "program",
+    }
+}
+
+/// The values to match for an argument, or None if it can't be read as
+/// `kind` (a URL without a host, a command line with command or process
+/// substitution).
+fn read(kind: ValueKind, value: &str) -> Option<Vec<String>> {
+    match kind {
+        ValueKind::Text => Some(vec![value.to_string()]),
+        ValueKind::Domain => domain(value).map(|d| vec![d]),
+        ValueKind::KeyCombo => Some(vec![key_combo(value)]),
+        ValueKind::Program => programs(value),
+    }
+}
+
+fn matches(kind: ValueKind, pattern: &str, value: &str) -> bool {
+    let pattern = pattern.to_lowercase();
+    let value = value.to_lowercase();
+    match kind {
+        ValueKind::Text | ValueKind::Program => wildcard(pattern.as_bytes(), value.as_bytes()),
+        // The domain or any domain it is a subdomain of
+        ValueKind::Domain => {
+            let mut domain = value.as_str();
+            loop {
+                if wildcard(pattern.as_bytes(), domain.as_bytes()) {
+                    return true;
+                }
+                match domain.split_once('.') {
+                    Some((_, parent)) => domain = parent,
+                    None => return false,
+                }
+            }
+        }
+        ValueKind::KeyCombo => wildcard(key_combo(&pattern).as_bytes(), value.as_bytes()),
+    }
+}
+
+fn wildcard(pattern: &[u8], text: &[u8]) -> bool {
+    match pattern {
+        [] => text.is_empty(),
+        [b'*', rest @ ..] => (0..=text.len()).any(|i| wildcard(rest, &text[i..])),
+        [b'?', rest @ ..] => !text.is_empty() && wildcard(rest, &text[1..]),
+        [c, rest @ ..] => text.first() == Some(c) && wildcard(rest, &text[1..]),
+    }
+}
+
+/// The host of a URL (or of `host/path` without a scheme), lowercase.
+fn domain(url: &str) -> Option<String> {
+    let rest = url.split_once("://").map_or(url, |(_, rest)| rest);
+    let authority = rest.split(['/', '?', '#']).next()?;
+    let host = authority.rsplit('@').next()?;
+    let host = match host.strip_prefix('[') {
+        Some(ipv6) => ipv6.split(']').next()?,
+        None => host.split(':').next()?,
+    };
+    let host = host.trim_end_matches('.').to_lowercase();
+    (!host.is_empty()).then_some(host)
+}
+
+/// A combo with its modifiers and key spelled and ordered one way, e.g.
+/// `Shift+Command+S` as `cmd+shift+s` and `cmd+-` as `cmd+minus`. Like
+/// the input backend, names that aren't modifiers are ignored unless last.
+fn key_combo(combo: &str) -> String {
+    let parts: Vec<String> = combo.split('+').map(|p| p.trim().to_lowercase()).collect();
+    let Some((key, modifiers)) = parts.split_last() else {
+        return String::new();
+    };
+    let mut normalized: Vec<&str> = MODIFIERS
+        .iter()
+        .filter(|(_, names)| modifiers.iter().any(|m| names.contains(&m.as_str())))
+        .map(|(name, _)| *name)
+        .collect();
+    let key = keys::canonical_name(key).unwrap_or(key);
+    let key = MODIFIERS
+        .iter()
+        .find(|(_, names)| names.contains(&key))
+        .map_or(key, |(name, _)| name);
+    normalized.push(key);
+    normalized.join("+")
+}
+
+/// The program each command of a shell command line runs, without its
+/// directory: `cd src && make test | tee log` runs cd, make and tee.
+fn programs(command: &str) -> Option<Vec<String>> {
+    if command.contains('`') || ["$(", "<(", ">(", "=("].iter().any(|s| command.contains(s)) {
+        return None;
+    }
+    let programs = command
+        .split([';', '&', '|', '\n'])
+        .filter_map(|segment| {
+            segment
+                .split_whitespace()
+                .map(|word| word.trim_start_matches(['(', '{']).trim_matches(['"', '\'']))
+                .find(|word| !word.is_empty() && !is_assignment(word))
+        })
+        .map(|program| program.rsplit('/').next().unwrap_or(program).to_string())
+        .collect();
+    Some(programs)
+}
+
+/// Whether a word is an environment assignment (`FOO=bar`) in front of a
+/// command.
+fn is_assignment(word: &str) -> bool {
+    word.split_once('=').is_some_and(|(name, _)| {
+        !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn rules(toml: &str) -> BTreeMap<String, Constraint> {
+        toml::from_str(toml).unwrap()
+    }
+
+    fn rule(check: Result<(), DaemonError>) -> Option<&'static str> {
+        match check {
+            Ok(()) => None,
+            Err(DaemonError::PolicyViolation { rule, .. }) => Some(rule),
+            Err(e) => panic!("unexpected error: {e}"),
+        }
+    }
+
+    #[test]
+    fn parses_constraints() {
+        let rules = rules(
+            r#"
+            url = { as = "domain", in = ["example.com"] }
+            text = { not_in = ["*password*"] }
+            "#,
+        );
+        assert_eq!(rules["url"].kind, ValueKind::Domain);
+        assert_eq!(rules["url"].allowed.as_deref(), Some(&["example.com".to_string()][..]));
+        assert_eq!(rules["text"].kind, ValueKind::Text);
+        assert_eq!(rules["text"].not_in, ["*password*"]);
+        assert!(toml::from_str::<BTreeMap<String, Constraint>>("x = { as = \"domain\", allow = [] }").is_err());
+        assert!(toml::from_str::<BTreeMap<String, Constraint>>("x = { as = \"path\" }").is_err());
+    }
+
+    #[test]
+    fn checks_text_ignoring_case() {
+        let rules = rules(r#"text = { in = ["hello*"], not_in = ["*secret*"] }"#);
+        assert_eq!(rule(check(&rules, &json!({ "text": "Hello world" }))), None);
+        assert_eq!(rule(check(&rules, &json!({ "text": "hello SECRET" }))), Some("not_in"));
+        assert_eq!(rule(check(&rules, &json!({ "text": "bye" }))), Some("in"));
+        assert_eq!(rule(check(&rules, &json!({}))), None);
+    }
+
+    #[test]
+    fn reads_domains_of_urls() {
+        assert_eq!(domain("https://user@Docs.Example.com:8080/a?b").as_deref(), Some("docs.example.com"));
+        assert_eq!(domain("example.com./path").as_deref(), Some("example.com"));
+        assert_eq!(domain("http://[::1]:80/").as_deref(), Some("::1"));
+        assert_eq!(domain("file:///etc/passwd"), None);
+
+        let rules = rules(r#"url = { as = "domain", in = ["example.com"] }"#);
+        assert_eq!(rule(check(&rules, &json!({ "url": "https://docs.example.com/x" }))), None);
+        assert_eq!(rule(check(&rules, &json!({ "url": "https://example.com.evil.io" }))), Some("in"));
+        assert_eq!(rule(check(&rules, &json!({ "url": "file:///etc/passwd" }))), Some("in"));
+    }
+
+    #[test]
+    fn normalizes_key_combos() {
+        assert_eq!(key_combo("Shift+Command+S"), "cmd+shift+s");
+        assert_eq!(key_combo("⌥ + ⌘ + Escape"), "cmd+alt+escape");
+        assert_eq!(key_combo("ctrl+enter"), "ctrl+return");
+        for (alias, name) in [("-", "minus"), ("=", "equal"), ("`", "backtick"), ("/", "slash")] {
+            assert_eq!(key_combo(&format!("cmd+{alias}")), key_combo(&format!("command+{name}")));
+        }
+
+        let rules = rules(r#"keys = { as = "key_combo", not_in = ["cmd+q", "cmd+minus"] }"#);
+        assert_eq!(rule(check(&rules, &json!({ "keys": "Command+Q" }))), Some("not_in"));
+        assert_eq!(rule(check(&rules, &json!({ "keys": "cmd+-" }))), Some("not_in"));
+        assert_eq!(rule(check(&rules, &json!({ "keys": "cmd+w" }))), None);
+    }
+
+    #[test]
+    fn reads_the_programs_of_command_lines() {
+        assert_eq!(
+            programs("cd src && FOO=1 /usr/bin/make test | tee log; (echo done)").unwrap(),
+            ["cd", "make", "tee", "echo"]
+        );
+        assert_eq!(programs("\"ls\" -la").unwrap(), ["ls"]);
+        for command in ["echo `rm -rf ~`", "echo $(rm x)", "diff <(ls a) b", "tee >(rm x)", "print =(rm x)"] {
+            assert_eq!(programs(command), None, "{command}");
+        }
+
+        let rules = rules(r#"command = { as = "program", in = ["ls", "git"] }"#);
+        assert_eq!(rule(check(&rules, &json!({ "command": "git status && ls" }))), None);
+        assert_eq!(rule(check(&rules, &json!({ "command": "ls; rm -rf /" }))), Some("in"));
+        assert_eq!(rule(check(&rules, &json!({ "command": "ls <(rm x)" }))), Some("in"));
+    }
+
+    #[test]
+    fn describes_constraints() {
+        let rules = rules(r#"url = { as = "domain", in = ["a.com", "b.com"], not_in = ["x.a.com"] }"#);
+        assert_eq!(
+            describe(&rules).unwrap(),
+            "Restricted: url must be a domain matching a.com, b.com; url may not be a domain matching x.a.com."
+        );
+        assert_eq!(describe(&BTreeMap::new()), None);
+    }
+}
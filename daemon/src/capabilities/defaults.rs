@@ -1,6 +1,8 @@
 use serde_json::{json, Value};
-use crate::mcp::types::{CallToolResult, Tool};
-use super::CapabilityProvider;
+#[cfg(not(target_os = "macos"))]
+use crate::error::DaemonError;
+use crate::mcp::types::{Tool, ToolAnnotations};
+use super::{matching, CapabilityProvider, Progress, ToolResult};
 
 pub struct DefaultsProvider;
 
@@ -38,6 +40,8 @@ impl CapabilityProvider for DefaultsProvider {
                     },
                     "required": ["domain"]
                 }),
+                output_schema: None,
+                annotations: ToolAnnotations::read_only(),
             },
             Tool {
                 name: "defaults_write".into(),
@@ -65,6 +69,8 @@ impl CapabilityProvider for DefaultsProvider {
                     },
                     "required": ["domain", "key", "value_type", "value"]
                 }),
+                output_schema: None,
+                annotations: ToolAnnotations::destructive().idempotent(),
             },
             Tool {
                 name: "defaults_delete".into(),
@@ -83,6 +89,8 @@ impl CapabilityProvider for DefaultsProvider {
                     },
                     "required": ["domain", "key"]
                 }),
+                output_schema: None,
+                annotations: ToolAnnotations::destructive().idempotent(),
             },
             Tool {
                 name: "defaults_domains".into(),
@@ -91,6 +99,8 @@ impl CapabilityProvider for DefaultsProvider {
                     "type": "object",
                     "properties": {},
                 }),
+                output_schema: None,
+                annotations: ToolAnnotations::read_only(),
             },
             Tool {
                 name: "defaults_find".into(),
@@ -105,11 +115,13 @@ impl CapabilityProvider for DefaultsProvider {
                     },
                     "required": ["keyword"]
                 }),
+                output_schema: None,
+                annotations: ToolAnnotations::read_only(),
             },
         ]
     }
 
-    fn call(&self, tool_name: &str, arguments: &Value) -> Option<CallToolResult> {
+    fn call(&self, tool_name: &str, arguments: &Value, _progress: &Progress) -> Option<ToolResult> {
         match tool_name {
             "defaults_read" => {
                 let domain = arguments["domain"].as_str().unwrap_or("");
@@ -136,56 +148,90 @@ impl CapabilityProvider for DefaultsProvider {
             _ => None,
         }
     }
+
+    fn plan(&self, tool_name: &str, arguments: &Value) -> Option<ToolResult> {
+        let domain = arguments["domain"].as_str().unwrap_or("");
+        let key = arguments["key"].as_str().unwrap_or("");
+        match tool_name {
+            "defaults_write" => {
+                let value_type = arguments["value_type"].as_str().unwrap_or("");
+                let value = arguments["value"].as_str().unwrap_or("");
+                Some(defaults_plan(domain, key, Some((value_type, value))))
+            }
+            "defaults_delete" => Some(defaults_plan(domain, key, None)),
+            _ => None,
+        }
+    }
+
+    fn complete(&self, tool_name: &str, argument: &str, prefix: &str) -> Vec<String> {
+        if !matches!(tool_name, "defaults_read" | "defaults_write" | "defaults_delete") || argument != "domain" {
+            return Vec::new();
+        }
+        let listing = defaults_domains().map(|r| r.text_output()).unwrap_or_default();
+        let domains: Vec<String> = serde_json::from_str(&listing).unwrap_or_default();
+        // `defaults domains` leaves out the global domain
+        matching(std::iter::once("NSGlobalDomain".to_string()).chain(domains), prefix)
+    }
 }
 
 #[cfg(target_os = "macos")]
-fn defaults_read(domain: &str, key: Option<&str>) -> CallToolResult {
+fn defaults_read(domain: &str, key: Option<&str>) -> ToolResult {
     crate::platform::macos::defaults::read_default(domain, key)
 }
 
 #[cfg(target_os = "macos")]
-fn defaults_write(domain: &str, key: &str, value_type: &str, value: &str) -> CallToolResult {
+fn defaults_write(domain: &str, key: &str, value_type: &str, value: &str) -> ToolResult {
     crate::platform::macos::defaults::write_default(domain, key, value_type, value)
 }
 
 #[cfg(target_os = "macos")]
-fn defaults_delete(domain: &str, key: &str) -> CallToolResult {
+fn defaults_delete(domain: &str, key: &str) -> ToolResult {
     crate::platform::macos::defaults::delete_default(domain, key)
 }
 
 #[cfg(target_os = "macos")]
-fn defaults_domains() -> CallToolResult {
+fn defaults_plan(domain: &str, key: &str, typed: Option<(&str, &str)>) -> ToolResult {
+    crate::platform::macos::defaults::plan_change(domain, key, typed)
+}
+
+#[cfg(target_os = "macos")]
+fn defaults_domains() -> ToolResult {
     crate::platform::macos::defaults::list_domains()
 }
 
 #[cfg(target_os = "macos")]
-fn defaults_find(keyword: &str) -> CallToolResult {
+fn defaults_find(keyword: &str) -> ToolResult {
     crate::platform::macos::defaults::find_default(keyword)
 }
 
 #[cfg(not(target_os = "macos"))]
-fn defaults_read(_domain: &str, _key: Option<&str>) -> CallToolResult {
-    CallToolResult::error("Defaults not implemented on this platform")
+fn defaults_read(_domain: &str, _key: Option<&str>) -> ToolResult {
+    Err(DaemonError::NotSupportedOnPlatform("macOS defaults".into()))
+}
+
+#[cfg(not(target_os = "macos"))]
+fn defaults_write(_domain: &str, _key: &str, _value_type: &str, _value: &str) -> ToolResult {
+    Err(DaemonError::NotSupportedOnPlatform("macOS defaults".into()))
 }
 
 #[cfg(not(target_os = "macos"))]
-fn defaults_write(_domain: &str, _key: &str, _value_type: &str, _value: &str) -> CallToolResult {
-    CallToolResult::error("Defaults not implemented on this platform")
+fn defaults_delete(_domain: &str, _key: &str) -> ToolResult {
+    Err(DaemonError::NotSupportedOnPlatform("macOS defaults".into()))
 }
 
 #[cfg(not(target_os = "macos"))]
-fn defaults_delete(_domain: &str, _key: &str) -> CallToolResult {
-    CallToolResult::error("Defaults not implemented on this platform")
+fn defaults_plan(_domain: &str, _key: &str, _typed: Option<(&str, &str)>) -> ToolResult {
+    Err(DaemonError::NotSupportedOnPlatform("macOS defaults".into()))
 }
 
 #[cfg(not(target_os = "macos"))]
-fn defaults_domains() -> CallToolResult {
-    CallToolResult::error("Defaults not implemented on this platform")
+fn defaults_domains() -> ToolResult {
+    Err(DaemonError::NotSupportedOnPlatform("macOS defaults".into()))
 }
 
 #[cfg(not(target_os = "macos"))]
-fn defaults_find(_keyword: &str) -> CallToolResult {
-    CallToolResult::error("Defaults not implemented on this platform")
+fn defaults_find(_keyword: &str) -> ToolResult {
+    Err(DaemonError::NotSupportedOnPlatform("macOS defaults".into()))
 }
 
 pub fn provider() -> Box<dyn CapabilityProvider> {
@@ -1,6 +1,7 @@
 use serde_json::{json, Value};
-use crate::mcp::types::{CallToolResult, Tool};
-use super::CapabilityProvider;
+use crate::error::DaemonError;
+use crate::mcp::types::{Resource, ResourceContents, Tool, ToolAnnotations};
+use super::{observed, planned, tool_resource, CapabilityProvider, Progress, ToolResult};
 
 pub struct DisplayProvider;
 
@@ -33,6 +34,8 @@ impl CapabilityProvider for DisplayProvider {
                         }
                     },
                 }),
+                output_schema: None,
+                annotations: ToolAnnotations::read_only(),
             },
             Tool {
                 name: "display_info".into(),
@@ -41,6 +44,8 @@ impl CapabilityProvider for DisplayProvider {
                     "type": "object",
                     "properties": {},
                 }),
+                output_schema: None,
+                annotations: ToolAnnotations::read_only(),
             },
             Tool {
                 name: "display_dark_mode".into(),
@@ -54,19 +59,17 @@ impl CapabilityProvider for DisplayProvider {
                         }
                     },
                 }),
+                output_schema: None,
+                annotations: ToolAnnotations::additive().idempotent(),
             },
         ]
     }
 
-    fn call(&self, tool_name: &str, arguments: &Value) -> Option<CallToolResult> {
+    fn call(&self, tool_name: &str, arguments: &Value, _progress: &Progress) -> Option<ToolResult> {
         match tool_name {
             "display_brightness" => {
                 if arguments.get("level").is_some() && !arguments["level"].is_null() {
-                    Some(CallToolResult::error(
-                        "Setting brightness is not supported without third-party tools. \
-                         Install the `brightness` CLI (`brew install brightness`) and use \
-                         it directly from the shell to adjust brightness."
-                    ))
+                    Some(display_set_brightness())
                 } else {
                     Some(display_get_brightness())
                 }
@@ -84,46 +87,96 @@ impl CapabilityProvider for DisplayProvider {
             _ => None,
         }
     }
+
+    /// Without `enabled` the tool only reads the setting, so it just runs.
+    fn plan(&self, tool_name: &str, arguments: &Value) -> Option<ToolResult> {
+        if tool_name != "display_dark_mode" {
+            return None;
+        }
+        let Some(enabled) = arguments.get("enabled").and_then(Value::as_bool) else {
+            return Some(display_get_dark_mode());
+        };
+        let current = match observed(display_get_dark_mode()) {
+            Ok(current) => current,
+            Err(failed) => return Some(failed),
+        };
+        let style = if enabled { "dark" } else { "light" };
+        let effect = if current["dark_mode"].as_bool() == Some(enabled) {
+            format!("The appearance is already {style}; nothing would change")
+        } else {
+            format!("Would switch the appearance from {} to {style}", current["style"].as_str().unwrap_or("light"))
+        };
+        Some(planned(effect, current))
+    }
+
+    fn resources(&self) -> Vec<Resource> {
+        vec![Resource {
+            uri: "familiar://display/info".into(),
+            name: "display_info".into(),
+            description: "Display hardware: resolution, GPU and connected displays.".into(),
+            mime_type: "application/json".into(),
+            tool: "display_info",
+        }]
+    }
+
+    fn read_resource(&self, uri: &str) -> Option<Result<ResourceContents, String>> {
+        match uri {
+            "familiar://display/info" => Some(tool_resource(uri, "application/json", display_info())),
+            _ => None,
+        }
+    }
 }
 
 #[cfg(target_os = "macos")]
-fn display_get_brightness() -> CallToolResult {
+fn display_get_brightness() -> ToolResult {
     crate::platform::macos::display::get_brightness()
 }
 
+/// Needs the third-party `brightness` CLI, which the daemon doesn't drive;
+/// agents can run it from a shell.
+#[cfg(target_os = "macos")]
+fn display_set_brightness() -> ToolResult {
+    Err(DaemonError::missing("brightness"))
+}
+
 #[cfg(target_os = "macos")]
-fn display_info() -> CallToolResult {
+fn display_info() -> ToolResult {
     crate::platform::macos::display::get_info()
 }
 
 #[cfg(target_os = "macos")]
-fn display_get_dark_mode() -> CallToolResult {
+fn display_get_dark_mode() -> ToolResult {
     crate::platform::macos::display::get_dark_mode()
 }
 
 #[cfg(target_os = "macos")]
-fn display_set_dark_mode(enabled: bool) -> CallToolResult {
+fn display_set_dark_mode(enabled: bool) -> ToolResult {
     crate::platform::macos::display::set_dark_mode(enabled)
 }
 
 #[cfg(not(target_os = "macos"))]
-fn display_get_brightness() -> CallToolResult {
-    CallToolResult::error("Display brightness not implemented on this platform")
+fn display_get_brightness() -> ToolResult {
+    Err(DaemonError::NotSupportedOnPlatform("Display brightness".into()))
+}
+
+#[cfg(not(target_os = "macos"))]
+fn display_set_brightness() -> ToolResult {
+    Err(DaemonError::NotSupportedOnPlatform("Display brightness".into()))
 }
 
 #[cfg(not(target_os = "macos"))]
-fn display_info() -> CallToolResult {
-    CallToolResult::error("Display info not implemented on this platform")
+fn display_info() -> ToolResult {
+    Err(DaemonError::NotSupportedOnPlatform("Display info".into()))
 }
 
 #[cfg(not(target_os = "macos"))]
-fn display_get_dark_mode() -> CallToolResult {
-    CallToolResult::error("Dark mode not implemented on this platform")
+fn display_get_dark_mode() -> ToolResult {
+    Err(DaemonError::NotSupportedOnPlatform("Dark mode".into()))
 }
 
 #[cfg(not(target_os = "macos"))]
-fn display_set_dark_mode(_enabled: bool) -> CallToolResult {
-    CallToolResult::error("Dark mode not implemented on this platform")
+fn display_set_dark_mode(_enabled: bool) -> ToolResult {
+    Err(DaemonError::NotSupportedOnPlatform("Dark mode".into()))
 }
 
 pub fn provider() -> Box<dyn CapabilityProvider> {
@@ -1,6 +1,13 @@
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
 use serde_json::{json, Value};
-use crate::mcp::types::{CallToolResult, Tool};
-use super::CapabilityProvider;
+use crate::config::expand_home;
+#[cfg(not(target_os = "macos"))]
+use crate::error::DaemonError;
+use crate::mcp::types::{Tool, ToolAnnotations};
+use super::{matching, CapabilityProvider, Progress, ToolResult};
 
 pub struct FileOpsProvider;
 
@@ -8,6 +15,11 @@ impl CapabilityProvider for FileOpsProvider {
     fn id(&self) -> &str { "file_ops" }
     fn name(&self) -> &str { "File Operations" }
 
+    // Copies and cross-volume moves of large trees
+    fn command_timeout(&self) -> Duration {
+        Duration::from_secs(600)
+    }
+
     fn tools(&self) -> Vec<Tool> {
         vec![
             Tool {
@@ -17,10 +29,12 @@ impl CapabilityProvider for FileOpsProvider {
                     "type": "object",
                     "properties": {
                         "path": { "type": "string", "description": "Directory path to list" },
-                        "show_hidden": { "type": "boolean", "description": "Include hidden files (default: false)" }
+                        "show_hidden": { "type": "boolean", "default": false, "description": "Include hidden files (default: false)" }
                     },
                     "required": ["path"],
                 }),
+                output_schema: None,
+                annotations: ToolAnnotations::read_only(),
             },
             Tool {
                 name: "file_mkdir".into(),
@@ -32,6 +46,8 @@ impl CapabilityProvider for FileOpsProvider {
                     },
                     "required": ["path"],
                 }),
+                output_schema: None,
+                annotations: ToolAnnotations::additive().idempotent(),
             },
             Tool {
                 name: "file_move".into(),
@@ -44,6 +60,8 @@ impl CapabilityProvider for FileOpsProvider {
                     },
                     "required": ["source", "destination"],
                 }),
+                output_schema: None,
+                annotations: ToolAnnotations::destructive(),
             },
             Tool {
                 name: "file_copy".into(),
@@ -56,6 +74,8 @@ impl CapabilityProvider for FileOpsProvider {
                     },
                     "required": ["source", "destination"],
                 }),
+                output_schema: None,
+                annotations: ToolAnnotations::destructive().idempotent(),
             },
             Tool {
                 name: "file_trash".into(),
@@ -67,6 +87,8 @@ impl CapabilityProvider for FileOpsProvider {
                     },
                     "required": ["path"],
                 }),
+                output_schema: None,
+                annotations: ToolAnnotations::destructive(),
             },
             Tool {
                 name: "file_reveal".into(),
@@ -78,15 +100,17 @@ impl CapabilityProvider for FileOpsProvider {
                     },
                     "required": ["path"],
                 }),
+                output_schema: None,
+                annotations: ToolAnnotations::additive().idempotent(),
             },
         ]
     }
 
-    fn call(&self, tool_name: &str, arguments: &Value) -> Option<CallToolResult> {
+    fn call(&self, tool_name: &str, arguments: &Value, progress: &Progress) -> Option<ToolResult> {
         match tool_name {
             "file_list" => {
                 let path = arguments["path"].as_str()?;
-                let show_hidden = arguments["show_hidden"].as_bool().unwrap_or(false);
+                let show_hidden = arguments["show_hidden"].as_bool()?;
                 Some(dispatch_list(path, show_hidden))
             }
             "file_mkdir" => {
@@ -101,7 +125,7 @@ impl CapabilityProvider for FileOpsProvider {
             "file_copy" => {
                 let source = arguments["source"].as_str()?;
                 let destination = arguments["destination"].as_str()?;
-                Some(dispatch_copy(source, destination))
+                Some(dispatch_copy(source, destination, progress))
             }
             "file_trash" => {
                 let path = arguments["path"].as_str()?;
@@ -114,45 +138,138 @@ impl CapabilityProvider for FileOpsProvider {
             _ => None,
         }
     }
+
+    fn plan(&self, tool_name: &str, arguments: &Value) -> Option<ToolResult> {
+        match tool_name {
+            "file_mkdir" => Some(dispatch_plan_mkdir(arguments["path"].as_str()?)),
+            "file_move" | "file_copy" => {
+                let verb = if tool_name == "file_move" { "move" } else { "copy" };
+                let source = arguments["source"].as_str()?;
+                let destination = arguments["destination"].as_str()?;
+                Some(dispatch_plan_transfer(verb, source, destination))
+            }
+            "file_trash" => Some(dispatch_plan_trash(arguments["path"].as_str()?)),
+            _ => None,
+        }
+    }
+
+    fn path_arguments(&self, tool_name: &str) -> &[&str] {
+        match tool_name {
+            "file_move" | "file_copy" => &["source", "destination"],
+            _ => &["path"],
+        }
+    }
+
+    fn tree_arguments(&self, tool_name: &str) -> &[&str] {
+        match tool_name {
+            "file_move" | "file_copy" => &["source"],
+            "file_trash" => &["path"],
+            _ => &[],
+        }
+    }
+
+    fn listed_paths(&self, tool_name: &str) -> Option<(&str, Option<&str>)> {
+        (tool_name == "file_list").then_some(("entries", Some("path")))
+    }
+
+    fn complete(&self, _tool_name: &str, argument: &str, prefix: &str) -> Vec<String> {
+        match argument {
+            "path" | "source" | "destination" => complete_path(prefix),
+            _ => Vec::new(),
+        }
+    }
+}
+
+/// Entries of the directory `prefix` points into whose names continue it
+/// (ignoring case), with a trailing `/` on directories. The tools take
+/// absolute paths, so an empty prefix or `~` completes to the home
+/// directory. Hidden entries are only offered once the name starts with
+/// a dot.
+fn complete_path(prefix: &str) -> Vec<String> {
+    if prefix.is_empty() || prefix == "~" {
+        return vec![expand_home("~/").to_string_lossy().into_owned()];
+    }
+    if prefix.starts_with("~/") {
+        return complete_path(&expand_home(prefix).to_string_lossy());
+    }
+
+    let (dir, partial) = match prefix.rfind('/') {
+        Some(i) => prefix.split_at(i + 1),
+        None => return Vec::new(),
+    };
+    let Ok(entries) = fs::read_dir(Path::new(dir)) else {
+        return Vec::new();
+    };
+    let mut candidates: Vec<String> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            if name.starts_with('.') && !partial.starts_with('.') {
+                return None;
+            }
+            let slash = if entry.path().is_dir() { "/" } else { "" };
+            Some(format!("{dir}{name}{slash}"))
+        })
+        .collect();
+    candidates.sort();
+    matching(candidates, prefix)
 }
 
 #[cfg(target_os = "macos")]
-fn dispatch_list(path: &str, show_hidden: bool) -> CallToolResult {
-    crate::platform::macos::file_ops::list_dir(path, show_hidden)
+fn dispatch_list(path: &str, show_hidden: bool) -> ToolResult {
+    Ok(crate::platform::macos::file_ops::list_dir(path, show_hidden))
 }
 #[cfg(target_os = "macos")]
-fn dispatch_mkdir(path: &str) -> CallToolResult {
-    crate::platform::macos::file_ops::mkdir(path)
+fn dispatch_mkdir(path: &str) -> ToolResult {
+    Ok(crate::platform::macos::file_ops::mkdir(path))
 }
 #[cfg(target_os = "macos")]
-fn dispatch_move(source: &str, destination: &str) -> CallToolResult {
+fn dispatch_move(source: &str, destination: &str) -> ToolResult {
     crate::platform::macos::file_ops::move_file(source, destination)
 }
 #[cfg(target_os = "macos")]
-fn dispatch_copy(source: &str, destination: &str) -> CallToolResult {
-    crate::platform::macos::file_ops::copy_file(source, destination)
+fn dispatch_copy(source: &str, destination: &str, progress: &Progress) -> ToolResult {
+    crate::platform::macos::file_ops::copy_file(source, destination, progress)
 }
 #[cfg(target_os = "macos")]
-fn dispatch_trash(path: &str) -> CallToolResult {
+fn dispatch_trash(path: &str) -> ToolResult {
     crate::platform::macos::file_ops::trash(path)
 }
 #[cfg(target_os = "macos")]
-fn dispatch_reveal(path: &str) -> CallToolResult {
+fn dispatch_reveal(path: &str) -> ToolResult {
     crate::platform::macos::file_ops::reveal_in_finder(path)
 }
+#[cfg(target_os = "macos")]
+fn dispatch_plan_mkdir(path: &str) -> ToolResult {
+    crate::platform::macos::file_ops::plan_mkdir(path)
+}
+#[cfg(target_os = "macos")]
+fn dispatch_plan_transfer(verb: &str, source: &str, destination: &str) -> ToolResult {
+    crate::platform::macos::file_ops::plan_transfer(verb, source, destination)
+}
+#[cfg(target_os = "macos")]
+fn dispatch_plan_trash(path: &str) -> ToolResult {
+    crate::platform::macos::file_ops::plan_trash(path)
+}
 
 #[cfg(not(target_os = "macos"))]
-fn dispatch_list(_: &str, _: bool) -> CallToolResult { CallToolResult::error("file_ops not supported on this platform") }
+fn dispatch_list(_: &str, _: bool) -> ToolResult { Err(DaemonError::NotSupportedOnPlatform("File operations".into())) }
+#[cfg(not(target_os = "macos"))]
+fn dispatch_mkdir(_: &str) -> ToolResult { Err(DaemonError::NotSupportedOnPlatform("File operations".into())) }
+#[cfg(not(target_os = "macos"))]
+fn dispatch_move(_: &str, _: &str) -> ToolResult { Err(DaemonError::NotSupportedOnPlatform("File operations".into())) }
+#[cfg(not(target_os = "macos"))]
+fn dispatch_copy(_: &str, _: &str, _: &Progress) -> ToolResult { Err(DaemonError::NotSupportedOnPlatform("File operations".into())) }
 #[cfg(not(target_os = "macos"))]
-fn dispatch_mkdir(_: &str) -> CallToolResult { CallToolResult::error("file_ops not supported on this platform") }
+fn dispatch_trash(_: &str) -> ToolResult { Err(DaemonError::NotSupportedOnPlatform("File operations".into())) }
 #[cfg(not(target_os = "macos"))]
-fn dispatch_move(_: &str, _: &str) -> CallToolResult { CallToolResult::error("file_ops not supported on this platform") }
+fn dispatch_reveal(_: &str) -> ToolResult { Err(DaemonError::NotSupportedOnPlatform("File operations".into())) }
 #[cfg(not(target_os = "macos"))]
-fn dispatch_copy(_: &str, _: &str) -> CallToolResult { CallToolResult::error("file_ops not supported on this platform") }
+fn dispatch_plan_mkdir(_: &str) -> ToolResult { Err(DaemonError::NotSupportedOnPlatform("File operations".into())) }
 #[cfg(not(target_os = "macos"))]
-fn dispatch_trash(_: &str) -> CallToolResult { CallToolResult::error("file_ops not supported on this platform") }
+fn dispatch_plan_transfer(_: &str, _: &str, _: &str) -> ToolResult { Err(DaemonError::NotSupportedOnPlatform("File operations".into())) }
 #[cfg(not(target_os = "macos"))]
-fn dispatch_reveal(_: &str) -> CallToolResult { CallToolResult::error("file_ops not supported on this platform") }
+fn dispatch_plan_trash(_: &str) -> ToolResult { Err(DaemonError::NotSupportedOnPlatform("File operations".into())) }
 
 pub fn provider() -> Box<dyn CapabilityProvider> {
     Box::new(FileOpsProvider)
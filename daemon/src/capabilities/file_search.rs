@@ -1,6 +1,9 @@
+use std::time::Duration;
+
 use serde_json::{json, Value};
-use crate::mcp::types::{CallToolResult, Tool};
-use super::CapabilityProvider;
+use crate::error::DaemonError;
+use crate::mcp::types::{Tool, ToolAnnotations};
+use super::{CapabilityProvider, Progress, ToolResult};
 
 pub struct FileSearchProvider;
 
@@ -19,6 +22,11 @@ impl CapabilityProvider for FileSearchProvider {
         "File Search"
     }
 
+    // Spotlight queries over large trees
+    fn command_timeout(&self) -> Duration {
+        Duration::from_secs(60)
+    }
+
     fn tools(&self) -> Vec<Tool> {
         vec![
             Tool {
@@ -36,12 +44,16 @@ impl CapabilityProvider for FileSearchProvider {
                             "description": "Optional directory to restrict search to"
                         },
                         "limit": {
-                            "type": "number",
-                            "description": "Maximum number of results to return (default: 20)"
+                            "type": "integer",
+                            "default": 20,
+                            "description": "Maximum number of results to return (default: 20)",
+                            "minimum": 1
                         }
                     },
                     "required": ["query"]
                 }),
+                output_schema: None,
+                annotations: ToolAnnotations::read_only(),
             },
             Tool {
                 name: "file_metadata".into(),
@@ -56,51 +68,61 @@ impl CapabilityProvider for FileSearchProvider {
                     },
                     "required": ["path"]
                 }),
+                output_schema: None,
+                annotations: ToolAnnotations::read_only(),
             },
         ]
     }
 
-    fn call(&self, tool_name: &str, arguments: &Value) -> Option<CallToolResult> {
+    fn call(&self, tool_name: &str, arguments: &Value, progress: &Progress) -> Option<ToolResult> {
         match tool_name {
             "file_search" => {
                 let query = arguments["query"].as_str().unwrap_or("");
                 if query.is_empty() {
-                    return Some(CallToolResult::error("Missing required parameter: query"));
+                    return Some(Err(DaemonError::invalid_argument("query", "is required")));
                 }
                 let path = arguments["path"].as_str();
-                let limit = arguments["limit"].as_u64().unwrap_or(20) as usize;
-                Some(file_search(query, path, limit))
+                let limit = arguments["limit"].as_u64()? as usize;
+                Some(file_search(query, path, limit, progress))
             }
             "file_metadata" => {
                 let path = arguments["path"].as_str().unwrap_or("");
                 if path.is_empty() {
-                    return Some(CallToolResult::error("Missing required parameter: path"));
+                    return Some(Err(DaemonError::invalid_argument("path", "is required")));
                 }
                 Some(file_metadata(path))
             }
             _ => None,
         }
     }
+
+    fn path_arguments(&self, _tool_name: &str) -> &[&str] {
+        &["path"]
+    }
+
+    fn listed_paths(&self, tool_name: &str) -> Option<(&str, Option<&str>)> {
+        (tool_name == "file_search").then_some(("results", None))
+    }
 }
 
 #[cfg(target_os = "macos")]
-fn file_search(query: &str, path: Option<&str>, limit: usize) -> CallToolResult {
-    crate::platform::macos::file_search::search(query, path, limit)
+fn file_search(query: &str, path: Option<&str>, limit: usize, progress: &Progress) -> ToolResult {
+    crate::platform::macos::file_search::search(query, path, limit, progress)
 }
 
 #[cfg(target_os = "macos")]
-fn file_metadata(path: &str) -> CallToolResult {
+fn file_metadata(path: &str) -> ToolResult {
     crate::platform::macos::file_search::metadata(path)
 }
 
 #[cfg(not(target_os = "macos"))]
-fn file_search(_query: &str, _path: Option<&str>, _limit: usize) -> CallToolResult {
-    CallToolResult::error("File search not implemented on this platform")
+fn file_search(_query: &str, _path: Option<&str>, _limit: usize, _progress: &Progress) -> ToolResult {
+    Err(DaemonError::NotSupportedOnPlatform("File search".into()))
 }
 
 #[cfg(not(target_os = "macos"))]
-fn file_metadata(_path: &str) -> CallToolResult {
-    CallToolResult::error("File search not implemented on this platform")
+fn file_metadata(_path: &str) -> ToolResult {
+    Err(DaemonError::NotSupportedOnPlatform("File search".into()))
 }
 
 pub fn provider() -> Box<dyn CapabilityProvider> {
@@ -1,6 +1,10 @@
 use serde_json::{json, Value};
-use crate::mcp::types::{CallToolResult, Tool};
-use super::CapabilityProvider;
+#[cfg(not(target_os = "macos"))]
+use crate::error::DaemonError;
+#[cfg(target_os = "macos")]
+use crate::platform::macos::ax_helpers::ensure_trusted;
+use crate::mcp::types::{Tool, ToolAnnotations};
+use super::{CapabilityProvider, Progress, ToolResult};
 
 pub struct InputSimProvider;
 
@@ -39,6 +43,8 @@ impl CapabilityProvider for InputSimProvider {
                     },
                     "required": ["key"]
                 }),
+                output_schema: None,
+                annotations: ToolAnnotations::destructive(),
             },
             Tool {
                 name: "input_type".into(),
@@ -51,12 +57,16 @@ impl CapabilityProvider for InputSimProvider {
                             "description": "Text to type"
                         },
                         "delay_ms": {
-                            "type": "number",
-                            "description": "Delay in milliseconds between each character (default: 0, types all at once)"
+                            "type": "integer",
+                            "default": 0,
+                            "description": "Delay in milliseconds between each character (default: 0, types all at once)",
+                            "minimum": 0
                         }
                     },
                     "required": ["text"]
                 }),
+                output_schema: None,
+                annotations: ToolAnnotations::destructive(),
             },
             Tool {
                 name: "input_mouse_move".into(),
@@ -75,6 +85,8 @@ impl CapabilityProvider for InputSimProvider {
                     },
                     "required": ["x", "y"]
                 }),
+                output_schema: None,
+                annotations: ToolAnnotations::additive().idempotent(),
             },
             Tool {
                 name: "input_scroll".into(),
@@ -91,16 +103,20 @@ impl CapabilityProvider for InputSimProvider {
                             "description": "Y coordinate to scroll at"
                         },
                         "delta_y": {
-                            "type": "number",
+                            "type": "integer",
+                            "default": 0,
                             "description": "Vertical scroll amount (positive=up, negative=down). Default: 0"
                         },
                         "delta_x": {
-                            "type": "number",
+                            "type": "integer",
+                            "default": 0,
                             "description": "Horizontal scroll amount (positive=right, negative=left). Default: 0"
                         }
                     },
                     "required": ["x", "y"]
                 }),
+                output_schema: None,
+                annotations: ToolAnnotations::additive(),
             },
             Tool {
                 name: "input_drag".into(),
@@ -126,15 +142,21 @@ impl CapabilityProvider for InputSimProvider {
                         },
                         "button": {
                             "type": "string",
+                            "default": "left",
+                            "enum": ["left", "right"],
                             "description": "Mouse button: \"left\" (default), \"right\""
                         },
                         "duration_ms": {
-                            "type": "number",
-                            "description": "Duration of drag in milliseconds (default: 200)"
+                            "type": "integer",
+                            "default": 200,
+                            "description": "Duration of drag in milliseconds (default: 200)",
+                            "minimum": 0
                         }
                     },
                     "required": ["from_x", "from_y", "to_x", "to_y"]
                 }),
+                output_schema: None,
+                annotations: ToolAnnotations::destructive(),
             },
             Tool {
                 name: "input_hotkey".into(),
@@ -149,6 +171,8 @@ impl CapabilityProvider for InputSimProvider {
                     },
                     "required": ["combo"]
                 }),
+                output_schema: None,
+                annotations: ToolAnnotations::destructive(),
             },
             Tool {
                 name: "input_mouse_click".into(),
@@ -166,20 +190,26 @@ impl CapabilityProvider for InputSimProvider {
                         },
                         "button": {
                             "type": "string",
+                            "default": "left",
+                            "enum": ["left", "right", "center"],
                             "description": "Mouse button: \"left\" (default), \"right\", or \"center\""
                         },
                         "clicks": {
-                            "type": "number",
-                            "description": "Number of clicks (default: 1, use 2 for double-click)"
+                            "type": "integer",
+                            "default": 1,
+                            "description": "Number of clicks (default: 1, use 2 for double-click)",
+                            "minimum": 1
                         }
                     },
                     "required": ["x", "y"]
                 }),
+                output_schema: None,
+                annotations: ToolAnnotations::destructive(),
             },
         ]
     }
 
-    fn call(&self, tool_name: &str, arguments: &Value) -> Option<CallToolResult> {
+    fn call(&self, tool_name: &str, arguments: &Value, _progress: &Progress) -> Option<ToolResult> {
         match tool_name {
             "input_key" => {
                 let key = arguments["key"].as_str().unwrap_or("");
@@ -195,7 +225,7 @@ impl CapabilityProvider for InputSimProvider {
             }
             "input_type" => {
                 let text = arguments["text"].as_str().unwrap_or("");
-                let delay_ms = arguments["delay_ms"].as_u64().unwrap_or(0);
+                let delay_ms = arguments["delay_ms"].as_u64()?;
                 Some(input_type(text, delay_ms))
             }
             "input_mouse_move" => {
@@ -206,8 +236,8 @@ impl CapabilityProvider for InputSimProvider {
             "input_scroll" => {
                 let x = arguments["x"].as_f64().unwrap_or(0.0);
                 let y = arguments["y"].as_f64().unwrap_or(0.0);
-                let delta_y = arguments["delta_y"].as_i64().unwrap_or(0) as i32;
-                let delta_x = arguments["delta_x"].as_i64().unwrap_or(0) as i32;
+                let delta_y = arguments["delta_y"].as_i64()? as i32;
+                let delta_x = arguments["delta_x"].as_i64()? as i32;
                 Some(input_scroll(x, y, delta_y, delta_x))
             }
             "input_drag" => {
@@ -215,8 +245,8 @@ impl CapabilityProvider for InputSimProvider {
                 let from_y = arguments["from_y"].as_f64().unwrap_or(0.0);
                 let to_x = arguments["to_x"].as_f64().unwrap_or(0.0);
                 let to_y = arguments["to_y"].as_f64().unwrap_or(0.0);
-                let button = arguments["button"].as_str().unwrap_or("left");
-                let duration_ms = arguments["duration_ms"].as_u64().unwrap_or(200);
+                let button = arguments["button"].as_str()?;
+                let duration_ms = arguments["duration_ms"].as_u64()?;
                 Some(input_drag(from_x, from_y, to_x, to_y, button, duration_ms))
             }
             "input_hotkey" => {
@@ -226,85 +256,164 @@ impl CapabilityProvider for InputSimProvider {
             "input_mouse_click" => {
                 let x = arguments["x"].as_f64().unwrap_or(0.0);
                 let y = arguments["y"].as_f64().unwrap_or(0.0);
-                let button = arguments["button"].as_str().unwrap_or("left");
-                let clicks = arguments["clicks"].as_u64().unwrap_or(1) as u32;
+                let button = arguments["button"].as_str()?;
+                let clicks = arguments["clicks"].as_u64()? as u32;
                 Some(input_mouse_click(x, y, button, clicks))
             }
             _ => None,
         }
     }
+
+    /// Key events go to the frontmost app and mouse events to the window
+    /// under the pointer; the plan names which that is now.
+    fn plan(&self, tool_name: &str, arguments: &Value) -> Option<ToolResult> {
+        let point = |x: &str, y: &str| (arguments[x].as_f64().unwrap_or(0.0), arguments[y].as_f64().unwrap_or(0.0));
+        match tool_name {
+            "input_key" => {
+                let key = arguments["key"].as_str().unwrap_or("");
+                let mut combo: Vec<&str> = arguments["modifiers"]
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .filter_map(Value::as_str)
+                    .collect();
+                combo.push(key);
+                Some(plan_keys(Some(key), &format!("press {}", combo.join("+"))))
+            }
+            "input_hotkey" => {
+                let combo = arguments["combo"].as_str().unwrap_or("");
+                let key = combo.rsplit('+').next().unwrap_or("").trim();
+                Some(plan_keys(Some(key), &format!("press {combo}")))
+            }
+            "input_type" => {
+                let count = arguments["text"].as_str().unwrap_or("").chars().count();
+                Some(plan_keys(None, &format!("type {count} characters")))
+            }
+            "input_mouse_move" => {
+                let (x, y) = point("x", "y");
+                Some(plan_pointer(&[(x, y)], &format!("move the pointer to ({x}, {y})")))
+            }
+            "input_mouse_click" => {
+                let (x, y) = point("x", "y");
+                let button = arguments["button"].as_str()?;
+                let clicks = arguments["clicks"].as_u64()?;
+                let action = format!("{button}-click {clicks} time(s) at ({x}, {y})");
+                Some(plan_pointer(&[(x, y)], &action))
+            }
+            "input_scroll" => {
+                let (x, y) = point("x", "y");
+                let (delta_x, delta_y) = (&arguments["delta_x"], &arguments["delta_y"]);
+                let action = format!("scroll by ({delta_x}, {delta_y}) at ({x}, {y})");
+                Some(plan_pointer(&[(x, y)], &action))
+            }
+            "input_drag" => {
+                let from = point("from_x", "from_y");
+                let to = point("to_x", "to_y");
+                let action = format!("drag from ({}, {}) to ({}, {})", from.0, from.1, to.0, to.1);
+                Some(plan_pointer(&[from, to], &action))
+            }
+            _ => None,
+        }
+    }
 }
 
 // ── Platform dispatch ──────────────────────────────────────────────────────
 
 #[cfg(target_os = "macos")]
-fn input_key(key: &str, modifiers: &[String]) -> CallToolResult {
-    crate::platform::macos::input_sim::key_press(key, modifiers)
+fn input_key(key: &str, modifiers: &[String]) -> ToolResult {
+    ensure_trusted()?;
+    Ok(crate::platform::macos::input_sim::key_press(key, modifiers))
 }
 
 #[cfg(target_os = "macos")]
-fn input_type(text: &str, delay_ms: u64) -> CallToolResult {
+fn input_type(text: &str, delay_ms: u64) -> ToolResult {
+    ensure_trusted()?;
     crate::platform::macos::input_sim::type_text(text, delay_ms)
 }
 
 #[cfg(target_os = "macos")]
-fn input_mouse_move(x: f64, y: f64) -> CallToolResult {
-    crate::platform::macos::input_sim::mouse_move(x, y)
+fn input_mouse_move(x: f64, y: f64) -> ToolResult {
+    ensure_trusted()?;
+    Ok(crate::platform::macos::input_sim::mouse_move(x, y))
+}
+
+#[cfg(target_os = "macos")]
+fn input_scroll(x: f64, y: f64, delta_y: i32, delta_x: i32) -> ToolResult {
+    ensure_trusted()?;
+    Ok(crate::platform::macos::input_sim::scroll(x, y, delta_y, delta_x))
 }
 
 #[cfg(target_os = "macos")]
-fn input_scroll(x: f64, y: f64, delta_y: i32, delta_x: i32) -> CallToolResult {
-    crate::platform::macos::input_sim::scroll(x, y, delta_y, delta_x)
+fn input_drag(from_x: f64, from_y: f64, to_x: f64, to_y: f64, button: &str, duration_ms: u64) -> ToolResult {
+    ensure_trusted()?;
+    Ok(crate::platform::macos::input_sim::drag(from_x, from_y, to_x, to_y, button, duration_ms))
 }
 
 #[cfg(target_os = "macos")]
-fn input_drag(from_x: f64, from_y: f64, to_x: f64, to_y: f64, button: &str, duration_ms: u64) -> CallToolResult {
-    crate::platform::macos::input_sim::drag(from_x, from_y, to_x, to_y, button, duration_ms)
+fn input_hotkey(combo: &str) -> ToolResult {
+    ensure_trusted()?;
+    Ok(crate::platform::macos::input_sim::hotkey(combo))
 }
 
 #[cfg(target_os = "macos")]
-fn input_hotkey(combo: &str) -> CallToolResult {
-    crate::platform::macos::input_sim::hotkey(combo)
+fn input_mouse_click(x: f64, y: f64, button: &str, clicks: u32) -> ToolResult {
+    ensure_trusted()?;
+    Ok(crate::platform::macos::input_sim::mouse_click(x, y, button, clicks))
 }
 
 #[cfg(target_os = "macos")]
-fn input_mouse_click(x: f64, y: f64, button: &str, clicks: u32) -> CallToolResult {
-    crate::platform::macos::input_sim::mouse_click(x, y, button, clicks)
+fn plan_keys(key: Option<&str>, action: &str) -> ToolResult {
+    crate::platform::macos::input_sim::plan_keys(key, action)
+}
+
+#[cfg(target_os = "macos")]
+fn plan_pointer(points: &[(f64, f64)], action: &str) -> ToolResult {
+    crate::platform::macos::input_sim::plan_pointer(points, action)
+}
+
+#[cfg(not(target_os = "macos"))]
+fn plan_keys(_key: Option<&str>, _action: &str) -> ToolResult {
+    Err(DaemonError::NotSupportedOnPlatform("Input simulation".into()))
+}
+
+#[cfg(not(target_os = "macos"))]
+fn plan_pointer(_points: &[(f64, f64)], _action: &str) -> ToolResult {
+    Err(DaemonError::NotSupportedOnPlatform("Input simulation".into()))
 }
 
 #[cfg(not(target_os = "macos"))]
-fn input_scroll(_x: f64, _y: f64, _delta_y: i32, _delta_x: i32) -> CallToolResult {
-    CallToolResult::error("Input simulation not implemented on this platform")
+fn input_scroll(_x: f64, _y: f64, _delta_y: i32, _delta_x: i32) -> ToolResult {
+    Err(DaemonError::NotSupportedOnPlatform("Input simulation".into()))
 }
 
 #[cfg(not(target_os = "macos"))]
-fn input_drag(_from_x: f64, _from_y: f64, _to_x: f64, _to_y: f64, _button: &str, _duration_ms: u64) -> CallToolResult {
-    CallToolResult::error("Input simulation not implemented on this platform")
+fn input_drag(_from_x: f64, _from_y: f64, _to_x: f64, _to_y: f64, _button: &str, _duration_ms: u64) -> ToolResult {
+    Err(DaemonError::NotSupportedOnPlatform("Input simulation".into()))
 }
 
 #[cfg(not(target_os = "macos"))]
-fn input_hotkey(_combo: &str) -> CallToolResult {
-    CallToolResult::error("Input simulation not implemented on this platform")
+fn input_hotkey(_combo: &str) -> ToolResult {
+    Err(DaemonError::NotSupportedOnPlatform("Input simulation".into()))
 }
 
 #[cfg(not(target_os = "macos"))]
-fn input_key(_key: &str, _modifiers: &[String]) -> CallToolResult {
-    CallToolResult::error("Input simulation not implemented on this platform")
+fn input_key(_key: &str, _modifiers: &[String]) -> ToolResult {
+    Err(DaemonError::NotSupportedOnPlatform("Input simulation".into()))
 }
 
 #[cfg(not(target_os = "macos"))]
-fn input_type(_text: &str, _delay_ms: u64) -> CallToolResult {
-    CallToolResult::error("Input simulation not implemented on this platform")
+fn input_type(_text: &str, _delay_ms: u64) -> ToolResult {
+    Err(DaemonError::NotSupportedOnPlatform("Input simulation".into()))
 }
 
 #[cfg(not(target_os = "macos"))]
-fn input_mouse_move(_x: f64, _y: f64) -> CallToolResult {
-    CallToolResult::error("Input simulation not implemented on this platform")
+fn input_mouse_move(_x: f64, _y: f64) -> ToolResult {
+    Err(DaemonError::NotSupportedOnPlatform("Input simulation".into()))
 }
 
 #[cfg(not(target_os = "macos"))]
-fn input_mouse_click(_x: f64, _y: f64, _button: &str, _clicks: u32) -> CallToolResult {
-    CallToolResult::error("Input simulation not implemented on this platform")
+fn input_mouse_click(_x: f64, _y: f64, _button: &str, _clicks: u32) -> ToolResult {
+    Err(DaemonError::NotSupportedOnPlatform("Input simulation".into()))
 }
 
 pub fn provider() -> Box<dyn CapabilityProvider> {
@@ -1,8 +1,20 @@
-use serde_json::Value;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use serde_json::{json, Value};
 use tracing::{debug, warn};
 
-use crate::mcp::types::{CallToolResult, Tool};
-use crate::permissions::PermissionsConfig;
+use crate::error::DaemonError;
+use crate::mcp::types::{CallToolResult, Resource, ResourceContents, Tool};
+use crate::permissions::{Access, PermissionsConfig, RateLimit};
+use crate::process;
+use paths::PathScope;
+use rate_limit::RateLimiter;
+
+mod constraints;
+mod paths;
+mod rate_limit;
+pub mod schema;
 
 #[cfg(feature = "system_info")]
 pub mod system_info;
@@ -51,66 +63,616 @@ pub trait CapabilityProvider: Send + Sync {
     fn tools(&self) -> Vec<Tool>;
 
     /// Execute a tool call. Returns None if this provider doesn't handle the tool.
-    fn call(&self, tool_name: &str, arguments: &Value) -> Option<CallToolResult>;
+    /// Long-running tools report through `progress`; short ones ignore it.
+    /// Failures an agent can act on (a missing permission or program) are
+    /// errors; everything else is a result with `isError`.
+    fn call(&self, tool_name: &str, arguments: &Value, progress: &Progress) -> Option<ToolResult>;
+
+    /// How long each command this capability's tools run may take before
+    /// it is killed, unless `timeout_secs` in permissions.toml says
+    /// otherwise.
+    fn command_timeout(&self) -> Duration {
+        process::DEFAULT_TIMEOUT
+    }
+
+    /// The arguments of `tool_name` that are file paths, checked against
+    /// the capability's `allowed_paths` and `denied_paths` before it runs.
+    fn path_arguments(&self, _tool_name: &str) -> &[&str] {
+        &[]
+    }
+
+    /// The path arguments of `tool_name` that the tool acts on with
+    /// everything inside them (a directory copied, moved or trashed):
+    /// refused if anything inside matches `denied_paths`.
+    fn tree_arguments(&self, _tool_name: &str) -> &[&str] {
+        &[]
+    }
+
+    /// Where the JSON result of `tool_name` lists paths it found: the
+    /// array field, and the field of each element holding the path (None
+    /// if the elements are paths). Paths out of the capability's scope
+    /// are dropped from the result, and from progress messages, which
+    /// for these tools are paths too.
+    fn listed_paths(&self, _tool_name: &str) -> Option<(&str, Option<&str>)> {
+        None
+    }
+
+    /// Describe what a state-changing call would do, without doing it, for
+    /// dry runs: check the arguments and look up the window, app or file
+    /// the call would act on, then answer with `planned`. None if the tool
+    /// has no such preview.
+    fn plan(&self, _tool_name: &str, _arguments: &Value) -> Option<ToolResult> {
+        None
+    }
+
+    /// Suggest values for `argument` of `tool_name` starting with `prefix`,
+    /// for `completion/complete`. Most tools have nothing to suggest.
+    fn complete(&self, _tool_name: &str, _argument: &str, _prefix: &str) -> Vec<String> {
+        Vec::new()
+    }
+
+    /// Return the MCP Resource definitions this capability exposes (read-only state).
+    fn resources(&self) -> Vec<Resource> {
+        Vec::new()
+    }
+
+    /// Read a resource by URI. Returns None if this provider doesn't own the URI.
+    fn read_resource(&self, _uri: &str) -> Option<Result<ResourceContents, String>> {
+        None
+    }
+}
+
+/// Progress reporter for one tool call. Forwards to the client that asked for
+/// progress (`_meta.progressToken`) and does nothing otherwise, so providers
+/// can report unconditionally without knowing the transport.
+#[derive(Clone, Default)]
+pub struct Progress {
+    sink: Option<Arc<ProgressSink>>,
+}
+
+type ProgressSink = dyn Fn(u64, Option<u64>, &str) + Send + Sync;
+
+impl Progress {
+    pub fn new(sink: impl Fn(u64, Option<u64>, &str) + Send + Sync + 'static) -> Self {
+        Self {
+            sink: Some(Arc::new(sink)),
+        }
+    }
+
+    /// A reporter that discards everything (nested calls, prompt rendering).
+    pub fn none() -> Self {
+        Self::default()
+    }
+
+    /// Report `progress` out of `total`, if known. `progress` must increase
+    /// with every report for the same call.
+    pub fn report(&self, progress: u64, total: Option<u64>, message: &str) {
+        if let Some(sink) = &self.sink {
+            sink(progress, total, message);
+        }
+    }
+
+    /// A reporter forwarding only the reports whose message `keep` accepts.
+    fn filter(&self, keep: impl Fn(&str) -> bool + Send + Sync + 'static) -> Self {
+        let Some(sink) = self.sink.clone() else {
+            return Self::none();
+        };
+        Self::new(move |progress, total, message| {
+            if keep(message) {
+                sink(progress, total, message);
+            }
+        })
+    }
 }
 
-/// Registry of all enabled capability providers, gated by permissions.
+/// The outcome of a tool call.
+pub type ToolResult = Result<CallToolResult, DaemonError>;
+
+/// Who a tool call is made for and how: the client's permissions profile
+/// (None: the top-level permissions) and whether it is a dry run, in which
+/// tools that change anything only describe what they would do.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CallContext<'a> {
+    pub profile: Option<&'a str>,
+    pub dry_run: bool,
+}
+
+/// The result of a dry run: what the call would do, and the window, app
+/// or file it would act on as it is now.
+pub fn planned(effect: impl Into<String>, target: Value) -> ToolResult {
+    Ok(CallToolResult::json(&json!({
+        "dry_run": true,
+        "effect": effect.into(),
+        "target": target,
+    })))
+}
+
+/// The JSON a read-only tool returned, for plans built from the current
+/// state. When the tool failed, its failure is what the plan answers.
+#[cfg(any(feature = "window_mgmt", feature = "app_control", feature = "audio", feature = "display"))]
+pub fn observed(result: ToolResult) -> Result<Value, ToolResult> {
+    match result {
+        Ok(result) if result.is_error != Some(true) => {
+            let text = result.text_output();
+            Ok(match result.structured_content {
+                Some(value) => value,
+                None => serde_json::from_str(&text).unwrap_or(Value::String(text)),
+            })
+        }
+        failed => Err(failed),
+    }
+}
+
+/// Asks the user whether a tool set to `ask` may run with the given
+/// arguments; returns why not when it may not.
+pub type Confirm<'a> = dyn Fn(&Tool, &Value) -> Result<(), DaemonError> + 'a;
+
+/// For callers with nobody to ask (prompt rendering): `ask` tools don't run.
+pub fn no_confirmation(tool: &Tool, _arguments: &Value) -> Result<(), DaemonError> {
+    Err(DaemonError::PermissionDenied {
+        permission: "confirmation".into(),
+        message: format!("Tool '{}' requires confirmation, which is not available here", tool.name),
+    })
+}
+
+/// The candidates that start with `prefix` (ignoring case), in order and
+/// without duplicates.
+pub fn matching(candidates: impl IntoIterator<Item = String>, prefix: &str) -> Vec<String> {
+    let prefix = prefix.to_lowercase();
+    let mut values: Vec<String> = Vec::new();
+    for candidate in candidates {
+        if candidate.to_lowercase().starts_with(&prefix) && !values.contains(&candidate) {
+            values.push(candidate);
+        }
+    }
+    values
+}
+
+/// Build resource contents from the result of the read-only tool that
+/// exposes the same state, so providers don't duplicate their platform code.
+pub fn tool_resource(uri: &str, mime_type: &str, result: ToolResult) -> Result<ResourceContents, String> {
+    let result = result.map_err(|e| e.to_string())?;
+    let text = result.text_output();
+    if result.is_error.unwrap_or(false) {
+        return Err(text);
+    }
+    Ok(ResourceContents {
+        uri: uri.into(),
+        mime_type: mime_type.into(),
+        text,
+    })
+}
+
+/// Registry of all compiled-in capability providers, gated by permissions.
+/// Permissions can be swapped at runtime; every operation works on a
+/// snapshot, so a reload never applies half a config to a call.
 pub struct CapabilityRegistry {
     providers: Vec<Box<dyn CapabilityProvider>>,
-    permissions: PermissionsConfig,
+    permissions: RwLock<Arc<PermissionsConfig>>,
+    limiter: RateLimiter,
 }
 
 impl CapabilityRegistry {
     pub fn new(permissions: PermissionsConfig) -> Self {
         Self {
             providers: Vec::new(),
-            permissions,
+            permissions: RwLock::new(Arc::new(permissions)),
+            limiter: RateLimiter::default(),
         }
     }
 
-    /// Register a capability provider (only if its capability is allowed).
+    /// Register a capability provider. Providers whose capability is not
+    /// allowed stay registered but hidden, so a permissions reload can
+    /// enable them.
     pub fn register(&mut self, provider: Box<dyn CapabilityProvider>) {
         let id = provider.id().to_string();
-        if self.permissions.is_capability_allowed(&id) {
-            debug!(capability = %id, "registered");
-            self.providers.push(provider);
-        } else {
-            debug!(capability = %id, "skipped (not allowed)");
-        }
+        let allowed = self.permissions().is_capability_allowed(&id);
+        debug!(capability = %id, name = provider.name(), allowed, "registered");
+        self.providers.push(provider);
     }
 
-    /// List all tools from all registered (and permitted) providers.
-    pub fn list_tools(&self) -> Vec<Tool> {
+    /// Replace the permissions config.
+    pub fn set_permissions(&self, permissions: PermissionsConfig) {
+        *self.permissions.write().unwrap_or_else(|e| e.into_inner()) = Arc::new(permissions);
+    }
+
+    pub fn permissions(&self) -> Arc<PermissionsConfig> {
+        self.permissions.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Providers whose capability is currently allowed.
+    fn allowed_providers<'a>(
+        &'a self,
+        permissions: &'a PermissionsConfig,
+    ) -> impl Iterator<Item = &'a dyn CapabilityProvider> {
+        self.providers
+            .iter()
+            .map(|p| p.as_ref())
+            .filter(|p| permissions.is_capability_allowed(p.id()))
+    }
+
+    /// List all tools permitted to a profile (None: the top-level
+    /// permissions), with their rate limits and argument constraints added
+    /// to the descriptions. Tools are checked one by one rather than per
+    /// capability, since annotation rules can allow individual tools of a
+    /// capability that is not allowed as a whole.
+    pub fn list_tools(&self, profile: Option<&str>) -> Vec<Tool> {
+        let root = self.permissions();
+        let permissions = root.profile(profile);
         self.providers
             .iter()
             .flat_map(|p| {
                 p.tools()
                     .into_iter()
-                    .filter(|t| self.permissions.is_tool_allowed(p.id(), &t.name))
+                    .filter(|t| permissions.is_tool_allowed(p.id(), t))
+                    .map(|mut t| {
+                        let (capability, tool) = permissions.rate_limits(p.id(), &t.name);
+                        if let Some(limits) = rate_limit::describe(p.name(), capability, tool) {
+                            t.description = format!("{} {limits}", t.description);
+                        }
+                        let rules = permissions.constraints(p.id(), &t.name);
+                        if let Some(rules) = rules.and_then(constraints::describe) {
+                            t.description = format!("{} {rules}", t.description);
+                        }
+                        t
+                    })
             })
             .collect()
     }
 
-    /// Call a tool by name, checking permissions.
-    pub fn call_tool(&self, tool_name: &str, arguments: &Value) -> CallToolResult {
+    /// Call a tool by name with a profile's permissions, validating the
+    /// arguments against the tool's input schema (with its defaults filled
+    /// in), and checking its paths and argument values against the
+    /// capability's path rules and constraints. Tools set to `ask` run only
+    /// once `confirm` agrees. In a dry run, tools that aren't read-only are
+    /// checked the same way but only planned, which neither counts against
+    /// rate limits nor needs confirmation. A tool that doesn't exist or
+    /// is denied to the profile is an `UnknownTool` error.
+    pub fn call_tool(
+        &self,
+        call: CallContext<'_>,
+        tool_name: &str,
+        arguments: &Value,
+        progress: &Progress,
+        confirm: &Confirm<'_>,
+    ) -> ToolResult {
+        let root = self.permissions();
+        let permissions = root.profile(call.profile);
         for provider in &self.providers {
-            if !self.permissions.is_tool_allowed(provider.id(), tool_name) {
+            let Some(tool) = provider.tools().into_iter().find(|t| t.name == tool_name) else {
+                continue;
+            };
+            let access = permissions.tool_access(provider.id(), &tool);
+            if access == Access::Deny {
                 continue;
             }
-            if let Some(result) = provider.call(tool_name, arguments) {
+
+            let mut arguments = arguments.clone();
+            if let Err(e) = schema::validate(&tool.input_schema, &mut arguments) {
+                debug!(tool = %tool_name, field = %e.field, error = %e.message, "invalid arguments");
+                return Err(e.into());
+            }
+            let (allowed, denied) = permissions.path_rules(provider.id());
+            let scope = PathScope::new(allowed, denied).map(Arc::new);
+            if let Some(scope) = &scope
+                && let Err(e) = check_paths(provider.as_ref(), scope, tool_name, &arguments)
+            {
+                warn!(tool = %tool_name, error = %e, "tool call path not permitted");
+                return Err(e);
+            }
+            if let Some(rules) = permissions.constraints(provider.id(), tool_name)
+                && let Err(e) = constraints::check(rules, &arguments)
+            {
+                warn!(tool = %tool_name, error = %e, "tool call violates argument constraints");
+                return Err(e);
+            }
+
+            let timeout = command_timeout(provider.as_ref(), permissions);
+            if call.dry_run && !tool.annotations.read_only {
+                debug!(tool = %tool_name, "dry run");
+                let plan = process::with_timeout(timeout, || provider.plan(tool_name, &arguments));
+                return plan.unwrap_or_else(|| {
+                    let effect = format!("Would call {tool_name}; its effect can't be previewed");
+                    planned(effect, json!({ "tool": tool_name, "arguments": arguments }))
+                });
+            }
+
+            if let Err(e) = self.rate_limit(permissions, call.profile, provider.id(), tool_name) {
+                warn!(tool = %tool_name, error = %e, "tool call rate limited");
+                return Err(e);
+            }
+            if access == Access::Ask
+                && let Err(e) = confirm(&tool, &arguments)
+            {
+                warn!(tool = %tool_name, error = %e, "tool call not confirmed");
+                return Err(e);
+            }
+
+            let listing = scope.zip(provider.listed_paths(tool_name));
+            let progress = match &listing {
+                Some((scope, _)) => {
+                    let scope = scope.clone();
+                    progress.filter(move |path| scope.permits(path))
+                }
+                None => progress.clone(),
+            };
+            let result = process::with_timeout(timeout, || provider.call(tool_name, &arguments, &progress));
+            if let Some(mut result) = result {
+                if let (Some((scope, (field, key))), Ok(found)) = (&listing, &mut result) {
+                    filter_listing(scope, found, field, *key);
+                }
+                match &result {
+                    Ok(result) if result.is_error == Some(true) => {
+                        debug!(tool = %tool_name, error = %result.text_output(), "tool returned an error");
+                    }
+                    Err(e) => debug!(tool = %tool_name, kind = e.kind(), error = %e, "tool failed"),
+                    Ok(_) => {}
+                }
                 return result;
             }
         }
 
         warn!(tool = %tool_name, "tool not found or not permitted");
-        CallToolResult::error(format!("Tool '{tool_name}' not found or not permitted"))
+        Err(DaemonError::UnknownTool(tool_name.into()))
+    }
+
+    /// Count a call of `tool_name` against its rate limits and those of its
+    /// capability, failing if any is exhausted. Each profile has its own.
+    fn rate_limit(
+        &self,
+        permissions: &PermissionsConfig,
+        profile: Option<&str>,
+        capability_id: &str,
+        tool_name: &str,
+    ) -> Result<(), DaemonError> {
+        let (capability, tool) = permissions.rate_limits(capability_id, tool_name);
+        let limits: Vec<(&'static str, &str, RateLimit)> = [
+            ("capability", capability_id, capability),
+            ("tool", tool_name, tool),
+        ]
+        .into_iter()
+        .filter_map(|(scope, name, limit)| limit.map(|limit| (scope, name, limit)))
+        .collect();
+
+        let prefix = profile.map_or(String::new(), |p| format!("profile/{p}/"));
+        let buckets: Vec<(String, RateLimit)> = limits
+            .iter()
+            .map(|(scope, name, limit)| (format!("{prefix}{scope}/{name}"), *limit))
+            .collect();
+        self.limiter.acquire(&buckets).map_err(|(i, retry_after)| {
+            let (scope, name, limit) = limits[i];
+            DaemonError::RateLimited {
+                scope,
+                name: name.into(),
+                max_per_minute: limit.max_per_minute,
+                burst: limit.burst(),
+                retry_after,
+            }
+        })
+    }
+
+    /// Completion candidates for an argument of a tool permitted to a
+    /// profile.
+    pub fn complete(&self, profile: Option<&str>, tool_name: &str, argument: &str, prefix: &str) -> Vec<String> {
+        let root = self.permissions();
+        let permissions = root.profile(profile);
+        self.providers
+            .iter()
+            .find_map(|p| {
+                p.tools()
+                    .into_iter()
+                    .find(|t| t.name == tool_name)
+                    .filter(|t| permissions.is_tool_allowed(p.id(), t))
+                    .map(|_| {
+                        process::with_timeout(command_timeout(p.as_ref(), permissions), || {
+                            p.complete(tool_name, argument, prefix)
+                        })
+                    })
+            })
+            .unwrap_or_default()
     }
 
+    /// List the resources of all providers permitted to a profile.
+    pub fn list_resources(&self, profile: Option<&str>) -> Vec<Resource> {
+        let root = self.permissions();
+        let permissions = root.profile(profile);
+        self.allowed_providers(permissions)
+            .flat_map(|p| {
+                p.resources()
+                    .into_iter()
+                    .filter(|r| is_resource_allowed(permissions, p, r))
+            })
+            .collect()
+    }
+
+    /// Read a resource by URI, checking the profile's permissions.
+    /// Returns None if no permitted provider owns the URI.
+    pub fn read_resource(&self, profile: Option<&str>, uri: &str) -> Option<Result<ResourceContents, String>> {
+        let root = self.permissions();
+        let permissions = root.profile(profile);
+        self.allowed_providers(permissions)
+            .filter(|p| {
+                p.resources()
+                    .iter()
+                    .any(|r| r.uri == uri && is_resource_allowed(permissions, *p, r))
+            })
+            .find_map(|p| {
+                process::with_timeout(command_timeout(p, permissions), || p.read_resource(uri))
+            })
+    }
+
+    /// Tools and capabilities of the top-level permissions, for logging.
     pub fn tool_count(&self) -> usize {
-        self.list_tools().len()
+        self.list_tools(None).len()
     }
 
     pub fn capability_count(&self) -> usize {
-        self.providers.len()
+        let permissions = self.permissions();
+        self.allowed_providers(&permissions).count()
+    }
+}
+
+/// The provider's command timeout, unless permissions.toml overrides it.
+fn command_timeout(provider: &dyn CapabilityProvider, permissions: &PermissionsConfig) -> Duration {
+    permissions
+        .command_timeout(provider.id())
+        .unwrap_or_else(|| provider.command_timeout())
+}
+
+/// Whether a provider's resource is permitted: its tool must be allowed
+/// outright.
+fn is_resource_allowed(
+    permissions: &PermissionsConfig,
+    provider: &dyn CapabilityProvider,
+    resource: &Resource,
+) -> bool {
+    provider
+        .tools()
+        .iter()
+        .find(|t| t.name == resource.tool)
+        .is_some_and(|tool| permissions.is_resource_allowed(provider.id(), &resource.uri, tool))
+}
+
+/// Check the path arguments of a call against the capability's
+/// `allowed_paths` and `denied_paths`, and what is inside those it acts
+/// on as a whole.
+fn check_paths(
+    provider: &dyn CapabilityProvider,
+    scope: &PathScope,
+    tool_name: &str,
+    arguments: &Value,
+) -> Result<(), DaemonError> {
+    for field in provider.path_arguments(tool_name) {
+        scope.check(provider.id(), field, arguments[*field].as_str())?;
+    }
+    for field in provider.tree_arguments(tool_name) {
+        scope.check_tree(provider.id(), field, arguments[*field].as_str())?;
+    }
+    Ok(())
+}
+
+/// Drop the paths out of `scope` from the array `field` of a listing
+/// result (the path of each element is its `key`, or the element itself),
+/// and take them off its `count` and `total_matches`.
+fn filter_listing(scope: &PathScope, result: &mut CallToolResult, field: &str, key: Option<&str>) {
+    let Some(mut value) = result.structured_content.take() else {
+        return;
+    };
+    let dropped = match value.get_mut(field).and_then(Value::as_array_mut) {
+        Some(paths) => {
+            let before = paths.len();
+            paths.retain(|entry| {
+                let path = key.map_or(entry, |key| &entry[key]);
+                path.as_str().is_some_and(|path| scope.permits(path))
+            });
+            before - paths.len()
+        }
+        None => 0,
+    };
+    for counter in ["count", "total_matches"] {
+        if let Some(n) = value[counter].as_u64() {
+            value[counter] = n.saturating_sub(dropped as u64).into();
+        }
+    }
+    *result = CallToolResult::json(&value);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A clipboard whose `clipboard_read` tool is also the
+    /// `familiar://clipboard` resource.
+    struct Clipboard;
+
+    impl CapabilityProvider for Clipboard {
+        fn id(&self) -> &str {
+            "clipboard"
+        }
+
+        fn name(&self) -> &str {
+            "Clipboard"
+        }
+
+        fn tools(&self) -> Vec<Tool> {
+            vec![Tool {
+                name: "clipboard_read".into(),
+                description: String::new(),
+                input_schema: json!({ "type": "object" }),
+                output_schema: None,
+                annotations: crate::mcp::types::ToolAnnotations::read_only(),
+            }]
+        }
+
+        fn call(&self, _tool_name: &str, _arguments: &Value, _progress: &Progress) -> Option<ToolResult> {
+            Some(Ok(CallToolResult::text("copied")))
+        }
+
+        fn resources(&self) -> Vec<Resource> {
+            vec![Resource {
+                uri: "familiar://clipboard".into(),
+                name: "clipboard".into(),
+                description: String::new(),
+                mime_type: "text/plain".into(),
+                tool: "clipboard_read",
+            }]
+        }
+
+        fn read_resource(&self, uri: &str) -> Option<Result<ResourceContents, String>> {
+            Some(tool_resource(uri, "text/plain", Ok(CallToolResult::text("copied"))))
+        }
+    }
+
+    #[test]
+    fn resources_follow_the_permissions_of_their_tool() {
+        for (clipboard_read, readable) in [("true", true), ("false", false), ("\"ask\"", false)] {
+            let permissions =
+                format!("[capabilities.clipboard]\nallowed = true\ntools = {{ clipboard_read = {clipboard_read} }}");
+            let mut registry = CapabilityRegistry::new(crate::permissions::parse(&permissions).unwrap());
+            registry.register(Box::new(Clipboard));
+
+            let listed = registry.list_resources(None).iter().any(|r| r.uri == "familiar://clipboard");
+            assert_eq!(listed, readable, "clipboard_read = {clipboard_read}");
+            let read = registry.read_resource(None, "familiar://clipboard");
+            assert_eq!(read.is_some(), readable, "clipboard_read = {clipboard_read}");
+        }
+    }
+
+    #[test]
+    fn listings_leave_out_paths_out_of_scope() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = std::fs::canonicalize(dir.path()).unwrap().to_string_lossy().into_owned();
+        let scope = PathScope::new(&[], &[format!("{root}/**/*.key")]).unwrap();
+
+        let mut result = CallToolResult::json(&json!({
+            "entries": [{ "path": format!("{root}/a.txt") }, { "path": format!("{root}/id.key") }],
+            "count": 2,
+        }));
+        filter_listing(&scope, &mut result, "entries", Some("path"));
+        let listing = result.structured_content.unwrap();
+        assert_eq!(listing["entries"], json!([{ "path": format!("{root}/a.txt") }]));
+        assert_eq!(listing["count"], 1);
+
+        let mut result = CallToolResult::json(&json!({
+            "results": [format!("{root}/id.key"), format!("{root}/b.txt")],
+            "count": 2,
+            "total_matches": 40,
+        }));
+        filter_listing(&scope, &mut result, "results", None);
+        assert!(!result.text_output().contains("id.key"));
+        let listing = result.structured_content.unwrap();
+        assert_eq!(listing["results"], json!([format!("{root}/b.txt")]));
+        assert_eq!((listing["count"].clone(), listing["total_matches"].clone()), (json!(1), json!(39)));
+    }
+
+    #[test]
+    fn filtered_progress_drops_reports_out_of_scope() {
+        let reports = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let sink = reports.clone();
+        let progress = Progress::new(move |_, _, message| sink.lock().unwrap().push(message.to_string()));
+        let filtered = progress.filter(|message| !message.ends_with(".key"));
+        filtered.report(1, None, "/a.txt");
+        filtered.report(2, None, "/id.key");
+        assert_eq!(*reports.lock().unwrap(), ["/a.txt"]);
     }
 }
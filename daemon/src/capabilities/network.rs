@@ -1,6 +1,10 @@
+use std::time::Duration;
+
 use serde_json::{json, Value};
-use crate::mcp::types::{CallToolResult, Tool};
-use super::CapabilityProvider;
+#[cfg(not(target_os = "macos"))]
+use crate::error::DaemonError;
+use crate::mcp::types::{Resource, ResourceContents, Tool, ToolAnnotations};
+use super::{tool_resource, CapabilityProvider, Progress, ToolResult};
 
 pub struct NetworkProvider;
 
@@ -19,6 +23,11 @@ impl CapabilityProvider for NetworkProvider {
         "Network"
     }
 
+    // A ping of up to 100 packets, one per second
+    fn command_timeout(&self) -> Duration {
+        Duration::from_secs(120)
+    }
+
     fn tools(&self) -> Vec<Tool> {
         vec![
             Tool {
@@ -28,6 +37,8 @@ impl CapabilityProvider for NetworkProvider {
                     "type": "object",
                     "properties": {},
                 }),
+                output_schema: None,
+                annotations: ToolAnnotations::read_only(),
             },
             Tool {
                 name: "network_wifi".into(),
@@ -36,6 +47,8 @@ impl CapabilityProvider for NetworkProvider {
                     "type": "object",
                     "properties": {},
                 }),
+                output_schema: None,
+                annotations: ToolAnnotations::read_only(),
             },
             Tool {
                 name: "network_ping".into(),
@@ -48,7 +61,8 @@ impl CapabilityProvider for NetworkProvider {
                             "description": "Hostname or IP address to ping"
                         },
                         "count": {
-                            "type": "number",
+                            "type": "integer",
+                            "default": 4,
                             "description": "Number of ping packets to send (default 4)",
                             "minimum": 1,
                             "maximum": 100
@@ -56,6 +70,8 @@ impl CapabilityProvider for NetworkProvider {
                     },
                     "required": ["host"]
                 }),
+                output_schema: None,
+                annotations: ToolAnnotations::read_only().open_world(),
             },
             Tool {
                 name: "network_interfaces".into(),
@@ -64,63 +80,82 @@ impl CapabilityProvider for NetworkProvider {
                     "type": "object",
                     "properties": {},
                 }),
+                output_schema: None,
+                annotations: ToolAnnotations::read_only(),
             },
         ]
     }
 
-    fn call(&self, tool_name: &str, arguments: &Value) -> Option<CallToolResult> {
+    fn call(&self, tool_name: &str, arguments: &Value, progress: &Progress) -> Option<ToolResult> {
         match tool_name {
             "network_info" => Some(network_info()),
             "network_wifi" => Some(network_wifi()),
             "network_ping" => {
                 let host = arguments["host"].as_str().unwrap_or("");
-                let count = arguments["count"].as_u64().unwrap_or(4) as u32;
-                Some(network_ping(host, count))
+                let count = arguments["count"].as_u64()? as u32;
+                Some(network_ping(host, count, progress))
             }
             "network_interfaces" => Some(network_interfaces()),
             _ => None,
         }
     }
+
+    fn resources(&self) -> Vec<Resource> {
+        vec![Resource {
+            uri: "familiar://network/info".into(),
+            name: "network_info".into(),
+            description: "Hostname, IP addresses, gateway and DNS servers.".into(),
+            mime_type: "application/json".into(),
+            tool: "network_info",
+        }]
+    }
+
+    fn read_resource(&self, uri: &str) -> Option<Result<ResourceContents, String>> {
+        match uri {
+            "familiar://network/info" => Some(tool_resource(uri, "application/json", network_info())),
+            _ => None,
+        }
+    }
 }
 
 #[cfg(target_os = "macos")]
-fn network_info() -> CallToolResult {
+fn network_info() -> ToolResult {
     crate::platform::macos::network::get_info()
 }
 
 #[cfg(target_os = "macos")]
-fn network_wifi() -> CallToolResult {
+fn network_wifi() -> ToolResult {
     crate::platform::macos::network::get_wifi()
 }
 
 #[cfg(target_os = "macos")]
-fn network_ping(host: &str, count: u32) -> CallToolResult {
-    crate::platform::macos::network::ping(host, count)
+fn network_ping(host: &str, count: u32, progress: &Progress) -> ToolResult {
+    crate::platform::macos::network::ping(host, count, progress)
 }
 
 #[cfg(target_os = "macos")]
-fn network_interfaces() -> CallToolResult {
+fn network_interfaces() -> ToolResult {
     crate::platform::macos::network::get_interfaces()
 }
 
 #[cfg(not(target_os = "macos"))]
-fn network_info() -> CallToolResult {
-    CallToolResult::error("Network not implemented on this platform")
+fn network_info() -> ToolResult {
+    Err(DaemonError::NotSupportedOnPlatform("Network tools".into()))
 }
 
 #[cfg(not(target_os = "macos"))]
-fn network_wifi() -> CallToolResult {
-    CallToolResult::error("Network not implemented on this platform")
+fn network_wifi() -> ToolResult {
+    Err(DaemonError::NotSupportedOnPlatform("Network tools".into()))
 }
 
 #[cfg(not(target_os = "macos"))]
-fn network_ping(_host: &str, _count: u32) -> CallToolResult {
-    CallToolResult::error("Network not implemented on this platform")
+fn network_ping(_host: &str, _count: u32, _progress: &Progress) -> ToolResult {
+    Err(DaemonError::NotSupportedOnPlatform("Network tools".into()))
 }
 
 #[cfg(not(target_os = "macos"))]
-fn network_interfaces() -> CallToolResult {
-    CallToolResult::error("Network not implemented on this platform")
+fn network_interfaces() -> ToolResult {
+    Err(DaemonError::NotSupportedOnPlatform("Network tools".into()))
 }
 
 pub fn provider() -> Box<dyn CapabilityProvider> {
@@ -1,6 +1,8 @@
 use serde_json::{json, Value};
-use crate::mcp::types::{CallToolResult, Tool};
-use super::CapabilityProvider;
+#[cfg(not(target_os = "macos"))]
+use crate::error::DaemonError;
+use crate::mcp::types::{Tool, ToolAnnotations};
+use super::{CapabilityProvider, Progress, ToolResult};
 
 pub struct NotificationsProvider;
 
@@ -41,10 +43,12 @@ impl CapabilityProvider for NotificationsProvider {
                 },
                 "required": ["title", "body"]
             }),
+            output_schema: None,
+            annotations: ToolAnnotations::additive(),
         }]
     }
 
-    fn call(&self, tool_name: &str, arguments: &Value) -> Option<CallToolResult> {
+    fn call(&self, tool_name: &str, arguments: &Value, _progress: &Progress) -> Option<ToolResult> {
         match tool_name {
             "notify_send" => {
                 let title = arguments["title"].as_str().unwrap_or("Familiar");
@@ -58,13 +62,13 @@ impl CapabilityProvider for NotificationsProvider {
 }
 
 #[cfg(target_os = "macos")]
-fn send_notification(title: &str, body: &str, subtitle: Option<&str>) -> CallToolResult {
+fn send_notification(title: &str, body: &str, subtitle: Option<&str>) -> ToolResult {
     crate::platform::macos::notifications::send(title, body, subtitle)
 }
 
 #[cfg(not(target_os = "macos"))]
-fn send_notification(_title: &str, _body: &str, _subtitle: Option<&str>) -> CallToolResult {
-    CallToolResult::error("Notifications not implemented on this platform")
+fn send_notification(_title: &str, _body: &str, _subtitle: Option<&str>) -> ToolResult {
+    Err(DaemonError::NotSupportedOnPlatform("Notifications".into()))
 }
 
 pub fn provider() -> Box<dyn CapabilityProvider> {
@@ -1,6 +1,10 @@
+use std::time::Duration;
+
 use serde_json::{json, Value};
-use crate::mcp::types::{CallToolResult, Tool};
-use super::CapabilityProvider;
+#[cfg(not(target_os = "macos"))]
+use crate::error::DaemonError;
+use crate::mcp::types::{Tool, ToolAnnotations};
+use super::{CapabilityProvider, Progress, ToolResult};
 
 pub struct OcrProvider;
 
@@ -19,6 +23,11 @@ impl CapabilityProvider for OcrProvider {
         "Screen OCR"
     }
 
+    // The first call compiles the Swift helper
+    fn command_timeout(&self) -> Duration {
+        Duration::from_secs(120)
+    }
+
     fn tools(&self) -> Vec<Tool> {
         vec![
             Tool {
@@ -28,11 +37,15 @@ impl CapabilityProvider for OcrProvider {
                     "type": "object",
                     "properties": {
                         "display": {
-                            "type": "number",
-                            "description": "Display index (default: 0 for main display)"
+                            "type": "integer",
+                            "default": 0,
+                            "description": "Display index (default: 0 for main display)",
+                            "minimum": 0
                         }
                     },
                 }),
+                output_schema: Some(ocr_output_schema()),
+                annotations: ToolAnnotations::read_only(),
             },
             Tool {
                 name: "ocr_region".into(),
@@ -59,6 +72,8 @@ impl CapabilityProvider for OcrProvider {
                     },
                     "required": ["x", "y", "width", "height"]
                 }),
+                output_schema: Some(ocr_output_schema()),
+                annotations: ToolAnnotations::read_only(),
             },
             Tool {
                 name: "ocr_image".into(),
@@ -73,15 +88,17 @@ impl CapabilityProvider for OcrProvider {
                     },
                     "required": ["path"]
                 }),
+                output_schema: Some(ocr_output_schema()),
+                annotations: ToolAnnotations::read_only(),
             },
         ]
     }
 
-    fn call(&self, tool_name: &str, arguments: &Value) -> Option<CallToolResult> {
+    fn call(&self, tool_name: &str, arguments: &Value, progress: &Progress) -> Option<ToolResult> {
         match tool_name {
             "ocr_screen" => {
-                let display = arguments["display"].as_u64().unwrap_or(0) as u32;
-                Some(ocr_screen(display))
+                let display = arguments["display"].as_u64()? as u32;
+                Some(ocr_screen(display, progress))
             }
             "ocr_region" => {
                 let x = arguments["x"].as_f64().unwrap_or(0.0);
@@ -97,38 +114,71 @@ impl CapabilityProvider for OcrProvider {
             _ => None,
         }
     }
+
+    fn path_arguments(&self, tool_name: &str) -> &[&str] {
+        match tool_name {
+            "ocr_image" => &["path"],
+            _ => &[],
+        }
+    }
 }
 
 // ── Platform dispatch ──────────────────────────────────────────────────────
 
+/// Shared by all OCR tools: one entry per recognized line of text, in pixel
+/// coordinates of the captured image (origin top-left).
+fn ocr_output_schema() -> Value {
+    json!({
+        "type": "object",
+        "properties": {
+            "lines": {
+                "type": "array",
+                "items": {
+                    "type": "object",
+                    "properties": {
+                        "text": { "type": "string" },
+                        "confidence": { "type": "number" },
+                        "x": { "type": "integer" },
+                        "y": { "type": "integer" },
+                        "width": { "type": "integer" },
+                        "height": { "type": "integer" }
+                    },
+                    "required": ["text", "confidence", "x", "y", "width", "height"]
+                }
+            }
+        },
+        "required": ["lines"]
+    })
+}
+
 #[cfg(target_os = "macos")]
-fn ocr_screen(display: u32) -> CallToolResult {
-    crate::platform::macos::ocr::ocr_screen(display)
+fn ocr_screen(display: u32, progress: &Progress) -> ToolResult {
+    crate::platform::macos::ocr::ocr_screen(display, progress)
 }
 
 #[cfg(target_os = "macos")]
-fn ocr_region(x: f64, y: f64, width: f64, height: f64) -> CallToolResult {
+fn ocr_region(x: f64, y: f64, width: f64, height: f64) -> ToolResult {
     crate::platform::macos::ocr::ocr_region(x, y, width, height)
 }
 
 #[cfg(target_os = "macos")]
-fn ocr_image(path: &str) -> CallToolResult {
+fn ocr_image(path: &str) -> ToolResult {
     crate::platform::macos::ocr::ocr_image(path)
 }
 
 #[cfg(not(target_os = "macos"))]
-fn ocr_screen(_display: u32) -> CallToolResult {
-    CallToolResult::error("OCR not implemented on this platform")
+fn ocr_screen(_display: u32, _progress: &Progress) -> ToolResult {
+    Err(DaemonError::NotSupportedOnPlatform("OCR".into()))
 }
 
 #[cfg(not(target_os = "macos"))]
-fn ocr_region(_x: f64, _y: f64, _width: f64, _height: f64) -> CallToolResult {
-    CallToolResult::error("OCR not implemented on this platform")
+fn ocr_region(_x: f64, _y: f64, _width: f64, _height: f64) -> ToolResult {
+    Err(DaemonError::NotSupportedOnPlatform("OCR".into()))
 }
 
 #[cfg(not(target_os = "macos"))]
-fn ocr_image(_path: &str) -> CallToolResult {
-    CallToolResult::error("OCR not implemented on this platform")
+fn ocr_image(_path: &str) -> ToolResult {
+    Err(DaemonError::NotSupportedOnPlatform("OCR".into()))
 }
 
 pub fn provider() -> Box<dyn CapabilityProvider> {
@@ -0,0 +1,311 @@
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+use crate::config::expand_home;
+use crate::error::DaemonError;
+
+/// Where a capability's path arguments may point: `allowed_paths` and
+/// `denied_paths` from permissions.toml. Patterns are globs (`*` within a
+/// path component, `**` across them, `?` one character) with `~`
+/// expanded, and cover everything inside a directory they match.
+pub struct PathScope {
+    allowed: Vec<String>,
+    denied: Vec<String>,
+}
+
+impl PathScope {
+    /// The scope set by `allowed` and `denied`, or None if both are empty.
+    pub fn new(allowed: &[String], denied: &[String]) -> Option<Self> {
+        if allowed.is_empty() && denied.is_empty() {
+            return None;
+        }
+        Some(Self {
+            allowed: allowed.iter().map(|p| expand_pattern(p)).collect(),
+            denied: denied.iter().map(|p| expand_pattern(p)).collect(),
+        })
+    }
+
+    /// Check the path in argument `field` of a `capability` tool. The path is
+    /// resolved the way the tool will see it: `~` expanded, symlinks and
+    /// `..` resolved. A symlink has to be in scope both where it is and
+    /// where it points.
+    pub fn check(&self, capability: &str, field: &str, path: Option<&str>) -> Result<(), DaemonError> {
+        let denied = |message: String| DaemonError::PermissionDenied {
+            permission: "path".into(),
+            message,
+        };
+        let Some(path) = path else {
+            if self.allowed.is_empty() {
+                return Ok(());
+            }
+            return Err(denied(format!(
+                "{field} must be given, since {capability} is limited to allowed_paths"
+            )));
+        };
+
+        let resolved = resolve(path).map_err(|message| DaemonError::invalid_argument(field, &message))?;
+        for candidate in &resolved {
+            let shown = candidate.display();
+            if let Some(pattern) = self.denied_pattern(candidate) {
+                return Err(denied(format!(
+                    "{field} {shown} matches denied_paths pattern '{pattern}' of {capability}"
+                )));
+            }
+            if !self.is_allowed(candidate) {
+                return Err(denied(format!(
+                    "{field} {shown} is outside the allowed_paths of {capability}"
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Check what is inside the directory in argument `field`, for tools
+    /// that act on it as a whole (copy, move, trash): nothing in it may
+    /// match `denied_paths`. Symlinks in it are checked where they are,
+    /// not followed. Anything but a directory passes.
+    pub fn check_tree(&self, capability: &str, field: &str, path: Option<&str>) -> Result<(), DaemonError> {
+        let Some(path) = path else {
+            return Ok(());
+        };
+        if self.denied.is_empty() {
+            return Ok(());
+        }
+        let resolved = resolve(path).map_err(|message| DaemonError::invalid_argument(field, &message))?;
+        let mut dirs: Vec<PathBuf> = resolved.into_iter().take(1).filter(|p| p.is_dir()).collect();
+        while let Some(dir) = dirs.pop() {
+            let Ok(entries) = fs::read_dir(&dir) else {
+                continue;
+            };
+            for entry in entries.flatten() {
+                let entry_path = entry.path();
+                if let Some(pattern) = self.denied_pattern(&entry_path) {
+                    return Err(DaemonError::PermissionDenied {
+                        permission: "path".into(),
+                        message: format!(
+                            "{field} {path} contains {}, which matches denied_paths pattern '{pattern}' of {capability}",
+                            entry_path.display()
+                        ),
+                    });
+                }
+                if entry.file_type().is_ok_and(|t| t.is_dir()) {
+                    dirs.push(entry_path);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether a path a tool found (a directory entry, a search result)
+    /// is in scope, by the same rules as `check`. Paths that can't be
+    /// resolved aren't.
+    pub fn permits(&self, path: &str) -> bool {
+        resolve(path).is_ok_and(|resolved| {
+            resolved
+                .iter()
+                .all(|p| self.denied_pattern(p).is_none() && self.is_allowed(p))
+        })
+    }
+
+    fn denied_pattern(&self, path: &Path) -> Option<&str> {
+        self.denied.iter().find(|p| covers(p, path)).map(String::as_str)
+    }
+
+    fn is_allowed(&self, path: &Path) -> bool {
+        self.allowed.is_empty() || self.allowed.iter().any(|p| covers(p, path))
+    }
+}
+
+/// A pattern with `~` expanded and the directory before its first wildcard
+/// canonicalized, so it compares with resolved paths (e.g. `/tmp/**` on
+/// macOS, where /tmp links to /private/tmp).
+fn expand_pattern(pattern: &str) -> String {
+    let pattern = expand_home(pattern).to_string_lossy().trim_end_matches('/').to_string();
+    let literal = pattern.find(['*', '?']).map_or(pattern.as_str(), |i| &pattern[..i]);
+    let Some(split) = literal.rfind('/') else {
+        return pattern;
+    };
+    let (dir, rest) = pattern.split_at(split);
+    match fs::canonicalize(if dir.is_empty() { "/" } else { dir }) {
+        Ok(dir) => format!("{}{rest}", dir.to_string_lossy().trim_end_matches('/')),
+        Err(_) => pattern,
+    }
+}
+
+/// Whether `pattern` matches `path` or a directory containing it.
+fn covers(pattern: &str, path: &Path) -> bool {
+    path.ancestors()
+        .any(|p| glob_match(pattern.as_bytes(), p.to_string_lossy().as_bytes()))
+}
+
+fn glob_match(pattern: &[u8], text: &[u8]) -> bool {
+    match pattern {
+        [] => text.is_empty(),
+        [b'*', b'*', rest @ ..] => {
+            // `**/` also matches no directories at all
+            (0..=text.len()).any(|i| glob_match(rest, &text[i..]))
+                || rest.first() == Some(&b'/') && glob_match(&rest[1..], text)
+        }
+        [b'*', rest @ ..] => {
+            for i in 0..=text.len() {
+                if glob_match(rest, &text[i..]) {
+                    return true;
+                }
+                if text.get(i) == Some(&b'/') {
+                    break;
+                }
+            }
+            false
+        }
+        [b'?', rest @ ..] => matches!(text, [c, tail @ ..] if *c != b'/' && glob_match(rest, tail)),
+        [c, rest @ ..] => matches!(text, [t, tail @ ..] if t == c && glob_match(rest, tail)),
+    }
+}
+
+/// The real locations `path` refers to: the existing part canonicalized,
+/// with whatever doesn't exist yet (a destination) appended. For a
+/// symlink, both the link and its target.
+fn resolve(path: &str) -> Result<Vec<PathBuf>, String> {
+    let absolute = std::path::absolute(expand_home(path)).map_err(|e| e.to_string())?;
+
+    let mut existing = absolute.as_path();
+    let mut missing = Vec::new();
+    while fs::symlink_metadata(existing).is_err() {
+        match existing.components().next_back() {
+            Some(Component::Normal(name)) => missing.push(name),
+            Some(Component::ParentDir) => {
+                return Err(format!("{path} has '..' after a directory that doesn't exist"));
+            }
+            _ => {}
+        }
+        existing = existing.parent().ok_or_else(|| format!("{path} doesn't exist"))?;
+    }
+
+    let mut target = fs::canonicalize(existing).map_err(|e| format!("can't resolve {path}: {e}"))?;
+    target.extend(missing.iter().rev());
+
+    let mut paths = vec![target];
+    let is_link = fs::symlink_metadata(&absolute).is_ok_and(|m| m.file_type().is_symlink());
+    if is_link
+        && let (Some(parent), Some(name)) = (absolute.parent(), absolute.file_name())
+    {
+        let parent = fs::canonicalize(parent).map_err(|e| format!("can't resolve {path}: {e}"))?;
+        paths.push(parent.join(name));
+    }
+    Ok(paths)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scope(allowed: &[String], denied: &[String]) -> PathScope {
+        PathScope::new(allowed, denied).unwrap()
+    }
+
+    /// A temporary directory with `project/.git/config`, `project/src/main.rs`
+    /// and `keys/id.key`, and its canonical path.
+    fn tree() -> (tempfile::TempDir, String) {
+        let dir = tempfile::tempdir().unwrap();
+        let root = fs::canonicalize(dir.path()).unwrap().to_string_lossy().into_owned();
+        for (path, content) in [("project/.git/config", "x"), ("project/src/main.rs", "x"), ("keys/id.key", "x")] {
+            let path = dir.path().join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, content).unwrap();
+        }
+        (dir, root)
+    }
+
+    fn kind(result: Result<(), DaemonError>) -> &'static str {
+        result.map_or_else(|e| e.kind(), |()| "ok")
+    }
+
+    #[test]
+    fn globs() {
+        let matches = |pattern: &str, text: &str| glob_match(pattern.as_bytes(), text.as_bytes());
+        assert!(matches("/a/*.txt", "/a/b.txt"));
+        assert!(!matches("/a/*.txt", "/a/b/c.txt"));
+        assert!(matches("/a/**/*.txt", "/a/b/c.txt"));
+        assert!(matches("/a/**/*.txt", "/a/c.txt"));
+        assert!(matches("/a/**", "/a/b/c"));
+        assert!(matches("/a/?.txt", "/a/b.txt"));
+        assert!(!matches("/a/?.txt", "/a/bb.txt"));
+        assert!(!matches("/a?b", "/a/b"));
+        assert!(!matches("/a/b", "/a/bc"));
+    }
+
+    #[test]
+    fn patterns_cover_what_is_inside_what_they_match() {
+        assert!(covers("/a/*/.git", Path::new("/a/b/.git/config")));
+        assert!(covers("/a", Path::new("/a")));
+        assert!(!covers("/a", Path::new("/ab")));
+        assert!(!covers("/a/b", Path::new("/a")));
+    }
+
+    #[test]
+    fn patterns_expand_home_and_drop_trailing_slashes() {
+        let home = dirs::home_dir().unwrap();
+        let home = fs::canonicalize(&home).unwrap_or(home);
+        assert_eq!(expand_pattern("~/**/.git/"), format!("{}/**/.git", home.display()));
+        assert_eq!(expand_pattern("*.key"), "*.key");
+    }
+
+    #[test]
+    fn checks_denied_and_allowed_paths() {
+        let (_dir, root) = tree();
+        let scope = scope(&[format!("{root}/project")], &[format!("{root}/**/.git")]);
+        let check = |path: String| kind(scope.check("file_ops", "path", Some(&path)));
+
+        assert_eq!(check(format!("{root}/project/src/main.rs")), "ok");
+        assert_eq!(check(format!("{root}/project/.git/config")), "permission_denied");
+        assert_eq!(check(format!("{root}/keys/id.key")), "permission_denied");
+        // `..` is resolved before checking
+        assert_eq!(check(format!("{root}/project/../keys")), "permission_denied");
+        // Destinations that don't exist yet are checked where they'd be
+        assert_eq!(check(format!("{root}/project/new/file")), "ok");
+        assert_eq!(check(format!("{root}/missing/../project")), "invalid_argument");
+    }
+
+    #[test]
+    fn a_missing_path_is_denied_only_with_allowed_paths() {
+        let denied_only = scope(&[], &["/x".into()]);
+        assert_eq!(kind(denied_only.check("file_search", "path", None)), "ok");
+        let allowed = scope(&["/x".into()], &[]);
+        assert_eq!(kind(allowed.check("file_search", "path", None)), "permission_denied");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn symlinks_are_checked_where_they_are_and_where_they_point() {
+        let (dir, root) = tree();
+        std::os::unix::fs::symlink(dir.path().join("keys"), dir.path().join("project/keys")).unwrap();
+        std::os::unix::fs::symlink(dir.path().join("project/src"), dir.path().join("src")).unwrap();
+        let scope = scope(&[format!("{root}/project")], &[]);
+        let check = |path: String| kind(scope.check("file_ops", "path", Some(&path)));
+
+        assert_eq!(check(format!("{root}/project/keys")), "permission_denied");
+        assert_eq!(check(format!("{root}/src")), "permission_denied");
+    }
+
+    #[test]
+    fn trees_with_denied_paths_inside_are_refused() {
+        let (_dir, root) = tree();
+        let scope = scope(&[], &[format!("{root}/**/.git"), format!("{root}/**/*.key")]);
+        let check = |path: String| kind(scope.check_tree("file_ops", "source", Some(&path)));
+
+        assert_eq!(check(root.clone()), "permission_denied");
+        assert_eq!(check(format!("{root}/project")), "permission_denied");
+        assert_eq!(check(format!("{root}/project/src")), "ok");
+        assert_eq!(check(format!("{root}/project/src/main.rs")), "ok");
+        assert_eq!(kind(scope.check_tree("file_ops", "source", None)), "ok");
+    }
+
+    #[test]
+    fn permits_found_paths_by_the_same_rules() {
+        let (_dir, root) = tree();
+        let scope = scope(&[format!("{root}/project")], &[format!("{root}/**/.git")]);
+        assert!(scope.permits(&format!("{root}/project/src/main.rs")));
+        assert!(!scope.permits(&format!("{root}/project/.git")));
+        assert!(!scope.permits(&format!("{root}/keys/id.key")));
+    }
+}
@@ -0,0 +1,210 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::permissions::RateLimit;
+
+/// Token buckets for the rate limits in permissions.toml, shared by every
+/// session. Each bucket holds up to `burst` calls and refills at
+/// `max_per_minute`. A bucket whose limit changes (permissions reload)
+/// starts over full.
+#[derive(Default)]
+pub struct RateLimiter {
+    buckets: Mutex<HashMap<String, Bucket>>,
+}
+
+struct Bucket {
+    limit: RateLimit,
+    tokens: f64,
+    updated: Instant,
+}
+
+impl Bucket {
+    fn new(limit: RateLimit, now: Instant) -> Self {
+        Self {
+            limit,
+            tokens: limit.burst() as f64,
+            updated: now,
+        }
+    }
+
+    fn per_second(&self) -> f64 {
+        self.limit.max_per_minute.max(1) as f64 / 60.0
+    }
+
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now.duration_since(self.updated).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.per_second()).min(self.limit.burst() as f64);
+        self.updated = now;
+    }
+
+    /// How long until the bucket has a call to give.
+    fn wait(&self) -> Duration {
+        Duration::from_secs_f64(((1.0 - self.tokens) / self.per_second()).max(0.0))
+    }
+}
+
+impl RateLimiter {
+    /// Take a call from each bucket in `limits` (keyed by what they limit),
+    /// or from none of them if any is empty. On failure, returns the index
+    /// of the limit whose bucket takes longest to refill, and how long
+    /// that is.
+    pub fn acquire(&self, limits: &[(String, RateLimit)]) -> Result<(), (usize, Duration)> {
+        self.acquire_at(limits, Instant::now())
+    }
+
+    fn acquire_at(&self, limits: &[(String, RateLimit)], now: Instant) -> Result<(), (usize, Duration)> {
+        let mut buckets = self.buckets.lock().unwrap_or_else(|e| e.into_inner());
+        for (key, limit) in limits {
+            let bucket = buckets.entry(key.clone()).or_insert_with(|| Bucket::new(*limit, now));
+            if bucket.limit != *limit {
+                *bucket = Bucket::new(*limit, now);
+            }
+            bucket.refill(now);
+        }
+
+        let exhausted = limits
+            .iter()
+            .enumerate()
+            .map(|(i, (key, _))| (i, buckets[key].wait()))
+            .filter(|(_, wait)| !wait.is_zero())
+            .max_by_key(|(_, wait)| *wait);
+        if let Some(exhausted) = exhausted {
+            return Err(exhausted);
+        }
+
+        for (key, _) in limits {
+            if let Some(bucket) = buckets.get_mut(key) {
+                bucket.tokens -= 1.0;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// How a tool's rate limits read at the end of its description.
+pub fn describe(capability_name: &str, capability: Option<RateLimit>, tool: Option<RateLimit>) -> Option<String> {
+    let rate = |limit: RateLimit| {
+        format!(
+            "{} calls per minute (at most {} in a row)",
+            limit.max_per_minute,
+            limit.burst()
+        )
+    };
+    match (tool, capability) {
+        (None, None) => None,
+        (Some(tool), None) => Some(format!("Rate limited to {}.", rate(tool))),
+        (None, Some(cap)) => Some(format!(
+            "Rate limited to {} across all {capability_name} tools.",
+            rate(cap)
+        )),
+        (Some(tool), Some(cap)) => Some(format!(
+            "Rate limited to {}, and to {} across all {capability_name} tools.",
+            rate(tool),
+            rate(cap)
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limit(max_per_minute: u32, burst: Option<u32>) -> RateLimit {
+        RateLimit { max_per_minute, burst }
+    }
+
+    #[test]
+    fn allows_a_burst_then_one_call_per_interval() {
+        let limiter = RateLimiter::default();
+        let limits = [("tool".to_string(), limit(60, Some(3)))];
+        let start = Instant::now();
+        for _ in 0..3 {
+            assert_eq!(limiter.acquire_at(&limits, start), Ok(()));
+        }
+        let (index, wait) = limiter.acquire_at(&limits, start).unwrap_err();
+        assert_eq!(index, 0);
+        assert_eq!(wait, Duration::from_secs(1));
+
+        // Half a call refilled: still half a second to go
+        let (_, wait) = limiter.acquire_at(&limits, start + Duration::from_millis(500)).unwrap_err();
+        assert_eq!(wait, Duration::from_millis(500));
+        assert_eq!(limiter.acquire_at(&limits, start + Duration::from_secs(1)), Ok(()));
+        assert!(limiter.acquire_at(&limits, start + Duration::from_secs(1)).is_err());
+    }
+
+    #[test]
+    fn refills_up_to_the_burst() {
+        let limiter = RateLimiter::default();
+        let limits = [("tool".to_string(), limit(60, Some(2)))];
+        let start = Instant::now();
+        let later = start + Duration::from_secs(3600);
+        assert_eq!(limiter.acquire_at(&limits, start), Ok(()));
+        for _ in 0..2 {
+            assert_eq!(limiter.acquire_at(&limits, later), Ok(()));
+        }
+        assert!(limiter.acquire_at(&limits, later).is_err());
+    }
+
+    #[test]
+    fn burst_defaults_to_max_per_minute() {
+        let limiter = RateLimiter::default();
+        let limits = [("tool".to_string(), limit(5, None))];
+        let start = Instant::now();
+        for _ in 0..5 {
+            assert_eq!(limiter.acquire_at(&limits, start), Ok(()));
+        }
+        let (_, wait) = limiter.acquire_at(&limits, start).unwrap_err();
+        assert_eq!(wait, Duration::from_secs(12));
+    }
+
+    #[test]
+    fn takes_from_every_bucket_or_none() {
+        let limiter = RateLimiter::default();
+        let capability = ("capability".to_string(), limit(60, Some(2)));
+        let tool = ("tool".to_string(), limit(60, Some(1)));
+        let start = Instant::now();
+        assert_eq!(limiter.acquire_at(&[capability.clone(), tool.clone()], start), Ok(()));
+
+        // The tool's bucket is empty, so the capability's keeps its call
+        let (index, _) = limiter.acquire_at(&[capability.clone(), tool.clone()], start).unwrap_err();
+        assert_eq!(index, 1);
+        assert_eq!(limiter.acquire_at(std::slice::from_ref(&capability), start), Ok(()));
+        assert!(limiter.acquire_at(std::slice::from_ref(&capability), start).is_err());
+    }
+
+    #[test]
+    fn reports_the_limit_that_takes_longest_to_refill() {
+        let limiter = RateLimiter::default();
+        let limits = [
+            ("fast".to_string(), limit(60, Some(1))),
+            ("slow".to_string(), limit(6, Some(1))),
+        ];
+        let start = Instant::now();
+        assert_eq!(limiter.acquire_at(&limits, start), Ok(()));
+        assert_eq!(limiter.acquire_at(&limits, start), Err((1, Duration::from_secs(10))));
+    }
+
+    #[test]
+    fn a_changed_limit_starts_over_full() {
+        let limiter = RateLimiter::default();
+        let start = Instant::now();
+        assert_eq!(limiter.acquire_at(&[("tool".to_string(), limit(1, None))], start), Ok(()));
+        assert!(limiter.acquire_at(&[("tool".to_string(), limit(1, None))], start).is_err());
+        assert_eq!(limiter.acquire_at(&[("tool".to_string(), limit(2, None))], start), Ok(()));
+    }
+
+    #[test]
+    fn describes_tool_and_capability_limits() {
+        assert_eq!(describe("Clipboard", None, None), None);
+        assert_eq!(
+            describe("Clipboard", None, Some(limit(10, Some(2)))).unwrap(),
+            "Rate limited to 10 calls per minute (at most 2 in a row)."
+        );
+        assert_eq!(
+            describe("Clipboard", Some(limit(30, None)), Some(limit(10, Some(2)))).unwrap(),
+            "Rate limited to 10 calls per minute (at most 2 in a row), \
+             and to 30 calls per minute (at most 30 in a row) across all Clipboard tools."
+        );
+    }
+}
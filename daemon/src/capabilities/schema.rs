@@ -0,0 +1,210 @@
+use serde_json::{Map, Value};
+
+/// A tool argument that doesn't match the tool's input schema.
+#[derive(Debug)]
+pub struct SchemaError {
+    /// Path of the offending argument, e.g. `level` or `steps[2].tool`.
+    /// Empty when the arguments as a whole are wrong.
+    pub field: String,
+    pub message: String,
+}
+
+/// Check `arguments` against a tool's input schema and fill in the
+/// `default` of every missing property, so providers can rely on both.
+///
+/// Covers the parts of JSON Schema our tool definitions use: `type`,
+/// `properties`, `required`, `items`, `enum`, `minimum` and `maximum`.
+/// Integral floats (`5.0`) are accepted for integers and normalized.
+pub fn validate(schema: &Value, arguments: &mut Value) -> Result<(), SchemaError> {
+    // Clients may leave out `arguments` for tools without parameters
+    if arguments.is_null() {
+        *arguments = Value::Object(Map::new());
+    }
+    check(schema, arguments, "")
+}
+
+fn check(schema: &Value, value: &mut Value, path: &str) -> Result<(), SchemaError> {
+    if let Some(expected) = schema.get("type") {
+        check_type(expected, value, path)?;
+    }
+
+    if let Some(allowed) = schema["enum"].as_array()
+        && !allowed.contains(value)
+    {
+        let allowed: Vec<String> = allowed.iter().map(Value::to_string).collect();
+        return Err(error(path, format!("must be one of {}, got {value}", allowed.join(", "))));
+    }
+
+    if let Some(n) = value.as_f64() {
+        if let Some(min) = schema.get("minimum").filter(|m| m.as_f64().is_some_and(|m| n < m)) {
+            return Err(error(path, format!("must be at least {min}, got {value}")));
+        }
+        if let Some(max) = schema.get("maximum").filter(|m| m.as_f64().is_some_and(|m| n > m)) {
+            return Err(error(path, format!("must be at most {max}, got {value}")));
+        }
+    }
+
+    match value {
+        Value::Object(map) => {
+            for (name, property) in schema["properties"].as_object().into_iter().flatten() {
+                match map.get_mut(name) {
+                    Some(v) => check(property, v, &join(path, name))?,
+                    None => {
+                        if let Some(default) = property.get("default") {
+                            map.insert(name.clone(), default.clone());
+                        }
+                    }
+                }
+            }
+            for name in schema["required"].as_array().into_iter().flatten().filter_map(Value::as_str) {
+                if !map.contains_key(name) {
+                    return Err(error(&join(path, name), "is required".into()));
+                }
+            }
+        }
+        Value::Array(items) => {
+            if let Some(item_schema) = schema.get("items") {
+                for (i, item) in items.iter_mut().enumerate() {
+                    check(item_schema, item, &format!("{path}[{i}]"))?;
+                }
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// `type` may be a single type name or a list of them.
+fn check_type(expected: &Value, value: &mut Value, path: &str) -> Result<(), SchemaError> {
+    let names: Vec<&str> = match expected {
+        Value::Array(names) => names.iter().filter_map(Value::as_str).collect(),
+        other => other.as_str().into_iter().collect(),
+    };
+    if names.is_empty() || names.iter().any(|name| is_type(name, value)) {
+        if names.contains(&"integer") && value.is_f64() {
+            // Integral, or is_type would have failed
+            *value = Value::from(value.as_f64().unwrap_or_default() as i64);
+        }
+        return Ok(());
+    }
+    Err(error(path, format!("must be {}, got {}", names.join(" or "), type_name(value))))
+}
+
+fn is_type(name: &str, value: &Value) -> bool {
+    match name {
+        "object" => value.is_object(),
+        "array" => value.is_array(),
+        "string" => value.is_string(),
+        "boolean" => value.is_boolean(),
+        "null" => value.is_null(),
+        "number" => value.is_number(),
+        "integer" => value.is_i64() || value.is_u64() || value.as_f64().is_some_and(|n| n.fract() == 0.0),
+        _ => true,
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(n) if n.is_f64() => "number",
+        Value::Number(_) => "integer",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+fn join(path: &str, name: &str) -> String {
+    if path.is_empty() {
+        name.to_string()
+    } else {
+        format!("{path}.{name}")
+    }
+}
+
+fn error(path: &str, message: String) -> SchemaError {
+    SchemaError {
+        field: path.to_string(),
+        message,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn schema() -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "name": { "type": "string" },
+                "limit": { "type": "integer", "default": 20, "minimum": 1, "maximum": 100 },
+                "mode": { "type": "string", "enum": ["fast", "slow"], "default": "fast" },
+                "steps": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "properties": { "tool": { "type": "string" }, "retries": { "type": "integer", "default": 0 } },
+                        "required": ["tool"]
+                    }
+                }
+            },
+            "required": ["name"]
+        })
+    }
+
+    fn validate(mut arguments: Value) -> Result<Value, SchemaError> {
+        super::validate(&schema(), &mut arguments).map(|()| arguments)
+    }
+
+    #[test]
+    fn fills_in_defaults() {
+        let arguments = validate(json!({ "name": "x", "steps": [{ "tool": "a" }] })).unwrap();
+        assert_eq!(
+            arguments,
+            json!({ "name": "x", "limit": 20, "mode": "fast", "steps": [{ "tool": "a", "retries": 0 }] })
+        );
+    }
+
+    #[test]
+    fn keeps_given_values() {
+        let arguments = validate(json!({ "name": "x", "limit": 5, "mode": "slow" })).unwrap();
+        assert_eq!(arguments, json!({ "name": "x", "limit": 5, "mode": "slow" }));
+    }
+
+    #[test]
+    fn null_arguments_are_an_empty_object() {
+        let mut arguments = Value::Null;
+        super::validate(&json!({ "type": "object", "properties": {} }), &mut arguments).unwrap();
+        assert_eq!(arguments, json!({}));
+    }
+
+    #[test]
+    fn integral_floats_are_integers() {
+        assert_eq!(validate(json!({ "name": "x", "limit": 5.0 })).unwrap()["limit"], json!(5));
+        let e = validate(json!({ "name": "x", "limit": 5.5 })).unwrap_err();
+        assert_eq!((e.field.as_str(), e.message.as_str()), ("limit", "must be integer, got number"));
+    }
+
+    #[test]
+    fn rejects_values_out_of_bounds() {
+        let e = validate(json!({ "name": "x", "limit": -1 })).unwrap_err();
+        assert_eq!((e.field.as_str(), e.message.as_str()), ("limit", "must be at least 1, got -1"));
+        let e = validate(json!({ "name": "x", "limit": 101 })).unwrap_err();
+        assert_eq!(e.message, "must be at most 100, got 101");
+    }
+
+    #[test]
+    fn reports_the_path_of_the_offending_argument() {
+        let e = validate(json!({})).unwrap_err();
+        assert_eq!((e.field.as_str(), e.message.as_str()), ("name", "is required"));
+        let e = validate(json!({ "name": "x", "steps": [{ "tool": "a" }, { "tool": 1 }] })).unwrap_err();
+        assert_eq!(e.field, "steps[1].tool");
+        let e = validate(json!({ "name": "x", "mode": "medium" })).unwrap_err();
+        assert_eq!(e.message, r#"must be one of "fast", "slow", got "medium""#);
+        let e = validate(json!([])).unwrap_err();
+        assert_eq!((e.field.as_str(), e.message.as_str()), ("", "must be object, got array"));
+    }
+}
@@ -1,6 +1,7 @@
 use serde_json::{json, Value};
-use crate::mcp::types::{CallToolResult, Tool};
-use super::CapabilityProvider;
+use crate::error::DaemonError;
+use crate::mcp::types::{Tool, ToolAnnotations};
+use super::{CapabilityProvider, Progress, ToolResult};
 
 pub struct ScreenshotsProvider;
 
@@ -28,11 +29,14 @@ impl CapabilityProvider for ScreenshotsProvider {
                     "type": "object",
                     "properties": {
                         "display_id": {
-                            "type": "number",
-                            "description": "Optional display number to capture (1-based). Omit to capture the main display."
+                            "type": "integer",
+                            "description": "Optional display number to capture (1-based). Omit to capture the main display.",
+                            "minimum": 1
                         }
                     },
                 }),
+                output_schema: None,
+                annotations: ToolAnnotations::read_only(),
             },
             Tool {
                 name: "screenshot_window".into(),
@@ -41,12 +45,15 @@ impl CapabilityProvider for ScreenshotsProvider {
                     "type": "object",
                     "properties": {
                         "window_id": {
-                            "type": "number",
-                            "description": "The CGWindowID of the window to capture."
+                            "type": "integer",
+                            "description": "The CGWindowID of the window to capture.",
+                            "minimum": 0
                         }
                     },
                     "required": ["window_id"]
                 }),
+                output_schema: None,
+                annotations: ToolAnnotations::read_only(),
             },
             Tool {
                 name: "screenshot_region".into(),
@@ -55,29 +62,33 @@ impl CapabilityProvider for ScreenshotsProvider {
                     "type": "object",
                     "properties": {
                         "x": {
-                            "type": "number",
+                            "type": "integer",
                             "description": "X coordinate of the top-left corner."
                         },
                         "y": {
-                            "type": "number",
+                            "type": "integer",
                             "description": "Y coordinate of the top-left corner."
                         },
                         "width": {
-                            "type": "number",
-                            "description": "Width of the region in pixels."
+                            "type": "integer",
+                            "description": "Width of the region in pixels.",
+                            "minimum": 1
                         },
                         "height": {
-                            "type": "number",
-                            "description": "Height of the region in pixels."
+                            "type": "integer",
+                            "description": "Height of the region in pixels.",
+                            "minimum": 1
                         }
                     },
                     "required": ["x", "y", "width", "height"]
                 }),
+                output_schema: None,
+                annotations: ToolAnnotations::read_only(),
             },
         ]
     }
 
-    fn call(&self, tool_name: &str, arguments: &Value) -> Option<CallToolResult> {
+    fn call(&self, tool_name: &str, arguments: &Value, _progress: &Progress) -> Option<ToolResult> {
         match tool_name {
             "screenshot_screen" => {
                 let display_id = arguments["display_id"].as_u64().map(|v| v as u32);
@@ -86,26 +97,26 @@ impl CapabilityProvider for ScreenshotsProvider {
             "screenshot_window" => {
                 let window_id = match arguments["window_id"].as_u64() {
                     Some(id) => id as u32,
-                    None => return Some(CallToolResult::error("Missing required parameter: window_id")),
+                    None => return Some(Err(DaemonError::invalid_argument("window_id", "is required"))),
                 };
                 Some(screenshot_window(window_id))
             }
             "screenshot_region" => {
                 let x = match arguments["x"].as_i64() {
                     Some(v) => v as i32,
-                    None => return Some(CallToolResult::error("Missing required parameter: x")),
+                    None => return Some(Err(DaemonError::invalid_argument("x", "is required"))),
                 };
                 let y = match arguments["y"].as_i64() {
                     Some(v) => v as i32,
-                    None => return Some(CallToolResult::error("Missing required parameter: y")),
+                    None => return Some(Err(DaemonError::invalid_argument("y", "is required"))),
                 };
                 let width = match arguments["width"].as_u64() {
                     Some(v) => v as u32,
-                    None => return Some(CallToolResult::error("Missing required parameter: width")),
+                    None => return Some(Err(DaemonError::invalid_argument("width", "is required"))),
                 };
                 let height = match arguments["height"].as_u64() {
                     Some(v) => v as u32,
-                    None => return Some(CallToolResult::error("Missing required parameter: height")),
+                    None => return Some(Err(DaemonError::invalid_argument("height", "is required"))),
                 };
                 Some(screenshot_region(x, y, width, height))
             }
@@ -115,33 +126,33 @@ impl CapabilityProvider for ScreenshotsProvider {
 }
 
 #[cfg(target_os = "macos")]
-fn screenshot_screen(display_id: Option<u32>) -> CallToolResult {
+fn screenshot_screen(display_id: Option<u32>) -> ToolResult {
     crate::platform::macos::screenshots::capture_screen(display_id)
 }
 
 #[cfg(target_os = "macos")]
-fn screenshot_window(window_id: u32) -> CallToolResult {
+fn screenshot_window(window_id: u32) -> ToolResult {
     crate::platform::macos::screenshots::capture_window(window_id)
 }
 
 #[cfg(target_os = "macos")]
-fn screenshot_region(x: i32, y: i32, width: u32, height: u32) -> CallToolResult {
+fn screenshot_region(x: i32, y: i32, width: u32, height: u32) -> ToolResult {
     crate::platform::macos::screenshots::capture_region(x, y, width, height)
 }
 
 #[cfg(not(target_os = "macos"))]
-fn screenshot_screen(_display_id: Option<u32>) -> CallToolResult {
-    CallToolResult::error("Screenshots not implemented on this platform")
+fn screenshot_screen(_display_id: Option<u32>) -> ToolResult {
+    Err(DaemonError::NotSupportedOnPlatform("Screenshots".into()))
 }
 
 #[cfg(not(target_os = "macos"))]
-fn screenshot_window(_window_id: u32) -> CallToolResult {
-    CallToolResult::error("Screenshots not implemented on this platform")
+fn screenshot_window(_window_id: u32) -> ToolResult {
+    Err(DaemonError::NotSupportedOnPlatform("Screenshots".into()))
 }
 
 #[cfg(not(target_os = "macos"))]
-fn screenshot_region(_x: i32, _y: i32, _width: u32, _height: u32) -> CallToolResult {
-    CallToolResult::error("Screenshots not implemented on this platform")
+fn screenshot_region(_x: i32, _y: i32, _width: u32, _height: u32) -> ToolResult {
+    Err(DaemonError::NotSupportedOnPlatform("Screenshots".into()))
 }
 
 pub fn provider() -> Box<dyn CapabilityProvider> {
@@ -1,8 +1,8 @@
 use serde_json::{json, Value};
 use sysinfo::{Disks, System};
 
-use crate::mcp::types::{CallToolResult, Tool};
-use super::CapabilityProvider;
+use crate::mcp::types::{CallToolResult, Resource, ResourceContents, Tool, ToolAnnotations};
+use super::{tool_resource, CapabilityProvider, Progress, ToolResult};
 
 pub struct SystemInfoProvider;
 
@@ -30,6 +30,61 @@ impl CapabilityProvider for SystemInfoProvider {
                     "type": "object",
                     "properties": {},
                 }),
+                output_schema: Some(json!({
+                    "type": "object",
+                    "properties": {
+                        "hostname": { "type": "string" },
+                        "os": { "type": "string" },
+                        "kernel": { "type": "string" },
+                        "uptime_seconds": { "type": "integer" },
+                        "cpu": {
+                            "type": "object",
+                            "properties": {
+                                "name": { "type": "string" },
+                                "cores": { "type": "integer" },
+                                "usage_percent": { "type": "number" }
+                            }
+                        },
+                        "memory": {
+                            "type": "object",
+                            "properties": {
+                                "total_mb": { "type": "integer" },
+                                "used_mb": { "type": "integer" },
+                                "available_mb": { "type": "integer" }
+                            }
+                        },
+                        "swap": {
+                            "type": "object",
+                            "properties": {
+                                "total_mb": { "type": "integer" },
+                                "used_mb": { "type": "integer" }
+                            }
+                        },
+                        "disks": {
+                            "type": "array",
+                            "items": {
+                                "type": "object",
+                                "properties": {
+                                    "name": { "type": "string" },
+                                    "mount": { "type": "string" },
+                                    "total_gb": { "type": "number" },
+                                    "free_gb": { "type": "number" },
+                                    "fs": { "type": "string" }
+                                }
+                            }
+                        },
+                        "battery": {
+                            "type": "object",
+                            "description": "macOS only, absent on machines without a battery",
+                            "properties": {
+                                "percent": { "type": "number" },
+                                "charging": { "type": "boolean" }
+                            }
+                        }
+                    },
+                    "required": ["hostname", "os", "kernel", "uptime_seconds", "cpu", "memory", "swap", "disks"]
+                })),
+                annotations: ToolAnnotations::read_only(),
             },
             Tool {
                 name: "system_processes".into(),
@@ -38,32 +93,53 @@ impl CapabilityProvider for SystemInfoProvider {
                     "type": "object",
                     "properties": {
                         "limit": {
-                            "type": "number",
-                            "description": "Max processes to return, sorted by CPU usage (default: 20)"
+                            "type": "integer",
+                            "default": 20,
+                            "description": "Max processes to return, sorted by CPU usage (default: 20)",
+                            "minimum": 1
                         }
                     },
                 }),
+                output_schema: None,
+                annotations: ToolAnnotations::read_only(),
             },
         ]
     }
 
-    fn call(&self, tool_name: &str, arguments: &Value) -> Option<CallToolResult> {
+    fn call(&self, tool_name: &str, arguments: &Value, _progress: &Progress) -> Option<ToolResult> {
         match tool_name {
-            "system_info" => Some(get_system_info()),
+            "system_info" => Some(Ok(get_system_info())),
             "system_processes" => {
-                let limit = arguments["limit"].as_u64().unwrap_or(20) as usize;
-                Some(get_processes(limit))
+                let limit = arguments["limit"].as_u64()? as usize;
+                Some(Ok(get_processes(limit)))
             }
             _ => None,
         }
     }
+
+    fn resources(&self) -> Vec<Resource> {
+        vec![Resource {
+            uri: "familiar://system/info".into(),
+            name: "system_info".into(),
+            description: "Current CPU, memory, disk, battery, OS version and uptime.".into(),
+            mime_type: "application/json".into(),
+            tool: "system_info",
+        }]
+    }
+
+    fn read_resource(&self, uri: &str) -> Option<Result<ResourceContents, String>> {
+        match uri {
+            "familiar://system/info" => Some(tool_resource(uri, "application/json", Ok(get_system_info()))),
+            _ => None,
+        }
+    }
 }
 
 fn get_system_info() -> CallToolResult {
     let mut sys = System::new_all();
     sys.refresh_all();
 
-    let mut info = json!({
+    let info = json!({
         "hostname": System::host_name().unwrap_or_default(),
         "os": System::long_os_version().unwrap_or_default(),
         "kernel": System::kernel_version().unwrap_or_default(),
@@ -91,18 +167,26 @@ fn get_system_info() -> CallToolResult {
         })).collect::<Vec<_>>(),
     });
 
-    // Add battery info (macOS only)
-    #[cfg(target_os = "macos")]
-    if let Some((percent, charging)) = crate::platform::macos::system_info::get_battery() {
-        if let Some(obj) = info.as_object_mut() {
-            obj.insert("battery".into(), json!({
-                "percent": percent,
-                "charging": charging,
-            }));
-        }
+    CallToolResult::json(&with_battery(info))
+}
+
+/// Add battery info (macOS only)
+#[cfg(target_os = "macos")]
+fn with_battery(mut info: Value) -> Value {
+    if let Some((percent, charging)) = crate::platform::macos::system_info::get_battery()
+        && let Some(obj) = info.as_object_mut()
+    {
+        obj.insert("battery".into(), json!({
+            "percent": percent,
+            "charging": charging,
+        }));
     }
+    info
+}
 
-    CallToolResult::json(&info)
+#[cfg(not(target_os = "macos"))]
+fn with_battery(info: Value) -> Value {
+    info
 }
 
 fn get_processes(limit: usize) -> CallToolResult {
@@ -1,6 +1,8 @@
 use serde_json::{json, Value};
-use crate::mcp::types::{CallToolResult, Tool};
-use super::CapabilityProvider;
+#[cfg(not(target_os = "macos"))]
+use crate::error::DaemonError;
+use crate::mcp::types::{Tool, ToolAnnotations};
+use super::{matching, CapabilityProvider, Progress, ToolResult};
 
 pub struct TerminalProvider;
 
@@ -28,6 +30,8 @@ impl CapabilityProvider for TerminalProvider {
                     "type": "object",
                     "properties": {},
                 }),
+                output_schema: None,
+                annotations: ToolAnnotations::read_only(),
             },
             Tool {
                 name: "terminal_send_keys".into(),
@@ -45,11 +49,14 @@ impl CapabilityProvider for TerminalProvider {
                         },
                         "literal": {
                             "type": "boolean",
+                            "default": false,
                             "description": "Send as literal text instead of interpreting key names (default: false)"
                         }
                     },
                     "required": ["target", "keys"]
                 }),
+                output_schema: None,
+                annotations: ToolAnnotations::destructive(),
             },
             Tool {
                 name: "terminal_capture".into(),
@@ -62,12 +69,16 @@ impl CapabilityProvider for TerminalProvider {
                             "description": "Target session/pane (e.g. \"main:0.0\" for tmux, \"1\" for Terminal.app window index)"
                         },
                         "lines": {
-                            "type": "number",
-                            "description": "Number of lines to capture from the bottom (default: 50)"
+                            "type": "integer",
+                            "default": 50,
+                            "description": "Number of lines to capture from the bottom (default: 50)",
+                            "minimum": 1
                         }
                     },
                     "required": ["target"]
                 }),
+                output_schema: None,
+                annotations: ToolAnnotations::read_only(),
             },
             Tool {
                 name: "terminal_create".into(),
@@ -89,22 +100,24 @@ impl CapabilityProvider for TerminalProvider {
                         }
                     },
                 }),
+                output_schema: None,
+                annotations: ToolAnnotations::additive(),
             },
         ]
     }
 
-    fn call(&self, tool_name: &str, arguments: &Value) -> Option<CallToolResult> {
+    fn call(&self, tool_name: &str, arguments: &Value, _progress: &Progress) -> Option<ToolResult> {
         match tool_name {
             "terminal_list_sessions" => Some(terminal_list_sessions()),
             "terminal_send_keys" => {
                 let target = arguments["target"].as_str().unwrap_or("");
                 let keys = arguments["keys"].as_str().unwrap_or("");
-                let literal = arguments["literal"].as_bool().unwrap_or(false);
+                let literal = arguments["literal"].as_bool()?;
                 Some(terminal_send_keys(target, keys, literal))
             }
             "terminal_capture" => {
                 let target = arguments["target"].as_str().unwrap_or("");
-                let lines = arguments["lines"].as_u64().unwrap_or(50) as usize;
+                let lines = arguments["lines"].as_u64()? as usize;
                 Some(terminal_capture(target, lines))
             }
             "terminal_create" => {
@@ -116,48 +129,59 @@ impl CapabilityProvider for TerminalProvider {
             _ => None,
         }
     }
+
+    /// tmux panes (`session:window.pane`) and sessions.
+    fn complete(&self, tool_name: &str, argument: &str, prefix: &str) -> Vec<String> {
+        if !matches!(tool_name, "terminal_send_keys" | "terminal_capture") || argument != "target" {
+            return Vec::new();
+        }
+        let listing = terminal_list_sessions().ok().and_then(|r| r.structured_content).unwrap_or_default();
+        let panes = listing["panes"].as_array().into_iter().flatten().filter_map(|p| p["target"].as_str());
+        let sessions = listing["sessions"].as_array().into_iter().flatten().filter_map(|s| s["name"].as_str());
+        matching(panes.chain(sessions).map(str::to_string), prefix)
+    }
 }
 
 // ── Platform dispatch ──────────────────────────────────────────────────────
 
 #[cfg(target_os = "macos")]
-fn terminal_list_sessions() -> CallToolResult {
+fn terminal_list_sessions() -> ToolResult {
     crate::platform::macos::terminal::list_sessions()
 }
 
 #[cfg(target_os = "macos")]
-fn terminal_send_keys(target: &str, keys: &str, literal: bool) -> CallToolResult {
+fn terminal_send_keys(target: &str, keys: &str, literal: bool) -> ToolResult {
     crate::platform::macos::terminal::send_keys(target, keys, literal)
 }
 
 #[cfg(target_os = "macos")]
-fn terminal_capture(target: &str, lines: usize) -> CallToolResult {
+fn terminal_capture(target: &str, lines: usize) -> ToolResult {
     crate::platform::macos::terminal::capture(target, lines)
 }
 
 #[cfg(target_os = "macos")]
-fn terminal_create(name: Option<&str>, command: Option<&str>, directory: Option<&str>) -> CallToolResult {
+fn terminal_create(name: Option<&str>, command: Option<&str>, directory: Option<&str>) -> ToolResult {
     crate::platform::macos::terminal::create_session(name, command, directory)
 }
 
 #[cfg(not(target_os = "macos"))]
-fn terminal_list_sessions() -> CallToolResult {
-    CallToolResult::error("Terminal automation not implemented on this platform")
+fn terminal_list_sessions() -> ToolResult {
+    Err(DaemonError::NotSupportedOnPlatform("Terminal automation".into()))
 }
 
 #[cfg(not(target_os = "macos"))]
-fn terminal_send_keys(_target: &str, _keys: &str, _literal: bool) -> CallToolResult {
-    CallToolResult::error("Terminal automation not implemented on this platform")
+fn terminal_send_keys(_target: &str, _keys: &str, _literal: bool) -> ToolResult {
+    Err(DaemonError::NotSupportedOnPlatform("Terminal automation".into()))
 }
 
 #[cfg(not(target_os = "macos"))]
-fn terminal_capture(_target: &str, _lines: usize) -> CallToolResult {
-    CallToolResult::error("Terminal automation not implemented on this platform")
+fn terminal_capture(_target: &str, _lines: usize) -> ToolResult {
+    Err(DaemonError::NotSupportedOnPlatform("Terminal automation".into()))
 }
 
 #[cfg(not(target_os = "macos"))]
-fn terminal_create(_name: Option<&str>, _command: Option<&str>, _directory: Option<&str>) -> CallToolResult {
-    CallToolResult::error("Terminal automation not implemented on this platform")
+fn terminal_create(_name: Option<&str>, _command: Option<&str>, _directory: Option<&str>) -> ToolResult {
+    Err(DaemonError::NotSupportedOnPlatform("Terminal automation".into()))
 }
 
 pub fn provider() -> Box<dyn CapabilityProvider> {
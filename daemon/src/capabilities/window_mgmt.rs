@@ -1,7 +1,10 @@
 use serde_json::{json, Value};
 
-use crate::mcp::types::{CallToolResult, Tool};
-use super::CapabilityProvider;
+use crate::error::DaemonError;
+#[cfg(target_os = "macos")]
+use crate::platform::macos::ax_helpers::ensure_trusted;
+use crate::mcp::types::{CallToolResult, Resource, ResourceContents, Tool, ToolAnnotations};
+use super::{observed, planned, tool_resource, CapabilityProvider, Progress, ToolResult};
 
 pub struct WindowMgmtProvider;
 
@@ -34,6 +37,32 @@ impl CapabilityProvider for WindowMgmtProvider {
                         }
                     },
                 }),
+                output_schema: Some(json!({
+                    "type": "object",
+                    "properties": {
+                        "windows": {
+                            "type": "array",
+                            "items": {
+                                "type": "object",
+                                "properties": {
+                                    "id": { "type": "integer" },
+                                    "pid": { "type": "integer" },
+                                    "owner_name": { "type": "string" },
+                                    "title": { "type": "string" },
+                                    "x": { "type": "number" },
+                                    "y": { "type": "number" },
+                                    "width": { "type": "number" },
+                                    "height": { "type": "number" },
+                                    "layer": { "type": "integer" },
+                                    "on_screen": { "type": "boolean" }
+                                },
+                                "required": ["id", "pid", "owner_name", "title"]
+                            }
+                        }
+                    },
+                    "required": ["windows"]
+                })),
+                annotations: ToolAnnotations::read_only(),
             },
             Tool {
                 name: "window_focus".into(),
@@ -42,12 +71,15 @@ impl CapabilityProvider for WindowMgmtProvider {
                     "type": "object",
                     "properties": {
                         "window_id": {
-                            "type": "number",
-                            "description": "The window ID to focus"
+                            "type": "integer",
+                            "description": "The window ID to focus",
+                            "minimum": 0
                         }
                     },
                     "required": ["window_id"]
                 }),
+                output_schema: None,
+                annotations: ToolAnnotations::additive().idempotent(),
             },
             Tool {
                 name: "window_move".into(),
@@ -56,8 +88,9 @@ impl CapabilityProvider for WindowMgmtProvider {
                     "type": "object",
                     "properties": {
                         "window_id": {
-                            "type": "number",
-                            "description": "The window ID to move"
+                            "type": "integer",
+                            "description": "The window ID to move",
+                            "minimum": 0
                         },
                         "x": {
                             "type": "number",
@@ -70,6 +103,8 @@ impl CapabilityProvider for WindowMgmtProvider {
                     },
                     "required": ["window_id", "x", "y"]
                 }),
+                output_schema: None,
+                annotations: ToolAnnotations::additive().idempotent(),
             },
             Tool {
                 name: "window_resize".into(),
@@ -78,8 +113,9 @@ impl CapabilityProvider for WindowMgmtProvider {
                     "type": "object",
                     "properties": {
                         "window_id": {
-                            "type": "number",
-                            "description": "The window ID to resize"
+                            "type": "integer",
+                            "description": "The window ID to resize",
+                            "minimum": 0
                         },
                         "width": {
                             "type": "number",
@@ -92,6 +128,8 @@ impl CapabilityProvider for WindowMgmtProvider {
                     },
                     "required": ["window_id", "width", "height"]
                 }),
+                output_schema: None,
+                annotations: ToolAnnotations::additive().idempotent(),
             },
             Tool {
                 name: "window_minimize".into(),
@@ -100,12 +138,15 @@ impl CapabilityProvider for WindowMgmtProvider {
                     "type": "object",
                     "properties": {
                         "window_id": {
-                            "type": "number",
-                            "description": "The window ID to minimize"
+                            "type": "integer",
+                            "description": "The window ID to minimize",
+                            "minimum": 0
                         }
                     },
                     "required": ["window_id"]
                 }),
+                output_schema: None,
+                annotations: ToolAnnotations::additive().idempotent(),
             },
             Tool {
                 name: "window_close".into(),
@@ -114,17 +155,20 @@ impl CapabilityProvider for WindowMgmtProvider {
                     "type": "object",
                     "properties": {
                         "window_id": {
-                            "type": "number",
-                            "description": "The window ID to close"
+                            "type": "integer",
+                            "description": "The window ID to close",
+                            "minimum": 0
                         }
                     },
                     "required": ["window_id"]
                 }),
+                output_schema: None,
+                annotations: ToolAnnotations::destructive(),
             },
         ]
     }
 
-    fn call(&self, tool_name: &str, arguments: &Value) -> Option<CallToolResult> {
+    fn call(&self, tool_name: &str, arguments: &Value, _progress: &Progress) -> Option<ToolResult> {
         match tool_name {
             "window_list" => {
                 let app_name = arguments["app_name"].as_str();
@@ -133,117 +177,203 @@ impl CapabilityProvider for WindowMgmtProvider {
             "window_focus" => {
                 let window_id = match arguments["window_id"].as_u64() {
                     Some(id) => id as u32,
-                    None => return Some(CallToolResult::error("Missing required parameter: window_id")),
+                    None => return Some(Err(DaemonError::invalid_argument("window_id", "is required"))),
                 };
                 Some(window_focus(window_id))
             }
             "window_move" => {
                 let window_id = match arguments["window_id"].as_u64() {
                     Some(id) => id as u32,
-                    None => return Some(CallToolResult::error("Missing required parameter: window_id")),
+                    None => return Some(Err(DaemonError::invalid_argument("window_id", "is required"))),
                 };
                 let x = match arguments["x"].as_f64() {
                     Some(v) => v,
-                    None => return Some(CallToolResult::error("Missing required parameter: x")),
+                    None => return Some(Err(DaemonError::invalid_argument("x", "is required"))),
                 };
                 let y = match arguments["y"].as_f64() {
                     Some(v) => v,
-                    None => return Some(CallToolResult::error("Missing required parameter: y")),
+                    None => return Some(Err(DaemonError::invalid_argument("y", "is required"))),
                 };
                 Some(window_move(window_id, x, y))
             }
             "window_resize" => {
                 let window_id = match arguments["window_id"].as_u64() {
                     Some(id) => id as u32,
-                    None => return Some(CallToolResult::error("Missing required parameter: window_id")),
+                    None => return Some(Err(DaemonError::invalid_argument("window_id", "is required"))),
                 };
                 let width = match arguments["width"].as_f64() {
                     Some(v) => v,
-                    None => return Some(CallToolResult::error("Missing required parameter: width")),
+                    None => return Some(Err(DaemonError::invalid_argument("width", "is required"))),
                 };
                 let height = match arguments["height"].as_f64() {
                     Some(v) => v,
-                    None => return Some(CallToolResult::error("Missing required parameter: height")),
+                    None => return Some(Err(DaemonError::invalid_argument("height", "is required"))),
                 };
                 Some(window_resize(window_id, width, height))
             }
             "window_minimize" => {
                 let window_id = match arguments["window_id"].as_u64() {
                     Some(id) => id as u32,
-                    None => return Some(CallToolResult::error("Missing required parameter: window_id")),
+                    None => return Some(Err(DaemonError::invalid_argument("window_id", "is required"))),
                 };
                 Some(window_minimize(window_id))
             }
             "window_close" => {
                 let window_id = match arguments["window_id"].as_u64() {
                     Some(id) => id as u32,
-                    None => return Some(CallToolResult::error("Missing required parameter: window_id")),
+                    None => return Some(Err(DaemonError::invalid_argument("window_id", "is required"))),
                 };
                 Some(window_close(window_id))
             }
             _ => None,
         }
     }
+
+    /// Find the window among the on-screen ones, as the tool would, and
+    /// describe the change from where and how big it is now.
+    fn plan(&self, tool_name: &str, arguments: &Value) -> Option<ToolResult> {
+        let Some(window_id) = arguments["window_id"].as_u64() else {
+            return Some(Err(DaemonError::invalid_argument("window_id", "is required")));
+        };
+        let windows = match observed(window_list(None)) {
+            Ok(windows) => windows,
+            Err(failed) => return Some(failed),
+        };
+        let Some(window) = windows["windows"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .find(|w| w["id"].as_u64() == Some(window_id))
+        else {
+            return Some(Ok(CallToolResult::error(format!("Window {window_id} not found"))));
+        };
+
+        let name = format!(
+            "window {window_id} ('{}' of {})",
+            window["title"].as_str().unwrap_or(""),
+            window["owner_name"].as_str().unwrap_or("")
+        );
+        let (x, y) = (&window["x"], &window["y"]);
+        let (width, height) = (&window["width"], &window["height"]);
+        let effect = match tool_name {
+            "window_focus" => format!("Would bring {name} to the front"),
+            "window_move" => format!(
+                "Would move {name} from ({x}, {y}) to ({}, {})",
+                arguments["x"], arguments["y"]
+            ),
+            "window_resize" => format!(
+                "Would resize {name} from {width}x{height} to {}x{}",
+                arguments["width"], arguments["height"]
+            ),
+            "window_minimize" => format!("Would minimize {name} to the dock"),
+            "window_close" => format!("Would close {name}"),
+            _ => return None,
+        };
+        Some(planned(effect, window.clone()))
+    }
+
+    /// Window IDs, matched by the ID itself or by the window's app name or
+    /// title, so typing "Safari" offers Safari's windows.
+    fn complete(&self, tool_name: &str, argument: &str, prefix: &str) -> Vec<String> {
+        if tool_name == "window_list" || argument != "window_id" {
+            return Vec::new();
+        }
+        let needle = prefix.to_lowercase();
+        let windows = window_list(None).ok().and_then(|r| r.structured_content).unwrap_or_default();
+        windows["windows"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter(|w| {
+                let id = w["id"].to_string();
+                let label = format!("{} {}", w["owner_name"].as_str().unwrap_or(""), w["title"].as_str().unwrap_or(""));
+                id.starts_with(prefix) || label.to_lowercase().contains(&needle)
+            })
+            .map(|w| w["id"].to_string())
+            .collect()
+    }
+
+    fn resources(&self) -> Vec<Resource> {
+        vec![Resource {
+            uri: "familiar://windows".into(),
+            name: "window_list".into(),
+            description: "On-screen windows with ID, app name, title, position and size.".into(),
+            mime_type: "application/json".into(),
+            tool: "window_list",
+        }]
+    }
+
+    fn read_resource(&self, uri: &str) -> Option<Result<ResourceContents, String>> {
+        match uri {
+            "familiar://windows" => Some(tool_resource(uri, "application/json", window_list(None))),
+            _ => None,
+        }
+    }
 }
 
 #[cfg(target_os = "macos")]
-fn window_list(app_name: Option<&str>) -> CallToolResult {
-    crate::platform::macos::window_mgmt::list_windows(app_name)
+fn window_list(app_name: Option<&str>) -> ToolResult {
+    Ok(crate::platform::macos::window_mgmt::list_windows(app_name))
 }
 
 #[cfg(target_os = "macos")]
-fn window_focus(window_id: u32) -> CallToolResult {
-    crate::platform::macos::window_mgmt::focus_window(window_id)
+fn window_focus(window_id: u32) -> ToolResult {
+    ensure_trusted()?;
+    Ok(crate::platform::macos::window_mgmt::focus_window(window_id))
 }
 
 #[cfg(target_os = "macos")]
-fn window_move(window_id: u32, x: f64, y: f64) -> CallToolResult {
-    crate::platform::macos::window_mgmt::move_window(window_id, x, y)
+fn window_move(window_id: u32, x: f64, y: f64) -> ToolResult {
+    ensure_trusted()?;
+    Ok(crate::platform::macos::window_mgmt::move_window(window_id, x, y))
 }
 
 #[cfg(target_os = "macos")]
-fn window_resize(window_id: u32, width: f64, height: f64) -> CallToolResult {
-    crate::platform::macos::window_mgmt::resize_window(window_id, width, height)
+fn window_resize(window_id: u32, width: f64, height: f64) -> ToolResult {
+    ensure_trusted()?;
+    Ok(crate::platform::macos::window_mgmt::resize_window(window_id, width, height))
 }
 
 #[cfg(target_os = "macos")]
-fn window_minimize(window_id: u32) -> CallToolResult {
-    crate::platform::macos::window_mgmt::minimize_window(window_id)
+fn window_minimize(window_id: u32) -> ToolResult {
+    ensure_trusted()?;
+    Ok(crate::platform::macos::window_mgmt::minimize_window(window_id))
 }
 
 #[cfg(target_os = "macos")]
-fn window_close(window_id: u32) -> CallToolResult {
-    crate::platform::macos::window_mgmt::close_window(window_id)
+fn window_close(window_id: u32) -> ToolResult {
+    ensure_trusted()?;
+    Ok(crate::platform::macos::window_mgmt::close_window(window_id))
 }
 
 #[cfg(not(target_os = "macos"))]
-fn window_list(_app_name: Option<&str>) -> CallToolResult {
-    CallToolResult::error("Window management not implemented on this platform")
+fn window_list(_app_name: Option<&str>) -> ToolResult {
+    Err(DaemonError::NotSupportedOnPlatform("Window management".into()))
 }
 
 #[cfg(not(target_os = "macos"))]
-fn window_focus(_window_id: u32) -> CallToolResult {
-    CallToolResult::error("Window management not implemented on this platform")
+fn window_focus(_window_id: u32) -> ToolResult {
+    Err(DaemonError::NotSupportedOnPlatform("Window management".into()))
 }
 
 #[cfg(not(target_os = "macos"))]
-fn window_move(_window_id: u32, _x: f64, _y: f64) -> CallToolResult {
-    CallToolResult::error("Window management not implemented on this platform")
+fn window_move(_window_id: u32, _x: f64, _y: f64) -> ToolResult {
+    Err(DaemonError::NotSupportedOnPlatform("Window management".into()))
 }
 
 #[cfg(not(target_os = "macos"))]
-fn window_resize(_window_id: u32, _width: f64, _height: f64) -> CallToolResult {
-    CallToolResult::error("Window management not implemented on this platform")
+fn window_resize(_window_id: u32, _width: f64, _height: f64) -> ToolResult {
+    Err(DaemonError::NotSupportedOnPlatform("Window management".into()))
 }
 
 #[cfg(not(target_os = "macos"))]
-fn window_minimize(_window_id: u32) -> CallToolResult {
-    CallToolResult::error("Window management not implemented on this platform")
+fn window_minimize(_window_id: u32) -> ToolResult {
+    Err(DaemonError::NotSupportedOnPlatform("Window management".into()))
 }
 
 #[cfg(not(target_os = "macos"))]
-fn window_close(_window_id: u32) -> CallToolResult {
-    CallToolResult::error("Window management not implemented on this platform")
+fn window_close(_window_id: u32) -> ToolResult {
+    Err(DaemonError::NotSupportedOnPlatform("Window management".into()))
 }
 
 pub fn provider() -> Box<dyn CapabilityProvider> {
@@ -1,15 +1,18 @@
 use serde::Deserialize;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use tracing::{info, warn};
 
 #[derive(Debug, Deserialize)]
 pub struct FamiliarConfig {
-    #[allow(dead_code)]
     #[serde(default = "default_version")]
     pub version: u32,
     #[serde(default)]
     pub identity: Identity,
+    #[serde(default)]
+    pub daemon: DaemonSettings,
+    #[serde(default)]
+    pub audit: AuditSettings,
 }
 
 #[derive(Debug, Deserialize)]
@@ -18,14 +21,118 @@ pub struct Identity {
     pub name: String,
 }
 
+/// `[daemon]` section: how MCP clients reach the daemon.
+#[derive(Debug, Deserialize)]
+pub struct DaemonSettings {
+    #[serde(default)]
+    pub transport: Transport,
+    /// Listen address for the HTTP transport. Keep on loopback unless the
+    /// daemon must be reachable from other machines.
+    #[serde(default = "default_http_bind")]
+    pub http_bind: String,
+    /// Socket path for the unix transport (`~` is expanded).
+    #[serde(default = "default_unix_socket")]
+    pub unix_socket: String,
+    /// Only describe what state-changing tools would do, for every call
+    /// (also set by `--dry-run`). Clients can ask for it per call with
+    /// `_meta.dryRun`.
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Transport {
+    /// Spawned as a child process, one client per daemon.
+    #[default]
+    Stdio,
+    /// MCP Streamable HTTP: POST for requests, SSE for server messages.
+    Http,
+    /// Unix domain socket shared by local clients, one session per connection.
+    Unix,
+}
+
+/// `[audit]` section: the log of tool calls (see `audit`).
+#[derive(Debug, Clone, Deserialize)]
+pub struct AuditSettings {
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// JSONL file the calls are appended to (`~` is expanded).
+    #[serde(default = "default_audit_path")]
+    pub path: String,
+    /// Size in megabytes at which the file is rotated.
+    #[serde(default = "default_audit_max_size_mb")]
+    pub max_size_mb: u64,
+    /// Rotated files kept next to the current one (`.1` is the newest).
+    #[serde(default = "default_audit_max_files")]
+    pub max_files: usize,
+    /// Argument fields, at any depth, whose values are left out of the log.
+    #[serde(default = "default_audit_redact")]
+    pub redact: Vec<String>,
+}
+
+/// The config format this daemon understands.
+const CONFIG_VERSION: u32 = 1;
+
 fn default_version() -> u32 {
-    1
+    CONFIG_VERSION
 }
 
 fn default_name() -> String {
     "familiar-daemon".into()
 }
 
+fn default_http_bind() -> String {
+    "127.0.0.1:18792".into()
+}
+
+fn default_unix_socket() -> String {
+    "~/.familiar/daemon.sock".into()
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_audit_path() -> String {
+    "~/.familiar/logs/daemon-audit.jsonl".into()
+}
+
+fn default_audit_max_size_mb() -> u64 {
+    10
+}
+
+fn default_audit_max_files() -> usize {
+    5
+}
+
+fn default_audit_redact() -> Vec<String> {
+    ["text", "value", "keys"].map(String::from).into()
+}
+
+impl Default for DaemonSettings {
+    fn default() -> Self {
+        Self {
+            transport: Transport::default(),
+            http_bind: default_http_bind(),
+            unix_socket: default_unix_socket(),
+            dry_run: false,
+        }
+    }
+}
+
+impl Default for AuditSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            path: default_audit_path(),
+            max_size_mb: default_audit_max_size_mb(),
+            max_files: default_audit_max_files(),
+            redact: default_audit_redact(),
+        }
+    }
+}
+
 impl Default for Identity {
     fn default() -> Self {
         Self {
@@ -37,9 +144,38 @@ impl Default for Identity {
 impl Default for FamiliarConfig {
     fn default() -> Self {
         Self {
-            version: 1,
+            version: CONFIG_VERSION,
             identity: Identity::default(),
+            daemon: DaemonSettings::default(),
+            audit: AuditSettings::default(),
+        }
+    }
+}
+
+impl FamiliarConfig {
+    /// Resolve the gateway token, which also guards the daemon's network
+    /// transports. Search order: token file > env var > None
+    pub fn gateway_token(&self) -> Option<String> {
+        if let Some(home) = dirs::home_dir() {
+            let token_file = home.join(".familiar/gateway.token");
+            if let Ok(t) = fs::read_to_string(&token_file) {
+                let trimmed = t.trim().to_string();
+                if !trimmed.is_empty() {
+                    return Some(trimmed);
+                }
+            }
+        }
+        std::env::var("FAMILIAR_GATEWAY_TOKEN").ok().filter(|t| !t.is_empty())
+    }
+}
+
+/// Expand a leading `~` to the user's home directory.
+pub fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix('~'), dirs::home_dir()) {
+        (Some(rest), Some(home)) if rest.is_empty() || rest.starts_with('/') => {
+            home.join(rest.trim_start_matches('/'))
         }
+        _ => Path::new(path).to_path_buf(),
     }
 }
 
@@ -62,8 +198,17 @@ pub fn load() -> FamiliarConfig {
                         info!(
                             path = %candidate.display(),
                             name = %config.identity.name,
+                            version = config.version,
                             "loaded familiar config"
                         );
+                        if config.version > CONFIG_VERSION {
+                            warn!(
+                                path = %candidate.display(),
+                                version = config.version,
+                                supported = CONFIG_VERSION,
+                                "config is newer than this daemon; unknown settings are ignored"
+                            );
+                        }
                         return config;
                     }
                     Err(e) => {
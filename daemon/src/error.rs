@@ -1,20 +1,207 @@
 use std::fmt;
+use std::io;
+use std::time::Duration;
 
+use serde_json::{json, Value};
+
+/// Why a tool call failed, for failures an agent can act on: clients get
+/// a stable error code and `data` describing the problem (see `kind`),
+/// instead of having to parse the message. Everything else is reported
+/// as a tool result with `isError`.
 #[derive(Debug)]
 pub enum DaemonError {
-    Permission(String),
+    /// macOS or the user refused: `permission` is the privacy permission
+    /// (`accessibility`) or `confirmation` for tools set to `ask`.
+    PermissionDenied { permission: String, message: String },
+    /// The capability has no backend on this OS.
+    NotSupportedOnPlatform(String),
+    /// No tool by this name, or none the client is allowed to call.
+    UnknownTool(String),
+    /// An external program the tool runs isn't installed.
+    #[cfg(any(target_os = "macos", test))]
+    MissingDependency { program: String, hint: String },
+    /// An operation didn't finish in time.
+    Timeout { operation: String, after: Duration },
+    /// Too many calls: `name` is the tool or capability (`scope`) whose
+    /// rate limit in permissions.toml was reached.
+    RateLimited {
+        scope: &'static str,
+        name: String,
+        max_per_minute: u32,
+        burst: u32,
+        retry_after: Duration,
+    },
+    /// An argument value ruled out by a constraint in permissions.toml:
+    /// `rule` is the violated list (`in` or `not_in`), `patterns` what the
+    /// value failed to match or matched, `value` the argument read as
+    /// `what` (e.g. the domain of a URL).
+    PolicyViolation {
+        field: String,
+        what: &'static str,
+        rule: &'static str,
+        patterns: Vec<String>,
+        value: String,
+    },
+    /// Tool arguments that don't match the tool's input schema.
+    InvalidArgument { field: String, message: String },
+    /// The client answered a request from the daemon with an error, or
+    /// went away before answering.
+    Client(String),
+    /// A macOS API or command failed.
+    #[cfg(target_os = "macos")]
     Platform(String),
-    NotImplemented(String),
-    Io(std::io::Error),
+    Io(io::Error),
     Json(serde_json::Error),
 }
 
+impl DaemonError {
+    /// The error for a command that failed to start: a missing program is a
+    /// missing dependency, anything else an I/O error.
+    #[cfg(any(target_os = "macos", test))]
+    pub fn spawn(program: &str, e: io::Error) -> Self {
+        match e.kind() {
+            io::ErrorKind::NotFound => Self::missing(program),
+            _ => Self::Io(e),
+        }
+    }
+
+    #[cfg(any(target_os = "macos", test))]
+    pub fn missing(program: &str) -> Self {
+        let hint = match program {
+            "tmux" => "Install it with `brew install tmux`",
+            "swiftc" => "Install the Xcode command line tools with `xcode-select --install`",
+            "brightness" => "Install it with `brew install brightness`",
+            "osascript" => "osascript ships with macOS; make sure /usr/bin is on the daemon's PATH",
+            _ => "Make sure it is installed and on the daemon's PATH",
+        };
+        Self::MissingDependency {
+            program: program.into(),
+            hint: hint.into(),
+        }
+    }
+
+    pub fn invalid_argument(field: &str, message: &str) -> Self {
+        Self::InvalidArgument {
+            field: field.into(),
+            message: message.into(),
+        }
+    }
+
+    /// Stable identifier of the variant, for the `kind` field of `data`.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::PermissionDenied { .. } => "permission_denied",
+            Self::NotSupportedOnPlatform(_) => "not_supported_on_platform",
+            Self::UnknownTool(_) => "unknown_tool",
+            #[cfg(any(target_os = "macos", test))]
+            Self::MissingDependency { .. } => "missing_dependency",
+            Self::Timeout { .. } => "timeout",
+            Self::RateLimited { .. } => "rate_limited",
+            Self::PolicyViolation { .. } => "policy_violation",
+            Self::InvalidArgument { .. } => "invalid_argument",
+            Self::Client(_) => "client",
+            #[cfg(target_os = "macos")]
+            Self::Platform(_) => "platform",
+            Self::Io(_) => "io",
+            Self::Json(_) => "json",
+        }
+    }
+
+    /// Machine-readable details, sent as the JSON-RPC error's `data`.
+    pub fn data(&self) -> Value {
+        let mut data = match self {
+            Self::PermissionDenied { permission, .. } => json!({ "permission": permission }),
+            Self::NotSupportedOnPlatform(feature) => json!({
+                "feature": feature,
+                "platform": std::env::consts::OS,
+            }),
+            Self::UnknownTool(tool) => json!({ "tool": tool }),
+            #[cfg(any(target_os = "macos", test))]
+            Self::MissingDependency { program, hint } => json!({ "program": program, "hint": hint }),
+            Self::Timeout { operation, after } => json!({
+                "operation": operation,
+                "timeoutMs": after.as_millis() as u64,
+            }),
+            Self::RateLimited {
+                scope,
+                name,
+                max_per_minute,
+                burst,
+                retry_after,
+            } => json!({
+                "scope": scope,
+                "name": name,
+                "maxPerMinute": max_per_minute,
+                "burst": burst,
+                "retryAfterMs": retry_after.as_millis().max(1) as u64,
+            }),
+            Self::PolicyViolation {
+                field,
+                rule,
+                patterns,
+                value,
+                ..
+            } => json!({
+                "field": field,
+                "rule": rule,
+                "patterns": patterns,
+                "value": value,
+            }),
+            Self::InvalidArgument { field, .. } => json!({ "field": field }),
+            #[cfg(target_os = "macos")]
+            Self::Platform(_) => json!({}),
+            Self::Client(_) | Self::Io(_) | Self::Json(_) => json!({}),
+        };
+        data["kind"] = self.kind().into();
+        data
+    }
+}
+
 impl fmt::Display for DaemonError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            Self::Permission(msg) => write!(f, "Permission denied: {msg}"),
+            Self::PermissionDenied { message, .. } => write!(f, "Permission denied: {message}"),
+            Self::NotSupportedOnPlatform(feature) => {
+                write!(f, "{feature} is not supported on {}", std::env::consts::OS)
+            }
+            Self::UnknownTool(tool) => write!(f, "Tool '{tool}' not found or not permitted"),
+            #[cfg(any(target_os = "macos", test))]
+            Self::MissingDependency { program, hint } => write!(f, "'{program}' is not installed. {hint}"),
+            Self::Timeout { operation, after } => {
+                write!(f, "{operation} timed out after {}s", after.as_secs_f64())
+            }
+            Self::RateLimited {
+                scope,
+                name,
+                max_per_minute,
+                retry_after,
+                ..
+            } => write!(
+                f,
+                "Rate limit of {max_per_minute} calls per minute for {scope} '{name}' reached; retry in {:.1}s",
+                retry_after.as_secs_f64()
+            ),
+            Self::PolicyViolation {
+                field,
+                what,
+                rule,
+                patterns,
+                value,
+            } => {
+                let patterns = patterns.join(", ");
+                let must = if *rule == "in" { "must" } else { "may not" };
+                write!(
+                    f,
+                    "Policy violation: {field} {must} be a {what} matching {patterns}, got '{value}'"
+                )
+            }
+            Self::InvalidArgument { field, message } if field.is_empty() => {
+                write!(f, "Invalid arguments: {message}")
+            }
+            Self::InvalidArgument { field, message } => write!(f, "Invalid argument '{field}': {message}"),
+            Self::Client(msg) => write!(f, "Client error: {msg}"),
+            #[cfg(target_os = "macos")]
             Self::Platform(msg) => write!(f, "Platform error: {msg}"),
-            Self::NotImplemented(msg) => write!(f, "Not implemented: {msg}"),
             Self::Io(e) => write!(f, "I/O error: {e}"),
             Self::Json(e) => write!(f, "JSON error: {e}"),
         }
@@ -23,12 +210,21 @@ impl fmt::Display for DaemonError {
 
 impl std::error::Error for DaemonError {}
 
-impl From<std::io::Error> for DaemonError {
-    fn from(e: std::io::Error) -> Self {
+impl From<io::Error> for DaemonError {
+    fn from(e: io::Error) -> Self {
         Self::Io(e)
     }
 }
 
+impl From<crate::capabilities::schema::SchemaError> for DaemonError {
+    fn from(e: crate::capabilities::schema::SchemaError) -> Self {
+        Self::InvalidArgument {
+            field: e.field,
+            message: e.message,
+        }
+    }
+}
+
 impl From<serde_json::Error> for DaemonError {
     fn from(e: serde_json::Error) -> Self {
         Self::Json(e)
@@ -1,25 +1,42 @@
+mod audit;
 mod config;
 mod error;
 mod mcp;
 mod capabilities;
 mod permissions;
 mod platform;
+mod process;
+mod prompts;
+mod workflow;
 
 use capabilities::CapabilityRegistry;
+use tracing::Level;
+use tracing_subscriber::filter::Targets;
+use tracing_subscriber::prelude::*;
 use tracing_subscriber::EnvFilter;
 
 fn main() {
-    // All logging goes to stderr (stdout is the MCP JSON-RPC channel)
-    tracing_subscriber::fmt()
-        .with_env_filter(
-            EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info")),
+    // Logging goes to stderr (stdout is the MCP JSON-RPC channel) and, at
+    // the level each client asks for, to clients as notifications/message
+    tracing_subscriber::registry()
+        .with(
+            tracing_subscriber::fmt::layer()
+                .with_writer(std::io::stderr)
+                .with_target(false)
+                .with_filter(
+                    EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info")),
+                ),
+        )
+        .with(
+            mcp::logging::ClientLogLayer
+                .with_filter(Targets::new().with_target(env!("CARGO_CRATE_NAME"), Level::DEBUG)),
         )
-        .with_writer(std::io::stderr)
-        .with_target(false)
         .init();
 
-    let cfg = config::load();
+    let mut cfg = config::load();
+    apply_args(&mut cfg);
     let perms = permissions::load();
+    let prompts = prompts::load();
     let mut registry = CapabilityRegistry::new(perms);
 
     // Register all enabled capabilities
@@ -74,6 +91,29 @@ fn main() {
     #[cfg(feature = "ocr")]
     registry.register(capabilities::ocr::provider());
 
-    // Run the MCP server (blocks on stdin)
-    mcp::server::run(registry, &cfg);
+    // Run the MCP server (blocks until the transport shuts down)
+    mcp::server::run(registry, prompts, cfg);
+}
+
+/// Command-line overrides for the config file.
+///   --listen-unix <path>   serve on a unix socket instead of stdio
+///   --dry-run              only describe what state-changing tools would do
+fn apply_args(cfg: &mut config::FamiliarConfig) {
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--listen-unix" => match args.next() {
+                Some(path) => {
+                    cfg.daemon.transport = config::Transport::Unix;
+                    cfg.daemon.unix_socket = path;
+                }
+                None => {
+                    eprintln!("--listen-unix requires a socket path");
+                    std::process::exit(2);
+                }
+            },
+            "--dry-run" => cfg.daemon.dry_run = true,
+            other => tracing::warn!(arg = %other, "ignoring unknown argument"),
+        }
+    }
 }
@@ -0,0 +1,81 @@
+use std::time::Duration;
+
+use serde_json::{json, Value};
+use tracing::info;
+
+use super::session::Session;
+use super::types::Tool;
+use crate::error::DaemonError;
+
+/// How long the user has to answer a confirmation prompt.
+const CONFIRM_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Ask the user, through the client, whether `tool` may run with
+/// `arguments`. Tools the user allowed for the session run without asking.
+/// Returns why when the call must not proceed: the user said no, or
+/// couldn't be asked, or didn't answer in time.
+pub fn confirm(session: &Session, tool: &Tool, arguments: &Value) -> Result<(), DaemonError> {
+    if session.is_tool_approved(&tool.name) {
+        return Ok(());
+    }
+    if !session.supports_elicitation() {
+        return Err(denied(format!(
+            "Tool '{}' requires confirmation, but this client does not support elicitation \
+             (MCP 2025-06-18). Set it to true in permissions.toml to run it without asking.",
+            tool.name
+        )));
+    }
+
+    let params = json!({
+        "message": describe(session, tool, arguments),
+        "requestedSchema": {
+            "type": "object",
+            "properties": {
+                "remember": {
+                    "type": "boolean",
+                    "title": "Allow for this session",
+                    "description": format!("Don't ask again for {} until the client disconnects", tool.name),
+                    "default": false
+                }
+            }
+        }
+    });
+    info!(tool = %tool.name, "asking user for confirmation");
+    let response = session.request("elicitation/create", params, CONFIRM_TIMEOUT)?;
+
+    match response["action"].as_str() {
+        Some("accept") => {
+            if response["content"]["remember"].as_bool() == Some(true) {
+                info!(tool = %tool.name, "allowed for the rest of the session");
+                session.approve_tool(&tool.name);
+            }
+            Ok(())
+        }
+        Some("decline") => Err(denied(format!("The user declined to run '{}'", tool.name))),
+        _ => Err(denied(format!("The user dismissed the confirmation for '{}'", tool.name))),
+    }
+}
+
+fn denied(message: String) -> DaemonError {
+    DaemonError::PermissionDenied {
+        permission: "confirmation".into(),
+        message,
+    }
+}
+
+/// What the user is agreeing to: the tool, what it does, and the exact
+/// arguments it will get.
+fn describe(session: &Session, tool: &Tool, arguments: &Value) -> String {
+    let mut message = format!("{} wants to run {}: {}", session.client_name(), tool.name, tool.description);
+    if let Some(args) = arguments.as_object().filter(|a| !a.is_empty()) {
+        message.push('\n');
+        for (name, value) in args {
+            let value = value.as_str().map_or_else(|| value.to_string(), str::to_string);
+            message.push_str(&format!("\n  {name}: {value}"));
+        }
+    }
+    if tool.annotations.destructive {
+        message.push_str("\n\nThis may not be undoable.");
+    }
+    message
+}
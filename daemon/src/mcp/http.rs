@@ -0,0 +1,358 @@
+use std::collections::HashMap;
+use std::io::{Cursor, Read, Write};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use serde_json::Value;
+use tiny_http::{Header, Method, Request, Response};
+use tracing::{debug, info, warn};
+
+use super::outbound::Outbound;
+use super::protocol::{JsonRpcResponse, INVALID_REQUEST};
+use super::server::Server;
+use super::session::Session;
+use super::types::ProtocolVersion;
+
+/// The single MCP endpoint (POST, GET and DELETE).
+const ENDPOINT: &str = "/mcp";
+/// Idle SSE streams get a comment line this often so dead clients are noticed.
+const KEEPALIVE_INTERVAL: Duration = Duration::from_secs(15);
+/// Sessions without requests or an open SSE stream for this long are
+/// closed, as if the client had sent DELETE.
+const SESSION_IDLE_TIMEOUT: Duration = Duration::from_secs(30 * 60);
+/// How often idle sessions are looked for.
+const REAP_INTERVAL: Duration = Duration::from_secs(60);
+const MAX_BODY_BYTES: u64 = 4 * 1024 * 1024;
+/// Most server-initiated messages held for a session while no SSE stream
+/// is open to take them; later ones are dropped.
+const OUTBOUND_QUEUE_SIZE: usize = 1024;
+
+/// Serve MCP Streamable HTTP on `[daemon] http_bind`: clients POST JSON-RPC
+/// messages and receive server-initiated messages on a GET SSE stream.
+/// Every request must carry the gateway token as a Bearer credential.
+/// Sessions left idle for `SESSION_IDLE_TIMEOUT` are closed.
+/// Blocks for the lifetime of the daemon; returns only on startup failure.
+pub fn serve(server: Arc<Server>) -> Result<(), String> {
+    let token = server.config().gateway_token().ok_or(
+        "HTTP transport requires a gateway token (~/.familiar/gateway.token or FAMILIAR_GATEWAY_TOKEN)",
+    )?;
+    let bind = server.config().daemon.http_bind.clone();
+    let listener = tiny_http::Server::http(&bind)
+        .map_err(|e| format!("failed to bind HTTP transport on {bind}: {e}"))?;
+    info!(%bind, endpoint = ENDPOINT, "HTTP transport listening");
+
+    let transport = Arc::new(HttpTransport {
+        server,
+        token,
+        sessions: Mutex::new(HashMap::new()),
+    });
+
+    let reaper = transport.clone();
+    thread::spawn(move || loop {
+        thread::sleep(REAP_INTERVAL);
+        reaper.reap_idle_sessions();
+    });
+
+    // One thread per request: SSE streams stay open for the whole session
+    for request in listener.incoming_requests() {
+        let transport = transport.clone();
+        thread::spawn(move || transport.handle(request));
+    }
+    Ok(())
+}
+
+struct HttpTransport {
+    server: Arc<Server>,
+    token: String,
+    sessions: Mutex<HashMap<String, Arc<HttpSession>>>,
+}
+
+struct HttpSession {
+    session: Arc<Session>,
+    /// Receiving end of the session's outbound queue. Taken by the GET
+    /// handler while an SSE stream is open, put back when it drops.
+    stream: Mutex<Option<Receiver<String>>>,
+    /// When the client last sent a request or closed its SSE stream.
+    last_active: Mutex<Instant>,
+}
+
+impl HttpTransport {
+    fn handle(&self, request: Request) {
+        let path = request.url().split('?').next().unwrap_or_default();
+        if path != ENDPOINT {
+            return respond(request, error_response(404, "Not found"));
+        }
+
+        // Browsers always send Origin; reject foreign pages (DNS rebinding)
+        if let Some(origin) = header(&request, "Origin")
+            && !is_local_origin(origin)
+        {
+            warn!(%origin, "rejected request from non-local origin");
+            return respond(request, error_response(403, "Forbidden origin"));
+        }
+
+        if !self.authorized(&request) {
+            let challenge = Header::from_bytes(&b"WWW-Authenticate"[..], &b"Bearer"[..]).unwrap();
+            return respond(request, error_response(401, "Unauthorized").with_header(challenge));
+        }
+
+        if let Some(version) = header(&request, "MCP-Protocol-Version")
+            && ProtocolVersion::parse(version).is_none()
+        {
+            let msg = format!("Unsupported MCP-Protocol-Version: {version}");
+            return respond(request, error_response(400, &msg));
+        }
+
+        // After initialize, the header must name the negotiated revision
+        if let Some(id) = header(&request, "Mcp-Session-Id")
+            && let Some(http_session) = self.lock_sessions().get(id)
+            && let Some(version) = header(&request, "MCP-Protocol-Version")
+            && let Some(negotiated) = http_session.session.protocol_version()
+            && version != negotiated.as_str()
+        {
+            let msg = format!(
+                "MCP-Protocol-Version {version} does not match the negotiated version {}",
+                negotiated.as_str()
+            );
+            return respond(request, error_response(400, &msg));
+        }
+
+        match request.method() {
+            Method::Post => self.handle_post(request),
+            Method::Get => self.handle_get(request),
+            Method::Delete => self.handle_delete(request),
+            _ => {
+                let allow = Header::from_bytes(&b"Allow"[..], &b"GET, POST, DELETE"[..]).unwrap();
+                respond(request, error_response(405, "Method not allowed").with_header(allow));
+            }
+        }
+    }
+
+    /// POST: one JSON-RPC message or batch. Requests are answered inline as
+    /// application/json; notifications and responses get 202 Accepted.
+    fn handle_post(&self, mut request: Request) {
+        let mut body = String::new();
+        if let Err(e) = request.as_reader().take(MAX_BODY_BYTES).read_to_string(&mut body) {
+            return respond(request, error_response(400, &format!("Failed to read body: {e}")));
+        }
+
+        let (http_session, new_id) = match header(&request, "Mcp-Session-Id") {
+            Some(id) => match self.lock_sessions().get(id) {
+                Some(s) => {
+                    s.touch();
+                    (s.clone(), None)
+                }
+                None => return respond(request, error_response(404, "Session not found")),
+            },
+            None => {
+                // Only initialize may open a session
+                let is_initialize = serde_json::from_str::<Value>(&body)
+                    .is_ok_and(|v| v.get("method").and_then(Value::as_str) == Some("initialize"));
+                if !is_initialize {
+                    return respond(request, error_response(400, "Missing Mcp-Session-Id header"));
+                }
+                let id = match new_session_id() {
+                    Ok(id) => id,
+                    Err(e) => {
+                        warn!(error = %e, "failed to generate a session id");
+                        return respond(request, error_response(500, "Failed to create a session"));
+                    }
+                };
+                let (tx, rx) = mpsc::sync_channel(OUTBOUND_QUEUE_SIZE);
+                let http_session = Arc::new(HttpSession {
+                    session: self.server.open_session(Outbound::queue(tx)),
+                    stream: Mutex::new(Some(rx)),
+                    last_active: Mutex::new(Instant::now()),
+                });
+                (http_session, Some(id))
+            }
+        };
+
+        let reply = self.server.handle_message(&http_session.session, &body);
+
+        let mut response = match reply {
+            Some(json) => Response::from_string(json).with_header(content_type("application/json")),
+            None => Response::from_string("").with_status_code(202),
+        };
+
+        // Keep the session only if the handshake succeeded
+        if let Some(id) = new_id
+            && http_session.session.protocol_version().is_some()
+        {
+            info!(session = %id, client = %http_session.session.client_name(), "HTTP session opened");
+            response.add_header(Header::from_bytes(&b"Mcp-Session-Id"[..], id.as_bytes()).unwrap());
+            self.lock_sessions().insert(id, http_session);
+        }
+
+        respond(request, response);
+    }
+
+    /// GET: open the SSE stream carrying server-initiated messages.
+    fn handle_get(&self, request: Request) {
+        let accepts_sse = header(&request, "Accept").is_some_and(|a| a.contains("text/event-stream"));
+        if !accepts_sse {
+            return respond(request, error_response(406, "GET requires Accept: text/event-stream"));
+        }
+        let Some(id) = header(&request, "Mcp-Session-Id").map(str::to_string) else {
+            return respond(request, error_response(400, "Missing Mcp-Session-Id header"));
+        };
+        let Some(http_session) = self.lock_sessions().get(&id).cloned() else {
+            return respond(request, error_response(404, "Session not found"));
+        };
+        let Some(rx) = http_session.lock_stream().take() else {
+            return respond(request, error_response(409, "An SSE stream is already open for this session"));
+        };
+
+        debug!(session = %id, "SSE stream opened");
+
+        // Written by hand: tiny_http's chunked encoder buffers output, which
+        // would hold back events. The body is delimited by connection close.
+        let mut writer = request.into_writer();
+        let head = "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nCache-Control: no-cache\r\nConnection: close\r\n\r\n";
+        let mut alive = writer.write_all(head.as_bytes()).and_then(|_| writer.flush()).is_ok();
+
+        while alive {
+            let frame = match rx.recv_timeout(KEEPALIVE_INTERVAL) {
+                Ok(message) => format!("data: {message}\n\n"),
+                Err(RecvTimeoutError::Timeout) => ": keepalive\n\n".to_string(),
+                // Session closed: all senders dropped
+                Err(RecvTimeoutError::Disconnected) => break,
+            };
+            alive = writer.write_all(frame.as_bytes()).and_then(|_| writer.flush()).is_ok();
+        }
+
+        debug!(session = %id, "SSE stream closed");
+        // Let the client reconnect; up to OUTBOUND_QUEUE_SIZE messages are kept meanwhile
+        http_session.touch();
+        *http_session.lock_stream() = Some(rx);
+    }
+
+    /// DELETE: terminate the session.
+    fn handle_delete(&self, request: Request) {
+        let Some(id) = header(&request, "Mcp-Session-Id").map(str::to_string) else {
+            return respond(request, error_response(400, "Missing Mcp-Session-Id header"));
+        };
+        match self.lock_sessions().remove(&id) {
+            Some(http_session) => {
+                http_session.session.cancel_all();
+                info!(session = %id, client = %http_session.session.client_name(), "HTTP session closed");
+                respond(request, Response::from_string("").with_status_code(200));
+            }
+            None => respond(request, error_response(404, "Session not found")),
+        }
+    }
+
+    /// Close the sessions idle for longer than `SESSION_IDLE_TIMEOUT`.
+    /// Dropping a session also ends its resource watcher thread.
+    fn reap_idle_sessions(&self) {
+        let mut sessions = self.lock_sessions();
+        let idle: Vec<String> = sessions
+            .iter()
+            .filter(|(_, s)| s.is_idle())
+            .map(|(id, _)| id.clone())
+            .collect();
+        for id in idle {
+            if let Some(http_session) = sessions.remove(&id) {
+                http_session.session.cancel_all();
+                info!(session = %id, client = %http_session.session.client_name(), "HTTP session expired");
+            }
+        }
+    }
+
+    fn authorized(&self, request: &Request) -> bool {
+        header(request, "Authorization")
+            .and_then(|v| v.strip_prefix("Bearer "))
+            .is_some_and(|t| constant_time_eq(t.trim().as_bytes(), self.token.as_bytes()))
+    }
+
+    fn lock_sessions(&self) -> MutexGuard<'_, HashMap<String, Arc<HttpSession>>> {
+        self.sessions.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl HttpSession {
+    fn touch(&self) {
+        *self.last_active.lock().unwrap_or_else(|e| e.into_inner()) = Instant::now();
+    }
+
+    /// No open SSE stream, and no request for `SESSION_IDLE_TIMEOUT`.
+    fn is_idle(&self) -> bool {
+        let last_active = *self.last_active.lock().unwrap_or_else(|e| e.into_inner());
+        self.lock_stream().is_some() && last_active.elapsed() > SESSION_IDLE_TIMEOUT
+    }
+
+    fn lock_stream(&self) -> MutexGuard<'_, Option<Receiver<String>>> {
+        self.stream.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+fn header<'a>(request: &'a Request, name: &'static str) -> Option<&'a str> {
+    request
+        .headers()
+        .iter()
+        .find(|h| h.field.equiv(name))
+        .map(|h| h.value.as_str())
+}
+
+fn content_type(value: &str) -> Header {
+    Header::from_bytes(&b"Content-Type"[..], value.as_bytes()).unwrap()
+}
+
+/// An HTTP error whose body is a JSON-RPC error, so MCP clients can surface it.
+fn error_response(status: u16, message: &str) -> Response<Cursor<Vec<u8>>> {
+    let body = JsonRpcResponse::error(Value::Null, INVALID_REQUEST, message.to_string());
+    Response::from_string(serde_json::to_string(&body).expect("failed to serialize response"))
+        .with_status_code(status)
+        .with_header(content_type("application/json"))
+}
+
+fn respond<R: Read>(request: Request, response: Response<R>) {
+    if let Err(e) = request.respond(response) {
+        debug!("failed to send HTTP response: {e}");
+    }
+}
+
+fn is_local_origin(origin: &str) -> bool {
+    let host_port = origin.split("://").nth(1).unwrap_or(origin);
+    let host = if host_port.starts_with('[') {
+        host_port.split(']').next().map(|h| &h[1..]).unwrap_or_default()
+    } else {
+        host_port.split([':', '/']).next().unwrap_or_default()
+    };
+    matches!(host, "localhost" | "127.0.0.1" | "::1")
+}
+
+pub(super) fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+/// 128-bit session id from the OS's secure random number generator.
+fn new_session_id() -> Result<String, getrandom::Error> {
+    let mut bytes = [0u8; 16];
+    getrandom::fill(&mut bytes)?;
+    Ok(bytes.iter().map(|b| format!("{b:02x}")).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn session_ids_are_random_and_128_bit() {
+        let (a, b) = (new_session_id().unwrap(), new_session_id().unwrap());
+        assert_eq!(a.len(), 32);
+        assert!(a.chars().all(|c| c.is_ascii_hexdigit()));
+        assert_ne!(a, b);
+    }
+
+    #[test]
+    fn only_loopback_origins_are_local() {
+        assert!(is_local_origin("http://localhost:3000"));
+        assert!(is_local_origin("http://127.0.0.1"));
+        assert!(is_local_origin("http://[::1]:8080"));
+        assert!(!is_local_origin("http://localhost.evil.com"));
+        assert!(!is_local_origin("https://example.com"));
+    }
+}
@@ -0,0 +1,132 @@
+use std::cell::Cell;
+use std::fmt;
+use std::sync::mpsc::{self, Sender};
+use std::sync::OnceLock;
+use std::thread;
+
+use serde_json::{Map, Value};
+use tracing::field::{Field, Visit};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::layer::Context;
+use tracing_subscriber::Layer;
+
+use super::types::LogLevel;
+
+/// A tracing event on its way to a client as `notifications/message`.
+pub struct LogRecord {
+    /// The session whose message was being handled when it was logged.
+    pub session: u64,
+    pub level: LogLevel,
+    pub logger: &'static str,
+    pub data: Value,
+}
+
+/// Set once the server is up; events before that only reach stderr.
+static FORWARD: OnceLock<Sender<LogRecord>> = OnceLock::new();
+
+thread_local! {
+    /// Set on the delivery thread, so that delivering a message (and any
+    /// write error it logs) doesn't produce more messages.
+    static DELIVERING: Cell<bool> = const { Cell::new(false) };
+    /// The session this thread is working for, if any.
+    static SESSION: Cell<Option<u64>> = const { Cell::new(None) };
+}
+
+/// Run `f` on behalf of session `id`: events logged meanwhile are
+/// forwarded to that session only.
+pub fn with_session<R>(id: u64, f: impl FnOnce() -> R) -> R {
+    let previous = SESSION.with(|s| s.replace(Some(id)));
+    let result = f();
+    SESSION.with(|s| s.set(previous));
+    result
+}
+
+/// Tracing layer that forwards events to MCP clients, next to the stderr
+/// output: each to the session it was logged for (see `with_session`), so
+/// no client sees what another one does. Events logged for no session
+/// (startup, reloads) only reach stderr. Events are queued and delivered on
+/// a separate thread, since they can be emitted while a session's writer is
+/// locked.
+pub struct ClientLogLayer;
+
+impl<S: Subscriber> Layer<S> for ClientLogLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let Some(tx) = FORWARD.get() else {
+            return;
+        };
+        if DELIVERING.with(Cell::get) {
+            return;
+        }
+        let Some(session) = SESSION.with(Cell::get) else {
+            return;
+        };
+
+        let mut fields = JsonFields(Map::new());
+        event.record(&mut fields);
+        let _ = tx.send(LogRecord {
+            session,
+            level: log_level(event.metadata().level()),
+            logger: event.metadata().target(),
+            data: Value::Object(fields.0),
+        });
+    }
+}
+
+/// Start delivering queued events with `deliver`. Only the first call has
+/// an effect.
+pub fn forward(deliver: impl Fn(LogRecord) + Send + 'static) {
+    let (tx, rx) = mpsc::channel();
+    if FORWARD.set(tx).is_err() {
+        return;
+    }
+    thread::spawn(move || {
+        DELIVERING.with(|d| d.set(true));
+        for record in rx {
+            deliver(record);
+        }
+    });
+}
+
+fn log_level(level: &Level) -> LogLevel {
+    match *level {
+        Level::ERROR => LogLevel::Error,
+        Level::WARN => LogLevel::Warning,
+        Level::INFO => LogLevel::Info,
+        _ => LogLevel::Debug,
+    }
+}
+
+/// Collects an event's message and fields into a JSON object.
+struct JsonFields(Map<String, Value>);
+
+impl JsonFields {
+    fn insert(&mut self, field: &Field, value: Value) {
+        self.0.insert(field.name().to_string(), value);
+    }
+}
+
+impl Visit for JsonFields {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.insert(field, Value::String(format!("{value:?}")));
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.insert(field, Value::String(value.to_string()));
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.insert(field, value.into());
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.insert(field, value.into());
+    }
+
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.insert(field, value.into());
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.insert(field, value.into());
+    }
+}
@@ -1,3 +1,12 @@
+pub mod elicitation;
+pub mod http;
+pub mod logging;
+pub mod outbound;
 pub mod protocol;
 pub mod server;
+pub mod session;
+pub mod stdio;
+pub mod subscriptions;
 pub mod types;
+#[cfg(unix)]
+pub mod unix;
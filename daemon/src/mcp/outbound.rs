@@ -0,0 +1,93 @@
+use std::io::{self, Write};
+use std::sync::mpsc::{SyncSender, TrySendError};
+use std::sync::{Arc, Mutex};
+
+use serde::Serialize;
+use serde_json::Value;
+use tracing::{debug, error, warn};
+
+use super::protocol::JsonRpcNotification;
+
+/// Writer for server → client messages. Cloneable so that background
+/// threads (e.g. the resource watcher) can emit notifications without
+/// interleaving with responses from the request loop.
+#[derive(Clone)]
+pub struct Outbound {
+    sink: Arc<Sink>,
+}
+
+enum Sink {
+    /// Newline-delimited JSON on a byte stream (stdio, unix socket).
+    Stream(Mutex<Box<dyn Write + Send>>),
+    /// Queued for a transport that delivers messages itself (HTTP SSE).
+    /// Bounded: while nothing drains it, messages past its capacity are
+    /// dropped.
+    Queue(SyncSender<String>),
+}
+
+impl Outbound {
+    pub fn stdout() -> Self {
+        Self::stream(Box::new(io::stdout()))
+    }
+
+    pub fn stream(writer: Box<dyn Write + Send>) -> Self {
+        Self {
+            sink: Arc::new(Sink::Stream(Mutex::new(writer))),
+        }
+    }
+
+    pub fn queue(tx: SyncSender<String>) -> Self {
+        Self {
+            sink: Arc::new(Sink::Queue(tx)),
+        }
+    }
+
+    /// Send one already-serialized JSON-RPC message.
+    pub fn send_raw(&self, json: &str) {
+        match &*self.sink {
+            Sink::Stream(writer) => {
+                let mut writer = writer.lock().unwrap_or_else(|e| e.into_inner());
+                if let Err(e) = writeln!(writer, "{json}").and_then(|_| writer.flush()) {
+                    error!("write error: {e}");
+                }
+            }
+            Sink::Queue(tx) => match tx.try_send(json.to_string()) {
+                Ok(()) => {}
+                Err(TrySendError::Full(_)) => warn!("outbound queue full, message dropped"),
+                Err(TrySendError::Disconnected(_)) => debug!("outbound queue closed, message dropped"),
+            },
+        }
+    }
+
+    pub fn send<T: Serialize>(&self, message: &T) {
+        match serde_json::to_string(message) {
+            Ok(json) => self.send_raw(&json),
+            Err(e) => error!("failed to serialize message: {e}"),
+        }
+    }
+
+    /// Send a JSON-RPC notification.
+    pub fn notify(&self, method: &str, params: Value) {
+        self.send(&JsonRpcNotification::new(method, params));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc;
+
+    use super::*;
+
+    #[test]
+    fn a_full_queue_drops_messages() {
+        let (tx, rx) = mpsc::sync_channel(2);
+        let out = Outbound::queue(tx);
+        for n in 0..3 {
+            out.send_raw(&n.to_string());
+        }
+        assert_eq!(rx.try_iter().collect::<Vec<_>>(), ["0", "1"]);
+
+        out.send_raw("3");
+        assert_eq!(rx.try_iter().collect::<Vec<_>>(), ["3"]);
+    }
+}
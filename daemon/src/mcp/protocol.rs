@@ -1,9 +1,28 @@
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
+// ── Error codes ──────────────────────────────────────────────────────────────
+
+pub const PARSE_ERROR: i32 = -32700;
+pub const INVALID_REQUEST: i32 = -32600;
+pub const METHOD_NOT_FOUND: i32 = -32601;
+pub const INVALID_PARAMS: i32 = -32602;
+pub const INTERNAL_ERROR: i32 = -32603;
+/// MCP-specific: the requested resource URI is unknown or not permitted.
+pub const RESOURCE_NOT_FOUND: i32 = -32002;
+/// Tool call failures from `DaemonError`; `data.kind` tells them apart in
+/// more detail.
+pub const TIMEOUT: i32 = -32001;
+pub const PERMISSION_DENIED: i32 = -32010;
+pub const NOT_SUPPORTED: i32 = -32011;
+#[cfg(any(target_os = "macos", test))]
+pub const MISSING_DEPENDENCY: i32 = -32012;
+pub const RATE_LIMITED: i32 = -32013;
+pub const POLICY_VIOLATION: i32 = -32014;
+
 // ── Incoming messages ────────────────────────────────────────────────────────
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 pub struct JsonRpcRequest {
     pub jsonrpc: String,
     pub id: Value,
@@ -12,7 +31,7 @@ pub struct JsonRpcRequest {
     pub params: Value,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 pub struct JsonRpcNotification {
     pub jsonrpc: String,
     pub method: String,
@@ -40,6 +59,28 @@ pub struct JsonRpcError {
     pub data: Option<Value>,
 }
 
+impl JsonRpcRequest {
+    /// A server → client request.
+    pub fn new(id: Value, method: impl Into<String>, params: Value) -> Self {
+        Self {
+            jsonrpc: "2.0".into(),
+            id,
+            method: method.into(),
+            params,
+        }
+    }
+}
+
+impl JsonRpcNotification {
+    pub fn new(method: impl Into<String>, params: Value) -> Self {
+        Self {
+            jsonrpc: "2.0".into(),
+            method: method.into(),
+            params,
+        }
+    }
+}
+
 impl JsonRpcResponse {
     pub fn success(id: Value, result: Value) -> Self {
         Self {
@@ -62,4 +103,12 @@ impl JsonRpcResponse {
             }),
         }
     }
+
+    pub fn error_with_data(id: Value, code: i32, message: String, data: Value) -> Self {
+        let mut response = Self::error(id, code, message);
+        if let Some(error) = &mut response.error {
+            error.data = Some(data);
+        }
+        response
+    }
 }
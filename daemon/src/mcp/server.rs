@@ -1,284 +1,997 @@
-use std::io::{self, BufRead, Write};
+use std::sync::{Arc, Mutex, Weak};
+use std::thread;
+use std::time::Instant;
 
+use serde::de::DeserializeOwned;
 use serde_json::{json, Value};
 use tracing::{debug, error, info, warn};
 
-use super::protocol::{JsonRpcRequest, JsonRpcResponse};
+use super::outbound::Outbound;
+use super::protocol::{
+    JsonRpcNotification, JsonRpcRequest, JsonRpcResponse, INTERNAL_ERROR, INVALID_PARAMS,
+    INVALID_REQUEST, METHOD_NOT_FOUND, NOT_SUPPORTED, PARSE_ERROR,
+    PERMISSION_DENIED, POLICY_VIOLATION, RATE_LIMITED, RESOURCE_NOT_FOUND, TIMEOUT,
+};
+use super::session::Session;
+use super::subscriptions;
 use super::types::*;
-use crate::capabilities::CapabilityRegistry;
-use crate::config::FamiliarConfig;
-
-/// Run the MCP server: read JSON-RPC from stdin, write responses to stdout.
-/// All logging goes to stderr.
-pub fn run(registry: CapabilityRegistry, config: &FamiliarConfig) {
-    let stdin = io::stdin();
-    let mut stdout = io::stdout();
-
+use super::elicitation;
+use super::logging::{self, LogRecord};
+use super::http::{self, constant_time_eq};
+use super::stdio;
+#[cfg(unix)]
+use super::unix;
+use crate::audit::{self, AuditLog};
+use crate::capabilities::{schema, CallContext, CapabilityRegistry, Progress};
+use crate::config::{FamiliarConfig, Transport};
+use crate::error::DaemonError;
+use crate::permissions::{self, PermissionsConfig};
+use crate::process;
+use crate::prompts::PromptTemplate;
+use crate::workflow;
+
+/// Most values a `completion/complete` response may carry.
+const MAX_COMPLETIONS: usize = 100;
+
+/// Run the MCP server on the configured transport. Blocks until the
+/// transport shuts down. Logs go to stderr and to clients that asked for them.
+pub fn run(mut registry: CapabilityRegistry, prompts: Vec<PromptTemplate>, config: FamiliarConfig) {
+    let audit = Arc::new(AuditLog::new(config.audit.clone()));
+    registry.register(audit::provider(audit.clone()));
     info!(
         tools = registry.tool_count(),
         capabilities = registry.capability_count(),
+        resources = registry.list_resources(None).len(),
+        prompts = prompts.len(),
+        transport = ?config.daemon.transport,
+        dry_run = config.daemon.dry_run,
         "familiar-daemon MCP server started"
     );
 
-    for line in stdin.lock().lines() {
-        let line = match line {
-            Ok(l) => l,
+    let server = Arc::new(Server {
+        registry: Arc::new(registry),
+        audit,
+        config,
+        prompts,
+        sessions: Mutex::new(Vec::new()),
+    });
+
+    let reloader = Arc::downgrade(&server);
+    permissions::watch(move |permissions| {
+        if let Some(server) = reloader.upgrade() {
+            server.reload_permissions(permissions);
+        }
+    });
+
+    let logger = Arc::downgrade(&server);
+    logging::forward(move |record| {
+        if let Some(server) = logger.upgrade() {
+            server.log(record);
+        }
+    });
+
+    let transport = server.config.daemon.transport;
+    let result = match transport {
+        Transport::Stdio => {
+            stdio::serve(server);
+            Ok(())
+        }
+        Transport::Http => http::serve(server),
+        #[cfg(unix)]
+        Transport::Unix => unix::serve(server),
+        #[cfg(not(unix))]
+        Transport::Unix => Err("The unix transport needs a unix system; use stdio or http".into()),
+    };
+    if let Err(e) = result {
+        error!("{e}");
+        std::process::exit(1);
+    }
+}
+
+/// State shared by all sessions: the capabilities and prompts being served.
+/// Transports own the connections and hand each message to `handle_message`.
+pub struct Server {
+    registry: Arc<CapabilityRegistry>,
+    /// Record of every tools/call.
+    audit: Arc<AuditLog>,
+    config: FamiliarConfig,
+    prompts: Vec<PromptTemplate>,
+    /// Open sessions, for broadcasting list changes.
+    sessions: Mutex<Vec<Weak<Session>>>,
+}
+
+impl Server {
+    pub fn config(&self) -> &FamiliarConfig {
+        &self.config
+    }
+
+    /// Create the state for a new client connection.
+    pub fn open_session(&self, out: Outbound) -> Arc<Session> {
+        let session = Arc::new(Session::new(out));
+        subscriptions::spawn_watcher(self.registry.clone(), &session);
+        let mut sessions = self.sessions.lock().unwrap_or_else(|e| e.into_inner());
+        sessions.retain(|s| s.strong_count() > 0);
+        sessions.push(Arc::downgrade(&session));
+        session
+    }
+
+    /// Apply a reloaded permissions file and tell clients whose tool or
+    /// resource list changed as a result, going by each client's profile.
+    fn reload_permissions(&self, permissions: PermissionsConfig) {
+        let sessions = self.initialized_sessions();
+        let mut profiles: Vec<Option<String>> = sessions.iter().map(|s| s.profile()).collect();
+        profiles.sort();
+        profiles.dedup();
+
+        let before: Vec<_> = profiles.iter().map(|p| self.listing(p.as_deref())).collect();
+        self.registry.set_permissions(permissions);
+
+        for (profile, (tools_before, resources_before)) in profiles.iter().zip(before) {
+            let (tools, resources) = self.listing(profile.as_deref());
+            let name = profile.as_deref().unwrap_or("default");
+            let mut changed = Vec::new();
+            if tools != tools_before {
+                info!(profile = %name, tools = tools.len(), "tool list changed");
+                changed.push("notifications/tools/list_changed");
+            }
+            if resources != resources_before {
+                info!(profile = %name, resources = resources.len(), "resource list changed");
+                changed.push("notifications/resources/list_changed");
+            }
+            for session in sessions.iter().filter(|s| s.profile() == *profile) {
+                for method in &changed {
+                    session.out.notify(method, json!({}));
+                }
+            }
+        }
+    }
+
+    /// What clients with a profile are offered: tool names with their
+    /// descriptions (which change with rate limits and constraints), and
+    /// resource URIs.
+    fn listing(&self, profile: Option<&str>) -> (Vec<(String, String)>, Vec<String>) {
+        let tools = self.registry.list_tools(profile).into_iter().map(|t| (t.name, t.description));
+        let resources = self.registry.list_resources(profile).into_iter().map(|r| r.uri);
+        (tools.collect(), resources.collect())
+    }
+
+    /// Send a log message to the session it was logged for, if that asked
+    /// for its level.
+    fn log(&self, record: LogRecord) {
+        let sessions = self.initialized_sessions();
+        let session = sessions.iter().find(|s| s.id() == record.session);
+        if let Some(session) = session.filter(|s| s.wants_log(record.level)) {
+            session.out.notify(
+                "notifications/message",
+                json!({ "level": record.level, "logger": record.logger, "data": record.data }),
+            );
+        }
+    }
+
+    fn initialized_sessions(&self) -> Vec<Arc<Session>> {
+        self.sessions
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .filter_map(Weak::upgrade)
+            .filter(|s| s.is_initialized())
+            .collect()
+    }
+
+    /// Handle a message from a stream transport and write the reply to the
+    /// session. Messages with requests that may run tools (alone or in a
+    /// batch) are handled on their own thread, so a slow call does not hold
+    /// up `ping` or other requests, and the reader stays free for the
+    /// client's answers to elicitations; everything else stays inline to
+    /// keep the handshake ordered.
+    pub fn dispatch(self: &Arc<Self>, session: &Arc<Session>, raw: &str) {
+        let value = serde_json::from_str::<Value>(raw).unwrap_or_default();
+        let items = match &value {
+            Value::Array(items) => items.as_slice(),
+            single => std::slice::from_ref(single),
+        };
+        let runs_tools = items.iter().any(|v| {
+            matches!(
+                v.get("method").and_then(Value::as_str),
+                Some("tools/call" | "resources/read" | "prompts/get")
+            )
+        });
+
+        if runs_tools {
+            // Tracked before the thread starts, so a cancellation read
+            // meanwhile isn't lost
+            let ids: Vec<Value> = items
+                .iter()
+                .filter(|v| v.get("method").is_some())
+                .filter_map(|v| v.get("id").cloned())
+                .collect();
+            for id in &ids {
+                session.start_request(id);
+            }
+            let (server, session, raw) = (self.clone(), session.clone(), raw.to_string());
+            thread::spawn(move || {
+                if let Some(json) = server.handle_message(&session, &raw) {
+                    session.out.send_raw(&json);
+                }
+                for id in &ids {
+                    session.finish_request(id);
+                }
+            });
+        } else if let Some(json) = self.handle_message(session, raw) {
+            session.out.send_raw(&json);
+        }
+    }
+
+    /// Handle one raw JSON-RPC message — a single object or a batch array — and
+    /// return the serialized reply, if the message warrants one.
+    pub fn handle_message(&self, session: &Session, raw: &str) -> Option<String> {
+        logging::with_session(session.id(), || self.handle_raw(session, raw))
+    }
+
+    fn handle_raw(&self, session: &Session, raw: &str) -> Option<String> {
+        let value = match serde_json::from_str::<Value>(raw) {
+            Ok(v) => v,
             Err(e) => {
-                error!("stdin read error: {e}");
-                break;
+                warn!(error = %e, bytes = raw.len(), "unparseable message");
+                let resp = JsonRpcResponse::error(Value::Null, PARSE_ERROR, format!("Parse error: {e}"));
+                return Some(serde_json::to_string(&resp).expect("failed to serialize response"));
             }
         };
 
-        let trimmed = line.trim();
-        if trimmed.is_empty() {
-            continue;
-        }
+        match value {
+            Value::Array(items) => {
+                if items.is_empty() {
+                    let resp = JsonRpcResponse::error(
+                        Value::Null,
+                        INVALID_REQUEST,
+                        "Invalid Request: empty batch".into(),
+                    );
+                    return Some(serde_json::to_string(&resp).expect("failed to serialize response"));
+                }
 
-        // Try to parse as a request (has 'id')
-        let response = match serde_json::from_str::<JsonRpcRequest>(trimmed) {
-            Ok(req) => handle_request(&registry, config, req),
-            Err(_) => {
-                // Might be a notification (no id) — check for method
-                if let Ok(val) = serde_json::from_str::<Value>(trimmed) {
-                    if val.get("method").is_some() && val.get("id").is_none() {
-                        let method = val["method"].as_str().unwrap_or("unknown");
-                        debug!("notification: {method}");
-                        continue; // notifications don't get responses
-                    }
+                debug!(size = items.len(), "batch");
+                let responses: Vec<JsonRpcResponse> = items
+                    .into_iter()
+                    .filter_map(|item| self.handle_value(session, item))
+                    .collect();
+
+                // A batch of only notifications gets no reply at all
+                if responses.is_empty() {
+                    None
+                } else {
+                    Some(serde_json::to_string(&responses).expect("failed to serialize response"))
                 }
-                warn!("unparseable message: {trimmed}");
-                continue;
             }
+            single => self
+                .handle_value(session, single)
+                .map(|resp| serde_json::to_string(&resp).expect("failed to serialize response")),
+        }
+    }
+
+    /// Classify a single JSON-RPC message and route it to the request or
+    /// notification handler. Returns a response only for requests and for
+    /// messages that are not valid JSON-RPC at all.
+    fn handle_value(&self, session: &Session, value: Value) -> Option<JsonRpcResponse> {
+        let Some(obj) = value.as_object() else {
+            return Some(invalid_request("message must be an object"));
         };
 
-        if let Some(resp) = response {
-            let json = serde_json::to_string(&resp).expect("failed to serialize response");
-            let _ = writeln!(stdout, "{json}");
-            let _ = stdout.flush();
+        // Responses to server-initiated requests carry a result/error but no method
+        if !obj.contains_key("method") && (obj.contains_key("result") || obj.contains_key("error")) {
+            let id = obj.get("id").cloned().unwrap_or_default();
+            let reply = match obj.get("error") {
+                Some(error) => Err(error["message"].as_str().unwrap_or("unknown error").to_string()),
+                None => Ok(obj.get("result").cloned().unwrap_or_default()),
+            };
+            if !session.complete_request(&id, reply) {
+                debug!(%id, "ignoring response to unknown request");
+            }
+            return None;
+        }
+
+        if !obj.get("method").is_some_and(Value::is_string) {
+            return Some(invalid_request("'method' must be a string"));
+        }
+
+        if obj.contains_key("id") {
+            let req = match serde_json::from_value::<JsonRpcRequest>(value) {
+                Ok(req) => req,
+                Err(e) => return Some(invalid_request(&e.to_string())),
+            };
+            if req.jsonrpc != "2.0" {
+                return Some(invalid_request("'jsonrpc' must be \"2.0\""));
+            }
+            if !(req.id.is_string() || req.id.is_number() || req.id.is_null()) {
+                return Some(invalid_request("'id' must be a string, number or null"));
+            }
+
+            let id = req.id.clone();
+            let token = session.start_request(&id);
+            let response = process::with_token(&token, || self.handle_request(session, req));
+            session.finish_request(&id);
+
+            // A cancelled request gets no response
+            if token.is_cancelled() {
+                info!(%id, "request cancelled");
+                return None;
+            }
+            response
+        } else {
+            let notif = match serde_json::from_value::<JsonRpcNotification>(value) {
+                Ok(notif) => notif,
+                Err(e) => return Some(invalid_request(&e.to_string())),
+            };
+            if notif.jsonrpc != "2.0" {
+                return Some(invalid_request("'jsonrpc' must be \"2.0\""));
+            }
+            self.handle_notification(session, notif);
+            None
         }
     }
 
-    info!("stdin closed, shutting down");
-}
+    fn handle_notification(&self, session: &Session, notif: JsonRpcNotification) {
+        match notif.method.as_str() {
+            "notifications/initialized" => {
+                if session.protocol_version().is_none() {
+                    warn!("notifications/initialized before initialize, ignored");
+                    return;
+                }
+                session.mark_initialized();
+                info!(client = %session.client_name(), "client initialized");
+            }
+            "notifications/cancelled" => {
+                let id = &notif.params["requestId"];
+                let reason = notif.params["reason"].as_str().unwrap_or("none");
+                if session.cancel_request(id) {
+                    info!(request_id = %id, %reason, "cancelling request");
+                } else {
+                    debug!(request_id = %id, "cancellation for unknown or finished request");
+                }
+            }
+            _ => debug!(method = %notif.method, "unhandled notification"),
+        }
+    }
 
-fn handle_request(registry: &CapabilityRegistry, config: &FamiliarConfig, req: JsonRpcRequest) -> Option<JsonRpcResponse> {
-    debug!(method = %req.method, "request");
+    /// Handle `initialize`: negotiate the protocol revision and remember who
+    /// the client is for the rest of the session.
+    fn initialize(&self, session: &Session, req: JsonRpcRequest) -> JsonRpcResponse {
+        if session.protocol_version().is_some() {
+            return JsonRpcResponse::error(req.id, INVALID_REQUEST, "Session already initialized".into());
+        }
 
-    match req.method.as_str() {
-        "initialize" => {
-            let result = InitializeResult {
-                protocol_version: "2024-11-05".into(),
-                capabilities: ServerCapabilities {
-                    tools: ToolsCapability { list_changed: true },
-                },
-                server_info: ServerInfo {
-                    name: config.identity.name.clone(),
-                    version: env!("CARGO_PKG_VERSION").into(),
+        let params: InitializeParams = match parse_params(&req) {
+            Ok(p) => p,
+            Err(resp) => return *resp,
+        };
+        let version = ProtocolVersion::negotiate(params.protocol_version.as_deref());
+        let client = params.client_info.as_ref().and_then(|c| c.name.as_deref()).unwrap_or("unknown");
+        let profile = match self.profile(client, params.meta.profile_token.as_deref()) {
+            Ok(profile) => profile,
+            Err(message) => {
+                warn!(%client, "{message}");
+                return JsonRpcResponse::error(req.id, PERMISSION_DENIED, message);
+            }
+        };
+        info!(
+            %client,
+            client_version = %params.client_info.as_ref().and_then(|c| c.version.as_deref()).unwrap_or("unknown"),
+            requested = %params.protocol_version.as_deref().unwrap_or("none"),
+            negotiated = %version.as_str(),
+            profile = %profile.as_deref().unwrap_or("default"),
+            "initialize"
+        );
+        session.begin(version, params.client_info, params.capabilities, profile);
+
+        let result = InitializeResult {
+            protocol_version: version.as_str().into(),
+            capabilities: ServerCapabilities {
+                tools: ToolsCapability { list_changed: true },
+                resources: ResourcesCapability {
+                    subscribe: true,
+                    list_changed: true,
                 },
-            };
-            Some(JsonRpcResponse::success(
-                req.id,
-                serde_json::to_value(result).unwrap(),
-            ))
+                prompts: PromptsCapability { list_changed: false },
+                logging: LoggingCapability {},
+                completions: version.supports_completions().then_some(CompletionsCapability {}),
+            },
+            server_info: ServerInfo {
+                name: self.config.identity.name.clone(),
+                version: env!("CARGO_PKG_VERSION").into(),
+            },
+        };
+        JsonRpcResponse::success(req.id, serde_json::to_value(result).unwrap())
+    }
+
+    /// The permissions profile for a client, by the name it gave. Over
+    /// the network transports, a profile with a token needs the client to
+    /// send it.
+    fn profile(&self, client: &str, token: Option<&str>) -> Result<Option<String>, String> {
+        let permissions = self.registry.permissions();
+        let Some((profile, expected)) = permissions.client_profile(client) else {
+            return Ok(None);
+        };
+        if let Some(expected) = expected
+            && self.config.daemon.transport != Transport::Stdio
+            && !token.is_some_and(|t| constant_time_eq(t.as_bytes(), expected.as_bytes()))
+        {
+            return Err(format!(
+                "Permission denied: profile '{profile}' of client '{client}' needs its token in _meta.profileToken"
+            ));
         }
+        Ok(Some(profile.to_string()))
+    }
 
-        "ping" => Some(JsonRpcResponse::success(req.id, Value::Object(Default::default()))),
+    fn handle_request(&self, session: &Session, req: JsonRpcRequest) -> Option<JsonRpcResponse> {
+        debug!(method = %req.method, "request");
+        let registry = &self.registry;
 
-        "tools/list" => {
-            let mut tools = registry.list_tools();
-            // Add the workflow_run meta-tool
-            tools.push(workflow_tool_definition());
-            let result = serde_json::json!({ "tools": tools });
-            Some(JsonRpcResponse::success(req.id, result))
+        // Only ping and the handshake itself are allowed before the client
+        // confirmed initialization
+        if !matches!(req.method.as_str(), "initialize" | "ping") && !session.is_initialized() {
+            warn!(method = %req.method, "request before initialization");
+            return Some(JsonRpcResponse::error(
+                req.id,
+                INVALID_REQUEST,
+                "Server not initialized: send initialize and notifications/initialized first".into(),
+            ));
         }
+        let version = session.protocol_version().unwrap_or(ProtocolVersion::LATEST);
+        let profile = session.profile();
+        let profile = profile.as_deref();
+        let call = CallContext {
+            profile,
+            dry_run: self.config.daemon.dry_run,
+        };
+
+        match req.method.as_str() {
+            "initialize" => Some(self.initialize(session, req)),
+
+            "ping" => Some(JsonRpcResponse::success(req.id, Value::Object(Default::default()))),
 
-        "tools/call" => {
-            let params: CallToolParams = match serde_json::from_value(req.params) {
-                Ok(p) => p,
-                Err(e) => {
+            "tools/list" => {
+                let mut tools = registry.list_tools(profile);
+                // Add the workflow_run meta-tool
+                tools.push(workflow::tool());
+                let tools: Vec<Value> = tools.into_iter().map(|t| t.for_version(version)).collect();
+                let result = serde_json::json!({ "tools": tools });
+                Some(JsonRpcResponse::success(req.id, result))
+            }
+
+            "tools/call" => {
+                let params: CallToolParams = match parse_params(&req) {
+                    Ok(p) => p,
+                    Err(resp) => return Some(*resp),
+                };
+
+                let progress = match params.meta.progress_token {
+                    Some(token) => progress_reporter(session.out.clone(), token, version),
+                    None => Progress::none(),
+                };
+
+                let confirm = |tool: &Tool, arguments: &Value| elicitation::confirm(session, tool, arguments);
+                // A client can ask for a dry run, but not out of one
+                let call = CallContext {
+                    dry_run: call.dry_run || params.meta.dry_run,
+                    ..call
+                };
+
+                let started = Instant::now();
+                let result = match params.name.as_str() {
+                    workflow::TOOL => {
+                        let mut arguments = params.arguments.clone();
+                        schema::validate(&workflow::tool().input_schema, &mut arguments)
+                            .map_err(DaemonError::from)
+                            .map(|()| workflow::run(registry, call, &arguments, &progress, &confirm))
+                    }
+                    _ => registry.call_tool(call, &params.name, &params.arguments, &progress, &confirm),
+                };
+                let client = session.client_name();
+                let status = audit::status(&result, call.dry_run);
+                self.audit.record(&client, &params.name, &params.arguments, status, started.elapsed());
+
+                match result {
+                    Ok(result) => Some(JsonRpcResponse::success(
+                        req.id,
+                        serde_json::to_value(result.for_version(version)).unwrap(),
+                    )),
+                    Err(e) => Some(tool_call_error(req.id, e)),
+                }
+            }
+
+            "logging/setLevel" => {
+                let params: SetLevelParams = match parse_params(&req) {
+                    Ok(p) => p,
+                    Err(resp) => return Some(*resp),
+                };
+                session.set_log_level(params.level);
+                debug!(level = ?params.level, "log level set");
+                Some(JsonRpcResponse::success(req.id, Value::Object(Default::default())))
+            }
+
+            "completion/complete" => {
+                let params: CompleteParams = match parse_params(&req) {
+                    Ok(p) => p,
+                    Err(resp) => return Some(*resp),
+                };
+
+                let argument = &params.argument;
+                let values = match &params.reference {
+                    CompletionReference::Tool { name } => {
+                        registry.complete(profile, name, &argument.name, &argument.value)
+                    }
+                    CompletionReference::Prompt { name } => {
+                        let Some(prompt) = self.prompts.iter().find(|p| &p.name == name) else {
+                            return Some(JsonRpcResponse::error(
+                                req.id,
+                                INVALID_PARAMS,
+                                format!("Prompt '{name}' not found"),
+                            ));
+                        };
+                        prompt
+                            .tool_argument(&argument.name)
+                            .map(|(tool, key)| registry.complete(profile, tool, key, &argument.value))
+                            .unwrap_or_default()
+                    }
+                    // No resource templates, so nothing to complete
+                    CompletionReference::Resource { .. } => Vec::new(),
+                };
+                Some(JsonRpcResponse::success(req.id, completion_result(values)))
+            }
+
+            "resources/list" => {
+                let result = json!({ "resources": registry.list_resources(profile) });
+                Some(JsonRpcResponse::success(req.id, result))
+            }
+
+            "resources/read" => {
+                let params: ResourceParams = match parse_params(&req) {
+                    Ok(p) => p,
+                    Err(resp) => return Some(*resp),
+                };
+
+                match registry.read_resource(profile, &params.uri) {
+                    Some(Ok(contents)) => Some(JsonRpcResponse::success(
+                        req.id,
+                        json!({ "contents": [contents] }),
+                    )),
+                    Some(Err(e)) => Some(JsonRpcResponse::error(
+                        req.id,
+                        INTERNAL_ERROR,
+                        format!("Failed to read resource '{}': {e}", params.uri),
+                    )),
+                    None => Some(resource_not_found(req.id, &params.uri)),
+                }
+            }
+
+            "resources/subscribe" | "resources/unsubscribe" => {
+                let params: ResourceParams = match parse_params(&req) {
+                    Ok(p) => p,
+                    Err(resp) => return Some(*resp),
+                };
+
+                if req.method == "resources/unsubscribe" {
+                    session.subscriptions.unsubscribe(&params.uri);
+                } else if registry.list_resources(profile).iter().any(|r| r.uri == params.uri) {
+                    session.subscriptions.subscribe(&params.uri);
+                } else {
+                    return Some(resource_not_found(req.id, &params.uri));
+                }
+                debug!(uri = %params.uri, method = %req.method, "subscription updated");
+                Some(JsonRpcResponse::success(req.id, Value::Object(Default::default())))
+            }
+
+            "prompts/list" => {
+                let prompts: Vec<Prompt> = self.prompts.iter().map(PromptTemplate::definition).collect();
+                Some(JsonRpcResponse::success(req.id, json!({ "prompts": prompts })))
+            }
+
+            "prompts/get" => {
+                let params: GetPromptParams = match parse_params(&req) {
+                    Ok(p) => p,
+                    Err(resp) => return Some(*resp),
+                };
+
+                let Some(prompt) = self.prompts.iter().find(|p| p.name == params.name) else {
                     return Some(JsonRpcResponse::error(
                         req.id,
-                        -32602,
-                        format!("Invalid params: {e}"),
+                        INVALID_PARAMS,
+                        format!("Prompt '{}' not found", params.name),
                     ));
+                };
+
+                let client = session.client_name();
+                match prompt.render(&params.arguments, registry, call, &self.audit, &client) {
+                    Ok(text) => {
+                        let message = PromptMessage {
+                            role: prompt.role.clone(),
+                            content: ContentBlock::Text { text },
+                        };
+                        Some(JsonRpcResponse::success(
+                            req.id,
+                            json!({ "description": prompt.description, "messages": [message] }),
+                        ))
+                    }
+                    Err(e) => Some(JsonRpcResponse::error(req.id, INVALID_PARAMS, e)),
                 }
-            };
+            }
 
-            let result = if params.name == "workflow_run" {
-                execute_workflow(registry, &params.arguments)
-            } else {
-                registry.call_tool(&params.name, &params.arguments)
-            };
-            Some(JsonRpcResponse::success(
-                req.id,
-                serde_json::to_value(result).unwrap(),
-            ))
+            _ => {
+                warn!(method = %req.method, "unknown method");
+                Some(JsonRpcResponse::error(
+                    req.id,
+                    METHOD_NOT_FOUND,
+                    format!("Method not found: {}", req.method),
+                ))
+            }
         }
+    }
+}
 
-        _ => {
-            warn!(method = %req.method, "unknown method");
-            Some(JsonRpcResponse::error(
-                req.id,
-                -32601,
-                format!("Method not found: {}", req.method),
-            ))
+fn invalid_request(reason: &str) -> JsonRpcResponse {
+    warn!("invalid request: {reason}");
+    JsonRpcResponse::error(Value::Null, INVALID_REQUEST, format!("Invalid Request: {reason}"))
+}
+
+fn resource_not_found(id: Value, uri: &str) -> JsonRpcResponse {
+    JsonRpcResponse::error(id, RESOURCE_NOT_FOUND, format!("Resource '{uri}' not found or not permitted"))
+}
+
+/// Progress reporter that sends `notifications/progress` for `token` to the
+/// session. Over HTTP these go out on the session's SSE stream.
+fn progress_reporter(out: Outbound, token: Value, version: ProtocolVersion) -> Progress {
+    Progress::new(move |progress, total, message| {
+        let mut params = json!({ "progressToken": token, "progress": progress });
+        if let Some(total) = total {
+            params["total"] = json!(total);
         }
-    }
+        if version.supports_progress_message() && !message.is_empty() {
+            params["message"] = json!(message);
+        }
+        out.notify("notifications/progress", params);
+    })
 }
 
-// ── Workflow Meta-Tool ─────────────────────────────────────────────────────
-
-fn workflow_tool_definition() -> Tool {
-    Tool {
-        name: "workflow_run".into(),
-        description: "Execute a sequence of daemon tools atomically with variable passing between steps. Each step's result is stored in its output_var and available as $var_name in subsequent step arguments.".into(),
-        input_schema: json!({
-            "type": "object",
-            "properties": {
-                "steps": {
-                    "type": "array",
-                    "items": {
-                        "type": "object",
-                        "properties": {
-                            "tool": {
-                                "type": "string",
-                                "description": "Tool name to call"
-                            },
-                            "arguments": {
-                                "type": "object",
-                                "description": "Arguments to pass to the tool. Use $var_name to reference previous step outputs."
-                            },
-                            "output_var": {
-                                "type": "string",
-                                "description": "Variable name to store this step's result (optional)"
-                            }
-                        },
-                        "required": ["tool"]
-                    },
-                    "description": "Ordered list of tool calls to execute"
-                }
-            },
-            "required": ["steps"]
-        }),
-    }
+/// The `completion/complete` result, capped at the 100 values the spec allows.
+fn completion_result(mut values: Vec<String>) -> Value {
+    let total = values.len();
+    values.truncate(MAX_COMPLETIONS);
+    json!({
+        "completion": { "values": values, "total": total, "hasMore": total > MAX_COMPLETIONS }
+    })
 }
 
-fn execute_workflow(registry: &CapabilityRegistry, arguments: &Value) -> CallToolResult {
-    let steps = match arguments["steps"].as_array() {
-        Some(s) => s,
-        None => return CallToolResult::error("'steps' must be an array"),
+/// A tools/call that failed before reaching the tool, as a JSON-RPC error.
+fn tool_call_error(id: Value, error: DaemonError) -> JsonRpcResponse {
+    let code = match error {
+        DaemonError::InvalidArgument { .. } | DaemonError::UnknownTool(_) => INVALID_PARAMS,
+        DaemonError::PermissionDenied { .. } => PERMISSION_DENIED,
+        DaemonError::NotSupportedOnPlatform(_) => NOT_SUPPORTED,
+        #[cfg(any(target_os = "macos", test))]
+        DaemonError::MissingDependency { .. } => super::protocol::MISSING_DEPENDENCY,
+        DaemonError::Timeout { .. } => TIMEOUT,
+        DaemonError::RateLimited { .. } => RATE_LIMITED,
+        DaemonError::PolicyViolation { .. } => POLICY_VIOLATION,
+        #[cfg(target_os = "macos")]
+        DaemonError::Platform(_) => INTERNAL_ERROR,
+        DaemonError::Client(_) | DaemonError::Io(_) | DaemonError::Json(_) => INTERNAL_ERROR,
     };
+    JsonRpcResponse::error_with_data(id, code, error.to_string(), error.data())
+}
 
-    if steps.is_empty() {
-        return CallToolResult::error("'steps' array is empty");
-    }
+/// Deserialize request params, mapping failures to an Invalid params response.
+fn parse_params<T: DeserializeOwned>(req: &JsonRpcRequest) -> Result<T, Box<JsonRpcResponse>> {
+    serde_json::from_value(req.params.clone()).map_err(|e| {
+        Box::new(JsonRpcResponse::error(req.id.clone(), INVALID_PARAMS, format!("Invalid params: {e}")))
+    })
+}
 
-    if steps.len() > 20 {
-        return CallToolResult::error("Workflow limited to 20 steps maximum");
-    }
+#[cfg(test)]
+mod tests {
+    use std::io::{self, Write};
+    use std::time::Duration;
 
-    let mut vars: std::collections::HashMap<String, Value> = std::collections::HashMap::new();
-    let mut step_results: Vec<Value> = Vec::new();
+    use super::*;
+    use crate::capabilities::{CapabilityProvider, ToolResult};
 
-    for (i, step) in steps.iter().enumerate() {
-        let tool_name = match step["tool"].as_str() {
-            Some(t) => t,
-            None => return CallToolResult::error(format!("Step {}: 'tool' must be a string", i + 1)),
-        };
+    /// Everything the server wrote to the client, one message per line.
+    #[derive(Clone, Default)]
+    struct Sent(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Sent {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
 
-        // Don't allow recursive workflow calls
-        if tool_name == "workflow_run" {
-            return CallToolResult::error(format!("Step {}: recursive workflow_run not allowed", i + 1));
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
         }
+    }
 
-        let mut tool_args = step.get("arguments").cloned().unwrap_or(json!({}));
+    impl Sent {
+        fn messages(&self) -> Vec<Value> {
+            let sent = String::from_utf8(self.0.lock().unwrap().clone()).unwrap();
+            sent.lines().map(|l| serde_json::from_str(l).unwrap()).collect()
+        }
 
-        // Substitute $var_name references in string arguments
-        substitute_vars(&mut tool_args, &vars);
+        /// Wait a few seconds for a message matching `matches`.
+        fn wait_for(&self, matches: impl Fn(&Value) -> bool) -> Value {
+            let deadline = Instant::now() + Duration::from_secs(5);
+            while Instant::now() < deadline {
+                if let Some(message) = self.messages().into_iter().find(&matches) {
+                    return message;
+                }
+                thread::sleep(Duration::from_millis(10));
+            }
+            panic!("no matching message sent");
+        }
+    }
 
-        debug!(step = i + 1, tool = tool_name, "workflow step");
-        let result = registry.call_tool(tool_name, &tool_args);
+    /// `wait` runs until its request is cancelled (for at most 5 seconds),
+    /// `echo` answers with its arguments.
+    struct TestTools;
 
-        // Extract text from result content
-        let text_output: String = result
-            .content
-            .iter()
-            .filter_map(|c| match c {
-                super::types::ContentBlock::Text { text } => Some(text.as_str()),
-                _ => None,
-            })
-            .collect::<Vec<_>>()
-            .join("\n");
-
-        let is_error = result.is_error.unwrap_or(false);
-
-        // Store in output_var if specified
-        if let Some(var_name) = step["output_var"].as_str() {
-            // Try to parse as JSON, fall back to string value
-            let var_value = serde_json::from_str::<Value>(&text_output)
-                .unwrap_or_else(|_| json!(text_output));
-            vars.insert(var_name.to_string(), var_value);
+    impl CapabilityProvider for TestTools {
+        fn id(&self) -> &str {
+            "test"
         }
 
-        step_results.push(json!({
-            "step": i + 1,
-            "tool": tool_name,
-            "output": text_output,
-            "is_error": is_error,
-        }));
-
-        // Stop on error
-        if is_error {
-            return CallToolResult::json(&json!({
-                "completed_steps": i + 1,
-                "total_steps": steps.len(),
-                "stopped_on_error": true,
-                "results": step_results,
-            }));
+        fn name(&self) -> &str {
+            "Test"
         }
-    }
 
-    CallToolResult::json(&json!({
-        "completed_steps": steps.len(),
-        "total_steps": steps.len(),
-        "results": step_results,
-    }))
-}
+        fn tools(&self) -> Vec<Tool> {
+            let tool = |name: &str| Tool {
+                name: name.into(),
+                description: String::new(),
+                input_schema: json!({ "type": "object", "properties": {} }),
+                output_schema: None,
+                annotations: ToolAnnotations::read_only(),
+            };
+            vec![tool("wait"), tool("echo")]
+        }
 
-/// Recursively substitute $var_name references in argument values.
-fn substitute_vars(value: &mut Value, vars: &std::collections::HashMap<String, Value>) {
-    match value {
-        Value::String(s) => {
-            // Check if the entire string is a variable reference
-            if s.starts_with('$') && !s.contains(' ') {
-                let var_name = &s[1..];
-                if let Some(var_val) = vars.get(var_name) {
-                    *value = var_val.clone();
-                    return;
-                }
-            }
-            // Otherwise do string interpolation for $var within text
-            for (var_name, var_val) in vars {
-                let placeholder = format!("${var_name}");
-                if s.contains(&placeholder) {
-                    let replacement = match var_val {
-                        Value::String(sv) => sv.clone(),
-                        other => other.to_string(),
-                    };
-                    *s = s.replace(&placeholder, &replacement);
+        fn call(&self, tool_name: &str, arguments: &Value, _progress: &Progress) -> Option<ToolResult> {
+            match tool_name {
+                "wait" => {
+                    let deadline = Instant::now() + Duration::from_secs(5);
+                    while !process::is_cancelled() && Instant::now() < deadline {
+                        thread::sleep(Duration::from_millis(10));
+                    }
+                    Some(Ok(CallToolResult::text("waited")))
                 }
+                "echo" => Some(Ok(CallToolResult::text(arguments.to_string()))),
+                _ => None,
             }
         }
-        Value::Object(map) => {
-            for val in map.values_mut() {
-                substitute_vars(val, vars);
+    }
+
+    /// A server with the `TestTools` capability, under `permissions` (TOML).
+    fn server_with(permissions: &str) -> Arc<Server> {
+        let mut config = FamiliarConfig::default();
+        config.audit.enabled = false;
+        config.audit.path = "/dev/null".into();
+        let audit = Arc::new(AuditLog::new(config.audit.clone()));
+        let mut registry = CapabilityRegistry::new(permissions::parse(permissions).unwrap());
+        registry.register(Box::new(TestTools));
+        registry.register(audit::provider(audit.clone()));
+        Arc::new(Server {
+            registry: Arc::new(registry),
+            audit,
+            config,
+            prompts: Vec::new(),
+            sessions: Mutex::new(Vec::new()),
+        })
+    }
+
+    fn server() -> Arc<Server> {
+        server_with("")
+    }
+
+    /// A new session, and what the server sends it.
+    fn connect(server: &Server) -> (Arc<Session>, Sent) {
+        let sent = Sent::default();
+        (server.open_session(Outbound::stream(Box::new(sent.clone()))), sent)
+    }
+
+    fn session(server: &Server) -> Arc<Session> {
+        connect(server).0
+    }
+
+    /// Complete the `initialize` handshake of `session` as client `client`.
+    fn initialize(server: &Server, session: &Session, client: &str) {
+        let initialize = json!({
+            "jsonrpc": "2.0", "id": 0, "method": "initialize",
+            "params": {
+                "protocolVersion": "2025-06-18",
+                "clientInfo": { "name": client },
+                "capabilities": { "elicitation": {} }
             }
+        });
+        reply(server, session, &initialize.to_string()).unwrap();
+        reply(server, session, r#"{"jsonrpc":"2.0","method":"notifications/initialized"}"#);
+    }
+
+    /// A session past the `initialize` handshake, as client `client`.
+    fn initialized(server: &Server, client: &str) -> Arc<Session> {
+        let session = session(server);
+        initialize(server, &session, client);
+        session
+    }
+
+    fn reply(server: &Server, session: &Session, raw: &str) -> Option<Value> {
+        server.handle_message(session, raw).map(|json| serde_json::from_str(&json).unwrap())
+    }
+
+    #[test]
+    fn unparseable_message_is_a_parse_error() {
+        let server = server();
+        let reply = reply(&server, &session(&server), r#"{"jsonrpc":"2.0","#).unwrap();
+        assert_eq!(reply["id"], Value::Null);
+        assert_eq!(reply["error"]["code"], PARSE_ERROR);
+    }
+
+    #[test]
+    fn empty_batch_is_an_invalid_request() {
+        let server = server();
+        let reply = reply(&server, &session(&server), "[]").unwrap();
+        assert_eq!(reply["error"]["code"], INVALID_REQUEST);
+    }
+
+    #[test]
+    fn batch_answers_each_request_in_order() {
+        let server = server();
+        let batch = r#"[
+            {"jsonrpc":"2.0","id":1,"method":"ping"},
+            {"jsonrpc":"2.0","method":"notifications/cancelled","params":{"requestId":9}},
+            {"jsonrpc":"2.0","id":"two","method":"no/such/method"},
+            {"jsonrpc":"2.0","id":3,"method":"tools/list"}
+        ]"#;
+        let reply = reply(&server, &initialized(&server, "test"), batch).unwrap();
+        let replies = reply.as_array().unwrap();
+        assert_eq!(replies.len(), 3);
+        assert_eq!(replies[0]["id"], 1);
+        assert!(replies[0]["result"].is_object());
+        assert_eq!(replies[1]["id"], "two");
+        assert_eq!(replies[1]["error"]["code"], METHOD_NOT_FOUND);
+        assert_eq!(replies[2]["id"], 3);
+        assert!(replies[2]["result"]["tools"].is_array());
+    }
+
+    #[test]
+    fn requests_before_initialization_are_refused() {
+        let server = server();
+        let reply = reply(&server, &session(&server), r#"{"jsonrpc":"2.0","id":1,"method":"tools/list"}"#).unwrap();
+        assert_eq!(reply["error"]["code"], INVALID_REQUEST);
+    }
+
+    #[test]
+    fn batch_of_notifications_gets_no_reply() {
+        let server = server();
+        let batch = r#"[{"jsonrpc":"2.0","method":"notifications/cancelled","params":{"requestId":1}}]"#;
+        assert!(reply(&server, &session(&server), batch).is_none());
+    }
+
+    #[test]
+    fn invalid_batch_items_are_invalid_requests() {
+        let server = server();
+        let batch = r#"[1, {"jsonrpc":"1.0","id":1,"method":"ping"}, {"jsonrpc":"2.0","id":{},"method":"ping"}, {"jsonrpc":"2.0","id":2}]"#;
+        let reply = reply(&server, &session(&server), batch).unwrap();
+        let replies = reply.as_array().unwrap();
+        assert_eq!(replies.len(), 4);
+        for reply in replies {
+            assert_eq!(reply["id"], Value::Null);
+            assert_eq!(reply["error"]["code"], INVALID_REQUEST);
+        }
+    }
+
+    #[test]
+    fn cancelling_a_batched_call_before_it_starts_is_not_lost() {
+        let server = server_with("[capabilities.test]\nallowed = true");
+        let (session, sent) = connect(&server);
+        initialize(&server, &session, "test");
+        let batch = r#"[
+            {"jsonrpc":"2.0","id":1,"method":"tools/call","params":{"name":"wait"}},
+            {"jsonrpc":"2.0","id":2,"method":"ping"}
+        ]"#;
+        let started = Instant::now();
+        server.dispatch(&session, batch);
+        server.dispatch(&session, r#"{"jsonrpc":"2.0","method":"notifications/cancelled","params":{"requestId":1}}"#);
+
+        // The cancelled call gets no response
+        let reply = sent.wait_for(Value::is_array);
+        assert_eq!(reply, json!([{ "jsonrpc": "2.0", "id": 2, "result": {} }]));
+        assert!(started.elapsed() < Duration::from_secs(4));
+    }
+
+    #[test]
+    fn tool_annotations_are_left_out_for_2024_11_05() {
+        let server = server_with("[capabilities.test]\nallowed = true");
+        for (version, has_annotations) in [("2024-11-05", false), ("2025-03-26", true)] {
+            let session = session(&server);
+            let initialize = json!({
+                "jsonrpc": "2.0", "id": 0, "method": "initialize",
+                "params": { "protocolVersion": version }
+            });
+            reply(&server, &session, &initialize.to_string()).unwrap();
+            reply(&server, &session, r#"{"jsonrpc":"2.0","method":"notifications/initialized"}"#);
+
+            let reply = reply(&server, &session, r#"{"jsonrpc":"2.0","id":1,"method":"tools/list"}"#).unwrap();
+            let tool = &reply["result"]["tools"][0];
+            assert_eq!(tool["name"], "wait");
+            assert_eq!(tool.get("annotations").is_some(), has_annotations, "{version}");
+        }
+    }
+
+    #[test]
+    fn log_messages_reach_only_their_session() {
+        let server = server();
+        let (first, first_sent) = connect(&server);
+        let (second, second_sent) = connect(&server);
+        initialize(&server, &first, "first");
+        initialize(&server, &second, "second");
+
+        server.log(LogRecord {
+            session: first.id(),
+            level: LogLevel::Error,
+            logger: "test",
+            data: json!({ "message": "for the first session" }),
+        });
+        let message = first_sent.wait_for(|m| m["method"] == "notifications/message");
+        assert_eq!(message["params"]["data"]["message"], "for the first session");
+        assert!(second_sent.messages().is_empty());
+    }
+
+    #[test]
+    fn batched_call_can_ask_for_confirmation() {
+        let server = server_with("[capabilities.test]\nallowed = true\n[capabilities.test.tools]\necho = \"ask\"");
+        let (session, sent) = connect(&server);
+        initialize(&server, &session, "test");
+        let batch = r#"[
+            {"jsonrpc":"2.0","id":1,"method":"tools/call","params":{"name":"echo"}},
+            {"jsonrpc":"2.0","id":2,"method":"ping"}
+        ]"#;
+        server.dispatch(&session, batch);
+
+        // The reader is free to pass on the user's answer
+        let ask = sent.wait_for(|m| m["method"] == "elicitation/create");
+        let answer = json!({ "jsonrpc": "2.0", "id": ask["id"], "result": { "action": "accept" } });
+        server.dispatch(&session, &answer.to_string());
+
+        let reply = sent.wait_for(Value::is_array);
+        assert_eq!(reply[0]["id"], 1);
+        assert_eq!(reply[0]["result"]["content"][0]["text"], "{}");
+        assert_eq!(reply[1]["id"], 2);
+    }
+
+    #[test]
+    fn unknown_tool_is_an_invalid_params_error() {
+        let server = server_with("[capabilities.test]\nallowed = true\n[capabilities.test.tools]\nwait = false");
+        let session = initialized(&server, "test");
+        for name in ["missing", "wait"] {
+            let call = json!({ "jsonrpc": "2.0", "id": 1, "method": "tools/call", "params": { "name": name } });
+            let reply = reply(&server, &session, &call.to_string()).unwrap();
+            assert_eq!(reply["error"]["code"], INVALID_PARAMS);
+            assert_eq!(reply["error"]["data"], json!({ "kind": "unknown_tool", "tool": name }));
         }
-        Value::Array(arr) => {
-            for val in arr.iter_mut() {
-                substitute_vars(val, vars);
+    }
+
+    #[test]
+    fn audit_query_is_denied_to_profiles_that_dont_allow_it() {
+        let server = server_with("[clients]\nbot = \"bot\"\n[profiles.bot.capabilities.test]\nallowed = true");
+        let call = json!({ "jsonrpc": "2.0", "id": 1, "method": "tools/call", "params": { "name": audit::QUERY_TOOL } });
+        let list = r#"{"jsonrpc":"2.0","id":2,"method":"tools/list"}"#;
+        for (client, allowed) in [("someone", true), ("bot", false)] {
+            let session = initialized(&server, client);
+            let tools = reply(&server, &session, list).unwrap()["result"]["tools"].clone();
+            let listed = tools.as_array().unwrap().iter().any(|t| t["name"] == audit::QUERY_TOOL);
+            assert_eq!(listed, allowed, "{client}");
+
+            let reply = reply(&server, &session, &call.to_string()).unwrap();
+            assert_eq!(reply["result"]["structuredContent"].is_object(), allowed, "{client}");
+            if !allowed {
+                assert_eq!(reply["error"]["data"]["kind"], "unknown_tool");
             }
         }
-        _ => {}
     }
 }
@@ -0,0 +1,226 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use serde_json::Value;
+
+use super::outbound::Outbound;
+use super::protocol::JsonRpcRequest;
+use super::subscriptions::Subscriptions;
+use super::types::{ClientInfo, LogLevel, ProtocolVersion};
+use crate::error::DaemonError;
+use crate::process::{self, CancelToken};
+
+/// Source of session ids.
+static NEXT_SESSION_ID: AtomicU64 = AtomicU64::new(1);
+
+/// How often a wait for a client response checks for cancellation.
+const RESPONSE_POLL: Duration = Duration::from_millis(250);
+
+/// Outcome of a server → client request: the result, or the error message.
+type Reply = Result<Value, String>;
+
+/// Per-connection state: where to send messages, what the client
+/// subscribed to, and what was negotiated at `initialize`.
+pub struct Session {
+    /// Unique for the lifetime of the daemon, for routing log messages.
+    id: u64,
+    pub out: Outbound,
+    pub subscriptions: Arc<Subscriptions>,
+    state: Mutex<SessionState>,
+    /// Requests currently being handled, keyed by serialized request id.
+    in_flight: Mutex<HashMap<String, CancelToken>>,
+    /// Requests sent to the client that await a response, by serialized id.
+    pending: Mutex<HashMap<String, Sender<Reply>>>,
+    next_request_id: AtomicU64,
+    /// Tools the user allowed for the rest of the session.
+    approved_tools: Mutex<HashSet<String>>,
+}
+
+#[derive(Default)]
+struct SessionState {
+    protocol_version: Option<ProtocolVersion>,
+    client_info: Option<ClientInfo>,
+    client_capabilities: Option<Value>,
+    /// Permissions profile the client was given at `initialize`; None for
+    /// the top-level permissions.
+    profile: Option<String>,
+    /// Set once the client sent `notifications/initialized`.
+    initialized: bool,
+    /// Least severe log level to forward, as set by `logging/setLevel`.
+    log_level: Option<LogLevel>,
+}
+
+impl Session {
+    pub fn new(out: Outbound) -> Self {
+        Self {
+            id: NEXT_SESSION_ID.fetch_add(1, Ordering::Relaxed),
+            out,
+            subscriptions: Arc::new(Subscriptions::default()),
+            state: Mutex::new(SessionState::default()),
+            in_flight: Mutex::new(HashMap::new()),
+            pending: Mutex::new(HashMap::new()),
+            next_request_id: AtomicU64::new(1),
+            approved_tools: Mutex::new(HashSet::new()),
+        }
+    }
+
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// Record the outcome of the `initialize` handshake.
+    pub fn begin(
+        &self,
+        version: ProtocolVersion,
+        client_info: Option<ClientInfo>,
+        capabilities: Option<Value>,
+        profile: Option<String>,
+    ) {
+        let mut state = self.lock();
+        state.protocol_version = Some(version);
+        state.client_info = client_info;
+        state.client_capabilities = capabilities;
+        state.profile = profile;
+    }
+
+    pub fn mark_initialized(&self) {
+        self.lock().initialized = true;
+    }
+
+    /// The negotiated protocol version, or None before `initialize`.
+    pub fn protocol_version(&self) -> Option<ProtocolVersion> {
+        self.lock().protocol_version
+    }
+
+    pub fn profile(&self) -> Option<String> {
+        self.lock().profile.clone()
+    }
+
+    pub fn is_initialized(&self) -> bool {
+        self.lock().initialized
+    }
+
+    /// Whether the client can be asked for input with `elicitation/create`.
+    pub fn supports_elicitation(&self) -> bool {
+        let state = self.lock();
+        state.protocol_version.is_some_and(ProtocolVersion::supports_elicitation)
+            && state
+                .client_capabilities
+                .as_ref()
+                .is_some_and(|c| c.get("elicitation").is_some())
+    }
+
+    pub fn set_log_level(&self, level: LogLevel) {
+        self.lock().log_level = Some(level);
+    }
+
+    /// Whether to forward a log message at `level`. Until the client picks
+    /// a level, only warnings and worse are sent.
+    pub fn wants_log(&self, level: LogLevel) -> bool {
+        level >= self.lock().log_level.unwrap_or(LogLevel::Warning)
+    }
+
+    /// The client's self-reported name, for logging.
+    pub fn client_name(&self) -> String {
+        self.lock()
+            .client_info
+            .as_ref()
+            .and_then(|c| c.name.clone())
+            .unwrap_or_else(|| "unknown".into())
+    }
+
+    /// Track a request so `notifications/cancelled` can reach it. A request
+    /// already tracked (by `dispatch`, before its thread started) keeps its
+    /// token, and with it any cancellation that arrived meanwhile.
+    pub fn start_request(&self, id: &Value) -> CancelToken {
+        self.lock_in_flight().entry(id.to_string()).or_default().clone()
+    }
+
+    pub fn finish_request(&self, id: &Value) {
+        self.lock_in_flight().remove(&id.to_string());
+    }
+
+    /// Cancel an in-flight request. Returns false if it already finished.
+    pub fn cancel_request(&self, id: &Value) -> bool {
+        match self.lock_in_flight().get(&id.to_string()) {
+            Some(token) => {
+                token.cancel();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Cancel everything still running, e.g. when the connection drops.
+    pub fn cancel_all(&self) {
+        for token in self.lock_in_flight().values() {
+            token.cancel();
+        }
+    }
+
+    /// Send a request to the client and wait for its response. Gives up
+    /// after `timeout`, or when the request this runs for is cancelled.
+    pub fn request(&self, method: &str, params: Value, timeout: Duration) -> Result<Value, DaemonError> {
+        let id = Value::from(self.next_request_id.fetch_add(1, Ordering::Relaxed));
+        let (tx, rx) = mpsc::channel();
+        self.lock_pending().insert(id.to_string(), tx);
+        self.out.send(&JsonRpcRequest::new(id.clone(), method, params));
+
+        let deadline = Instant::now() + timeout;
+        let reply = loop {
+            match rx.recv_timeout(RESPONSE_POLL) {
+                Ok(reply) => break reply.map_err(DaemonError::Client),
+                Err(RecvTimeoutError::Timeout) if process::is_cancelled() => {
+                    break Err(DaemonError::Client("request cancelled".into()));
+                }
+                Err(RecvTimeoutError::Timeout) if Instant::now() >= deadline => {
+                    break Err(DaemonError::Timeout {
+                        operation: format!("Client request {method}"),
+                        after: timeout,
+                    });
+                }
+                Err(RecvTimeoutError::Timeout) => continue,
+                Err(RecvTimeoutError::Disconnected) => break Err(DaemonError::Client("session closed".into())),
+            }
+        };
+        self.lock_pending().remove(&id.to_string());
+        reply
+    }
+
+    /// Hand a client response to the request waiting for it. Returns false
+    /// if nothing is waiting (unknown id, or it timed out).
+    pub fn complete_request(&self, id: &Value, reply: Reply) -> bool {
+        match self.lock_pending().remove(&id.to_string()) {
+            Some(tx) => tx.send(reply).is_ok(),
+            None => false,
+        }
+    }
+
+    pub fn is_tool_approved(&self, tool: &str) -> bool {
+        self.lock_approved().contains(tool)
+    }
+
+    /// Don't ask again for `tool` until the session ends.
+    pub fn approve_tool(&self, tool: &str) {
+        self.lock_approved().insert(tool.to_string());
+    }
+
+    fn lock_pending(&self) -> MutexGuard<'_, HashMap<String, Sender<Reply>>> {
+        self.pending.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn lock_approved(&self) -> MutexGuard<'_, HashSet<String>> {
+        self.approved_tools.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn lock_in_flight(&self) -> MutexGuard<'_, HashMap<String, CancelToken>> {
+        self.in_flight.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn lock(&self) -> MutexGuard<'_, SessionState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
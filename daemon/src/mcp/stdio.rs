@@ -0,0 +1,34 @@
+use std::io::{self, BufRead};
+use std::sync::Arc;
+
+use tracing::{error, info};
+
+use super::outbound::Outbound;
+use super::server::Server;
+
+/// Serve a single client over stdio: read JSON-RPC from stdin, write
+/// responses to stdout. Returns when stdin closes.
+pub fn serve(server: Arc<Server>) {
+    let stdin = io::stdin();
+    let session = server.open_session(Outbound::stdout());
+
+    for line in stdin.lock().lines() {
+        let line = match line {
+            Ok(l) => l,
+            Err(e) => {
+                error!("stdin read error: {e}");
+                break;
+            }
+        };
+
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            continue;
+        }
+
+        server.dispatch(&session, trimmed);
+    }
+
+    session.cancel_all();
+    info!(client = %session.client_name(), "stdin closed, shutting down");
+}
@@ -0,0 +1,84 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::sync::{Arc, Mutex, Weak};
+use std::thread;
+use std::time::Duration;
+
+use serde_json::json;
+use tracing::debug;
+
+use super::logging;
+use super::session::Session;
+use crate::capabilities::CapabilityRegistry;
+
+/// How often subscribed resources are re-read to detect changes.
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Resource URIs the client subscribed to, with a hash of the last content
+/// seen (None until the first successful read).
+#[derive(Default)]
+pub struct Subscriptions {
+    uris: Mutex<HashMap<String, Option<u64>>>,
+}
+
+impl Subscriptions {
+    pub fn subscribe(&self, uri: &str) {
+        self.lock().entry(uri.to_string()).or_insert(None);
+    }
+
+    pub fn unsubscribe(&self, uri: &str) {
+        self.lock().remove(uri);
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, Option<u64>>> {
+        self.uris.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Spawn the background thread that polls subscribed resources and sends
+/// `notifications/resources/updated` whenever their content changes.
+/// Resources are read with the session's permissions profile. The thread
+/// exits once the session is gone.
+pub fn spawn_watcher(registry: Arc<CapabilityRegistry>, session: &Arc<Session>) {
+    let session: Weak<Session> = Arc::downgrade(session);
+    thread::spawn(move || loop {
+        thread::sleep(POLL_INTERVAL);
+        let Some(session) = session.upgrade() else {
+            break;
+        };
+        logging::with_session(session.id(), || check(&registry, &session));
+    });
+}
+
+/// Re-read the resources `session` subscribed to, and notify it of those
+/// that changed.
+fn check(registry: &CapabilityRegistry, session: &Session) {
+    let (subscriptions, profile) = (&session.subscriptions, session.profile());
+
+    let uris: Vec<String> = subscriptions.lock().keys().cloned().collect();
+    for uri in uris {
+        let Some(Ok(contents)) = registry.read_resource(profile.as_deref(), &uri) else {
+            continue;
+        };
+        let mut hasher = DefaultHasher::new();
+        contents.text.hash(&mut hasher);
+        let hash = hasher.finish();
+
+        // Only notify on a change from a previously seen value; the first
+        // read just establishes the baseline
+        let changed = match subscriptions.lock().get_mut(&uri) {
+            Some(last) => {
+                let changed = last.is_some_and(|h| h != hash);
+                *last = Some(hash);
+                changed
+            }
+            None => false, // unsubscribed meanwhile
+        };
+
+        if changed {
+            debug!(%uri, "resource updated");
+            session.out.notify("notifications/resources/updated", json!({ "uri": uri }));
+        }
+    }
+}
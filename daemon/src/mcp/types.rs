@@ -9,6 +9,87 @@ pub struct Tool {
     pub description: String,
     #[serde(rename = "inputSchema")]
     pub input_schema: Value,
+    /// JSON Schema of the tool's `structuredContent`, for tools whose
+    /// output shape is stable enough to promise.
+    #[serde(rename = "outputSchema", skip_serializing_if = "Option::is_none")]
+    pub output_schema: Option<Value>,
+    pub annotations: ToolAnnotations,
+}
+
+/// What a tool does to the machine. Sent to clients as hints (from
+/// 2025-03-26 on) and matched by the `[annotations]` permission rules.
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct ToolAnnotations {
+    #[serde(rename = "readOnlyHint")]
+    pub read_only: bool,
+    #[serde(rename = "destructiveHint")]
+    pub destructive: bool,
+    #[serde(rename = "idempotentHint")]
+    pub idempotent: bool,
+    #[serde(rename = "openWorldHint")]
+    pub open_world: bool,
+}
+
+impl ToolAnnotations {
+    /// Only observes state.
+    pub const fn read_only() -> Self {
+        Self {
+            read_only: true,
+            destructive: false,
+            idempotent: true,
+            open_world: false,
+        }
+    }
+
+    /// Changes state, but nothing that can't be put back (launching,
+    /// focusing, moving, creating).
+    pub const fn additive() -> Self {
+        Self {
+            read_only: false,
+            destructive: false,
+            idempotent: false,
+            open_world: false,
+        }
+    }
+
+    /// May lose data or work: deleting, overwriting, quitting, closing.
+    pub const fn destructive() -> Self {
+        Self {
+            read_only: false,
+            destructive: true,
+            idempotent: false,
+            open_world: false,
+        }
+    }
+
+    /// Repeating the call with the same arguments has no further effect.
+    pub const fn idempotent(mut self) -> Self {
+        self.idempotent = true;
+        self
+    }
+
+    /// Reaches beyond this machine (network, web pages).
+    pub const fn open_world(mut self) -> Self {
+        self.open_world = true;
+        self
+    }
+}
+
+impl Tool {
+    /// The definition as sent to clients on `version`, without the fields
+    /// that revision doesn't know.
+    pub fn for_version(mut self, version: ProtocolVersion) -> Value {
+        if !version.supports_structured_content() {
+            self.output_schema = None;
+        }
+        let mut tool = serde_json::to_value(self).expect("failed to serialize tool");
+        if !version.supports_annotations()
+            && let Some(tool) = tool.as_object_mut()
+        {
+            tool.remove("annotations");
+        }
+        tool
+    }
 }
 
 // ── MCP Tool Call Result ─────────────────────────────────────────────────────
@@ -16,6 +97,9 @@ pub struct Tool {
 #[derive(Debug, Serialize)]
 pub struct CallToolResult {
     pub content: Vec<ContentBlock>,
+    /// The result as a JSON object, alongside its text serialization.
+    #[serde(rename = "structuredContent", skip_serializing_if = "Option::is_none")]
+    pub structured_content: Option<Value>,
     #[serde(rename = "isError", skip_serializing_if = "Option::is_none")]
     pub is_error: Option<bool>,
 }
@@ -25,6 +109,7 @@ pub struct CallToolResult {
 pub enum ContentBlock {
     #[serde(rename = "text")]
     Text { text: String },
+    #[cfg(all(target_os = "macos", feature = "screenshots"))]
     #[serde(rename = "image")]
     Image {
         data: String,
@@ -33,10 +118,21 @@ pub enum ContentBlock {
     },
 }
 
+impl ContentBlock {
+    fn text(&self) -> Option<&str> {
+        match self {
+            Self::Text { text } => Some(text),
+            #[cfg(all(target_os = "macos", feature = "screenshots"))]
+            _ => None,
+        }
+    }
+}
+
 impl CallToolResult {
     pub fn text(text: impl Into<String>) -> Self {
         Self {
             content: vec![ContentBlock::Text { text: text.into() }],
+            structured_content: None,
             is_error: None,
         }
     }
@@ -46,27 +142,176 @@ impl CallToolResult {
             content: vec![ContentBlock::Text {
                 text: message.into(),
             }],
+            structured_content: None,
             is_error: Some(true),
         }
     }
 
+    /// Concatenate all text blocks of the result.
+    pub fn text_output(&self) -> String {
+        self.content
+            .iter()
+            .filter_map(ContentBlock::text)
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Pretty-printed JSON text; objects are also returned as structured content.
     pub fn json(value: &Value) -> Self {
-        Self::text(serde_json::to_string_pretty(value).unwrap_or_else(|_| value.to_string()))
+        let mut result = Self::text(serde_json::to_string_pretty(value).unwrap_or_else(|_| value.to_string()));
+        if value.is_object() {
+            result.structured_content = Some(value.clone());
+        }
+        result
     }
 
+    /// Adapt the result to what clients on `version` understand: structured
+    /// content is dropped for revisions that predate it.
+    pub fn for_version(mut self, version: ProtocolVersion) -> Self {
+        if !version.supports_structured_content() {
+            self.structured_content = None;
+        }
+        self
+    }
+
+    #[cfg(all(target_os = "macos", feature = "screenshots"))]
     pub fn image(data: String, mime_type: impl Into<String>) -> Self {
         Self {
             content: vec![ContentBlock::Image {
                 data,
                 mime_type: mime_type.into(),
             }],
+            structured_content: None,
             is_error: None,
         }
     }
 }
 
+// ── MCP Resources ────────────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Resource {
+    pub uri: String,
+    pub name: String,
+    pub description: String,
+    #[serde(rename = "mimeType")]
+    pub mime_type: String,
+    /// The read-only tool whose state the resource exposes, whose
+    /// permissions it shares.
+    #[serde(skip)]
+    pub tool: &'static str,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ResourceContents {
+    pub uri: String,
+    #[serde(rename = "mimeType")]
+    pub mime_type: String,
+    pub text: String,
+}
+
+/// Params of resources/read, resources/subscribe and resources/unsubscribe.
+#[derive(Debug, Deserialize)]
+pub struct ResourceParams {
+    pub uri: String,
+}
+
+// ── MCP Prompts ──────────────────────────────────────────────────────────────
+
+#[derive(Debug, Serialize)]
+pub struct Prompt {
+    pub name: String,
+    pub description: String,
+    pub arguments: Vec<PromptArgument>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PromptArgument {
+    pub name: String,
+    pub description: String,
+    pub required: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PromptMessage {
+    pub role: String,
+    pub content: ContentBlock,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct GetPromptParams {
+    pub name: String,
+    #[serde(default)]
+    pub arguments: std::collections::HashMap<String, String>,
+}
+
 // ── MCP Initialize ───────────────────────────────────────────────────────────
 
+/// MCP protocol revisions this server speaks, oldest first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ProtocolVersion {
+    V2024_11_05,
+    V2025_03_26,
+    V2025_06_18,
+}
+
+impl ProtocolVersion {
+    pub const ALL: [Self; 3] = [Self::V2024_11_05, Self::V2025_03_26, Self::V2025_06_18];
+    pub const LATEST: Self = Self::V2025_06_18;
+
+    pub fn parse(version: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|v| v.as_str() == version)
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::V2024_11_05 => "2024-11-05",
+            Self::V2025_03_26 => "2025-03-26",
+            Self::V2025_06_18 => "2025-06-18",
+        }
+    }
+
+    /// Pick the revision to answer `initialize` with: the requested one if
+    /// supported, else the newest supported revision that predates it (the
+    /// revisions are ISO dates, so they order lexically). Clients older than
+    /// everything we support, or that send nothing, get the latest.
+    pub fn negotiate(requested: Option<&str>) -> Self {
+        let Some(requested) = requested else {
+            return Self::LATEST;
+        };
+        Self::ALL
+            .into_iter()
+            .rev()
+            .find(|v| v.as_str() <= requested)
+            .unwrap_or(Self::LATEST)
+    }
+
+    /// Tool `annotations` arrived in 2025-03-26.
+    pub fn supports_annotations(self) -> bool {
+        self >= Self::V2025_03_26
+    }
+
+    /// `structuredContent` and `outputSchema` arrived in 2025-06-18.
+    pub fn supports_structured_content(self) -> bool {
+        self >= Self::V2025_06_18
+    }
+
+    /// `elicitation/create` arrived in 2025-06-18.
+    pub fn supports_elicitation(self) -> bool {
+        self >= Self::V2025_06_18
+    }
+
+    /// `completion/complete` and the `completions` capability arrived in 2025-03-26.
+    pub fn supports_completions(self) -> bool {
+        self >= Self::V2025_03_26
+    }
+
+    /// The `message` field on progress notifications arrived in 2025-03-26.
+    pub fn supports_progress_message(self) -> bool {
+        self >= Self::V2025_03_26
+    }
+}
+
 #[derive(Debug, Deserialize)]
 pub struct InitializeParams {
     #[serde(rename = "protocolVersion")]
@@ -74,9 +319,11 @@ pub struct InitializeParams {
     pub capabilities: Option<Value>,
     #[serde(rename = "clientInfo")]
     pub client_info: Option<ClientInfo>,
+    #[serde(rename = "_meta", default)]
+    pub meta: RequestMeta,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 pub struct ClientInfo {
     pub name: Option<String>,
     pub version: Option<String>,
@@ -94,6 +341,11 @@ pub struct InitializeResult {
 #[derive(Debug, Serialize)]
 pub struct ServerCapabilities {
     pub tools: ToolsCapability,
+    pub resources: ResourcesCapability,
+    pub prompts: PromptsCapability,
+    pub logging: LoggingCapability,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub completions: Option<CompletionsCapability>,
 }
 
 #[derive(Debug, Serialize)]
@@ -102,6 +354,28 @@ pub struct ToolsCapability {
     pub list_changed: bool,
 }
 
+#[derive(Debug, Serialize)]
+pub struct ResourcesCapability {
+    pub subscribe: bool,
+    #[serde(rename = "listChanged")]
+    pub list_changed: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PromptsCapability {
+    #[serde(rename = "listChanged")]
+    pub list_changed: bool,
+}
+
+/// Advertised as `{}`: the server sends `notifications/message` and
+/// accepts `logging/setLevel`.
+#[derive(Debug, Serialize)]
+pub struct LoggingCapability {}
+
+/// Advertised as `{}` to clients that know `completion/complete`.
+#[derive(Debug, Serialize)]
+pub struct CompletionsCapability {}
+
 #[derive(Debug, Serialize)]
 pub struct ServerInfo {
     pub name: String,
@@ -115,4 +389,71 @@ pub struct CallToolParams {
     pub name: String,
     #[serde(default)]
     pub arguments: Value,
+    #[serde(rename = "_meta", default)]
+    pub meta: RequestMeta,
+}
+
+/// `_meta` on a request.
+#[derive(Debug, Default, Deserialize)]
+pub struct RequestMeta {
+    /// Opaque token (string or number) to echo in `notifications/progress`.
+    #[serde(rename = "progressToken")]
+    pub progress_token: Option<Value>,
+    /// Secret of the client's permissions profile, at `initialize`.
+    #[serde(rename = "profileToken")]
+    pub profile_token: Option<String>,
+    /// At `tools/call`: describe what the call would do instead of doing it.
+    #[serde(rename = "dryRun", default)]
+    pub dry_run: bool,
+}
+
+// ── MCP Logging ──────────────────────────────────────────────────────────────
+
+/// Syslog severities used by `logging/setLevel` and `notifications/message`,
+/// least severe first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+    Debug,
+    Info,
+    Notice,
+    Warning,
+    Error,
+    Critical,
+    Alert,
+    Emergency,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SetLevelParams {
+    pub level: LogLevel,
+}
+
+// ── MCP Completion ───────────────────────────────────────────────────────────
+
+#[derive(Debug, Deserialize)]
+pub struct CompleteParams {
+    #[serde(rename = "ref")]
+    pub reference: CompletionReference,
+    pub argument: CompletionArgument,
+}
+
+/// What the argument belongs to. `ref/tool` is our extension to the
+/// prompt and resource references of the spec.
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type")]
+pub enum CompletionReference {
+    #[serde(rename = "ref/prompt")]
+    Prompt { name: String },
+    #[serde(rename = "ref/resource")]
+    Resource {},
+    #[serde(rename = "ref/tool")]
+    Tool { name: String },
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CompletionArgument {
+    pub name: String,
+    #[serde(default)]
+    pub value: String,
 }
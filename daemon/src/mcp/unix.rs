@@ -0,0 +1,178 @@
+use std::fs;
+use std::io::{self, BufRead, BufReader};
+use std::os::unix::fs::{FileTypeExt, PermissionsExt};
+use std::os::unix::io::AsRawFd;
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::thread;
+
+use tracing::{error, info, warn};
+
+use super::outbound::Outbound;
+use super::server::Server;
+use crate::config::expand_home;
+
+/// Serve MCP on a unix domain socket so several local clients (editor,
+/// terminal app, tray) can attach to one long-lived daemon. Each connection
+/// is its own session with its own handshake; the registry is shared.
+/// Blocks for the lifetime of the daemon; returns only on startup failure.
+pub fn serve(server: Arc<Server>) -> Result<(), String> {
+    let path = expand_home(&server.config().daemon.unix_socket);
+    let listener = bind(&path)?;
+    info!(path = %path.display(), "unix transport listening");
+
+    let next_session = AtomicU64::new(1);
+    for stream in listener.incoming() {
+        match stream {
+            Ok(stream) => {
+                let id = next_session.fetch_add(1, Ordering::Relaxed);
+                let server = server.clone();
+                thread::spawn(move || serve_connection(server, id, stream));
+            }
+            Err(e) => warn!("unix socket accept error: {e}"),
+        }
+    }
+    Ok(())
+}
+
+/// Bind the socket owner-only (0600), replacing a stale socket left behind
+/// by a daemon that did not shut down cleanly.
+fn bind(path: &Path) -> Result<UnixListener, String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("failed to create {}: {e}", parent.display()))?;
+    }
+    remove_stale_socket(path)?;
+
+    // Restrict the mode at creation so there is no window before the chmod
+    // where another user could connect
+    let previous = unsafe { libc::umask(0o177) };
+    let listener = UnixListener::bind(path);
+    unsafe { libc::umask(previous) };
+    let listener =
+        listener.map_err(|e| format!("failed to bind unix socket {}: {e}", path.display()))?;
+
+    fs::set_permissions(path, fs::Permissions::from_mode(0o600))
+        .map_err(|e| format!("failed to set permissions on {}: {e}", path.display()))?;
+    Ok(listener)
+}
+
+fn remove_stale_socket(path: &Path) -> Result<(), String> {
+    let Ok(meta) = fs::symlink_metadata(path) else {
+        return Ok(());
+    };
+    if !meta.file_type().is_socket() {
+        return Err(format!("{} exists and is not a socket", path.display()));
+    }
+    if UnixStream::connect(path).is_ok() {
+        return Err(format!("another daemon is already listening on {}", path.display()));
+    }
+    fs::remove_file(path).map_err(|e| format!("failed to remove stale socket {}: {e}", path.display()))
+}
+
+/// Serve one connection: newline-delimited JSON-RPC, as on stdio.
+fn serve_connection(server: Arc<Server>, id: u64, stream: UnixStream) {
+    match peer_credentials(&stream) {
+        Ok(peer) => info!(
+            session = id,
+            pid = %peer.pid.map(|p| p.to_string()).unwrap_or_else(|| "unknown".into()),
+            uid = peer.uid,
+            gid = peer.gid,
+            "unix session opened"
+        ),
+        Err(e) => info!(session = id, "unix session opened (peer credentials unavailable: {e})"),
+    }
+
+    let writer = match stream.try_clone() {
+        Ok(w) => w,
+        Err(e) => {
+            error!(session = id, "failed to clone unix stream: {e}");
+            return;
+        }
+    };
+    let session = server.open_session(Outbound::stream(Box::new(writer)));
+
+    for line in BufReader::new(stream).lines() {
+        let line = match line {
+            Ok(l) => l,
+            Err(e) => {
+                warn!(session = id, "unix socket read error: {e}");
+                break;
+            }
+        };
+
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            continue;
+        }
+
+        server.dispatch(&session, trimmed);
+    }
+
+    session.cancel_all();
+    info!(session = id, client = %session.client_name(), "unix session closed");
+}
+
+struct PeerCredentials {
+    pid: Option<i32>,
+    uid: u32,
+    gid: u32,
+}
+
+#[cfg(target_os = "linux")]
+fn peer_credentials(stream: &UnixStream) -> io::Result<PeerCredentials> {
+    let mut cred = libc::ucred { pid: 0, uid: 0, gid: 0 };
+    let mut len = std::mem::size_of::<libc::ucred>() as libc::socklen_t;
+    let rc = unsafe {
+        libc::getsockopt(
+            stream.as_raw_fd(),
+            libc::SOL_SOCKET,
+            libc::SO_PEERCRED,
+            (&mut cred as *mut libc::ucred).cast(),
+            &mut len,
+        )
+    };
+    if rc != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(PeerCredentials {
+        pid: Some(cred.pid),
+        uid: cred.uid,
+        gid: cred.gid,
+    })
+}
+
+/// macOS has no SO_PEERCRED: uid/gid come from getpeereid, the pid from
+/// LOCAL_PEERPID.
+#[cfg(target_os = "macos")]
+fn peer_credentials(stream: &UnixStream) -> io::Result<PeerCredentials> {
+    let fd = stream.as_raw_fd();
+    let (mut uid, mut gid) = (0, 0);
+    if unsafe { libc::getpeereid(fd, &mut uid, &mut gid) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    let mut pid: libc::pid_t = 0;
+    let mut len = std::mem::size_of::<libc::pid_t>() as libc::socklen_t;
+    let rc = unsafe {
+        libc::getsockopt(
+            fd,
+            libc::SOL_LOCAL,
+            libc::LOCAL_PEERPID,
+            (&mut pid as *mut libc::pid_t).cast(),
+            &mut len,
+        )
+    };
+    Ok(PeerCredentials {
+        pid: (rc == 0).then_some(pid),
+        uid,
+        gid,
+    })
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+fn peer_credentials(_stream: &UnixStream) -> io::Result<PeerCredentials> {
+    Err(io::Error::new(io::ErrorKind::Unsupported, "not supported on this platform"))
+}
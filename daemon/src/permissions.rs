@@ -1,8 +1,21 @@
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fs;
-use std::path::PathBuf;
-use tracing::{info, warn};
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+use std::thread;
+use std::time::{Duration, SystemTime};
+use tracing::{error, info, warn};
+
+use crate::mcp::types::{Tool, ToolAnnotations};
+
+/// How often the permissions file is checked for changes.
+const WATCH_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Capabilities of the daemon itself (the audit log's query tool) that the
+/// top-level permissions of a file allow unless it configures them.
+/// Profiles get them only if they allow them.
+const ALLOWED_BY_DEFAULT: &[&str] = &[crate::audit::CAPABILITY];
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct PermissionsConfig {
@@ -10,6 +23,29 @@ pub struct PermissionsConfig {
     pub version: u32,
     #[serde(default)]
     pub capabilities: HashMap<String, CapabilityPermission>,
+    #[serde(default)]
+    pub annotations: AnnotationRules,
+    /// Named sets of permissions that replace the ones above for the
+    /// clients mapped to them in `clients`.
+    #[serde(default)]
+    pub profiles: HashMap<String, Profile>,
+    /// Profile of each client, by the `clientInfo.name` it sends at
+    /// `initialize`. Other clients get the top-level permissions.
+    #[serde(default)]
+    pub clients: HashMap<String, String>,
+}
+
+/// Permissions for some clients: `capabilities` and `annotations` as at
+/// the top level.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Profile {
+    /// Secret the client must send (`_meta.profileToken` of `initialize`)
+    /// to get the profile over the HTTP and Unix socket transports, where
+    /// any local process can claim any client name.
+    #[serde(default)]
+    pub token: Option<String>,
+    #[serde(flatten)]
+    pub permissions: PermissionsConfig,
 }
 
 fn default_version() -> u32 {
@@ -18,18 +54,264 @@ fn default_version() -> u32 {
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct CapabilityPermission {
-    pub allowed: bool,
+    pub allowed: Access,
+    #[serde(default)]
+    pub tools: HashMap<String, Access>,
+    #[serde(default)]
+    pub resources: HashMap<String, bool>,
+    /// Seconds each command run by the capability's tools may take before
+    /// it is killed. Unset: the capability's default.
+    #[serde(default)]
+    pub timeout_secs: Option<u64>,
+    /// Calls per minute across all the capability's tools. Unset: no limit.
+    #[serde(default)]
+    pub max_per_minute: Option<u32>,
+    /// Calls the capability's tools may make back to back before
+    /// `max_per_minute` kicks in. Unset: `max_per_minute`.
+    #[serde(default)]
+    pub burst: Option<u32>,
+    /// Rate limits of single tools, on top of the capability's.
+    #[serde(default)]
+    pub rate_limits: HashMap<String, RateLimit>,
+    /// Globs of the paths the capability's tools may touch. Empty: any.
+    #[serde(default)]
+    pub allowed_paths: Vec<String>,
+    /// Globs of paths the capability's tools may not touch, even if
+    /// allowed.
+    #[serde(default)]
+    pub denied_paths: Vec<String>,
+    /// Constraints on argument values, by tool and then argument.
     #[serde(default)]
-    pub tools: HashMap<String, bool>,
+    pub constraints: HashMap<String, BTreeMap<String, Constraint>>,
+}
+
+impl CapabilityPermission {
+    /// Allowed, with no overrides, limits or rules.
+    fn allowed() -> Self {
+        Self {
+            allowed: Access::Allow,
+            tools: HashMap::new(),
+            resources: HashMap::new(),
+            timeout_secs: None,
+            max_per_minute: None,
+            burst: None,
+            rate_limits: HashMap::new(),
+            allowed_paths: Vec::new(),
+            denied_paths: Vec::new(),
+            constraints: HashMap::new(),
+        }
+    }
+}
+
+/// At most `max_per_minute` calls a minute on average, and `burst` in
+/// quick succession (`max_per_minute` if unset).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub struct RateLimit {
+    pub max_per_minute: u32,
+    #[serde(default)]
+    pub burst: Option<u32>,
+}
+
+impl RateLimit {
+    pub fn burst(&self) -> u32 {
+        self.burst.unwrap_or(self.max_per_minute).max(1)
+    }
+}
+
+/// The values a tool argument may take, as globs (`*`, `?`) compared
+/// ignoring case with the argument read `as` a `ValueKind`. An argument
+/// that isn't given isn't checked.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct Constraint {
+    #[serde(rename = "as", default)]
+    pub kind: ValueKind,
+    /// The value must match one of these.
+    #[serde(rename = "in", default)]
+    pub allowed: Option<Vec<String>>,
+    /// The value must match none of these.
+    #[serde(default)]
+    pub not_in: Vec<String>,
+}
+
+/// How a constrained argument is read before matching.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ValueKind {
+    /// The value as given.
+    #[default]
+    Text,
+    /// The host of a URL; a pattern also matches its subdomains.
+    Domain,
+    /// A key combo like `cmd+shift+s`, whatever the order and spelling of
+    /// its modifiers.
+    KeyCombo,
+    /// The program of each command in a shell command line.
+    Program,
+}
+
+/// A permission setting: `true`, `false`, or `"ask"` to have the user
+/// confirm each call through the client. Ordered by strictness.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Deserialize, Serialize)]
+#[serde(try_from = "AccessSetting", into = "AccessSetting")]
+pub enum Access {
+    Allow,
+    Ask,
+    Deny,
+}
+
+/// How `Access` is written in the TOML file.
+#[derive(Deserialize, Serialize)]
+#[serde(untagged)]
+enum AccessSetting {
+    Bool(bool),
+    Word(String),
+}
+
+impl TryFrom<AccessSetting> for Access {
+    type Error = String;
+
+    fn try_from(setting: AccessSetting) -> Result<Self, Self::Error> {
+        match setting {
+            AccessSetting::Bool(true) => Ok(Self::Allow),
+            AccessSetting::Bool(false) => Ok(Self::Deny),
+            AccessSetting::Word(word) if word == "ask" => Ok(Self::Ask),
+            AccessSetting::Word(word) => Err(format!("expected true, false or \"ask\", got \"{word}\"")),
+        }
+    }
+}
+
+impl From<Access> for AccessSetting {
+    fn from(access: Access) -> Self {
+        match access {
+            Access::Allow => Self::Bool(true),
+            Access::Deny => Self::Bool(false),
+            Access::Ask => Self::Word("ask".into()),
+        }
+    }
+}
+
+/// Allow, deny or ask for tools by their annotations instead of by name,
+/// e.g. `read_only = true` to allow every read-only tool. Unset rules
+/// don't apply.
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct AnnotationRules {
+    pub read_only: Option<Access>,
+    pub destructive: Option<Access>,
+    pub open_world: Option<Access>,
+}
+
+impl AnnotationRules {
+    /// The verdict of the rules matching `annotations`, if any. When
+    /// several match, the strictest wins.
+    fn verdict(&self, annotations: &ToolAnnotations) -> Option<Access> {
+        [
+            (annotations.read_only, self.read_only),
+            (annotations.destructive, self.destructive),
+            (annotations.open_world, self.open_world),
+        ]
+        .into_iter()
+        .filter_map(|(matches, rule)| rule.filter(|_| matches))
+        .max()
+    }
 }
 
 impl PermissionsConfig {
-    /// Check if a specific tool is allowed.
-    /// Deny by default if capability or tool not configured.
-    pub fn is_tool_allowed(&self, capability_id: &str, tool_name: &str) -> bool {
+    /// The permissions of a profile, or the top-level ones for None. A
+    /// profile that no longer exists (removed by a reload) allows nothing.
+    pub fn profile(&self, name: Option<&str>) -> &PermissionsConfig {
+        static DENY_ALL: LazyLock<PermissionsConfig> = LazyLock::new(PermissionsConfig::default);
+        match name {
+            None => self,
+            Some(name) => self.profiles.get(name).map_or(&DENY_ALL, |p| &p.permissions),
+        }
+    }
+
+    /// The profile a client gets, by its name from `initialize`, with the
+    /// token it must present, if any.
+    pub fn client_profile(&self, client_name: &str) -> Option<(&str, Option<&str>)> {
+        let name = self.clients.get(client_name)?;
+        let token = self.profiles.get(name).and_then(|p| p.token.as_deref());
+        Some((name, token))
+    }
+
+    /// Problems parsing can't catch: clients mapped to unknown profiles,
+    /// and profiles with profiles of their own.
+    fn check_profiles(&self) -> Result<(), String> {
+        for (client, profile) in &self.clients {
+            if !self.profiles.contains_key(profile) {
+                return Err(format!("client '{client}' is mapped to unknown profile '{profile}'"));
+            }
+        }
+        for (name, profile) in &self.profiles {
+            if !profile.permissions.profiles.is_empty() || !profile.permissions.clients.is_empty() {
+                return Err(format!("profile '{name}' can't have profiles or clients of its own"));
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether a specific tool may run, and whether it needs confirmation.
+    /// Precedence: per-tool override, then annotation rules, then the
+    /// capability setting. Deny by default if none of them apply.
+    pub fn tool_access(&self, capability_id: &str, tool: &Tool) -> Access {
+        let cap = self.capabilities.get(capability_id);
+        if let Some(access) = cap.and_then(|c| c.tools.get(&tool.name)) {
+            return *access;
+        }
+        self.annotations
+            .verdict(&tool.annotations)
+            .unwrap_or_else(|| cap.map_or(Access::Deny, |c| c.allowed))
+    }
+
+    /// The configured command timeout for a capability, if any.
+    pub fn command_timeout(&self, capability_id: &str) -> Option<Duration> {
+        self.capabilities
+            .get(capability_id)
+            .and_then(|c| c.timeout_secs)
+            .map(Duration::from_secs)
+    }
+
+    /// The rate limits that apply to a tool: the capability's, shared by
+    /// all its tools, and the tool's own.
+    pub fn rate_limits(&self, capability_id: &str, tool_name: &str) -> (Option<RateLimit>, Option<RateLimit>) {
+        let Some(cap) = self.capabilities.get(capability_id) else {
+            return (None, None);
+        };
+        let capability = cap.max_per_minute.map(|max_per_minute| RateLimit {
+            max_per_minute,
+            burst: cap.burst,
+        });
+        (capability, cap.rate_limits.get(tool_name).copied())
+    }
+
+    /// The `allowed_paths` and `denied_paths` of a capability.
+    pub fn path_rules(&self, capability_id: &str) -> (&[String], &[String]) {
+        self.capabilities
+            .get(capability_id)
+            .map_or((&[], &[]), |c| (&c.allowed_paths, &c.denied_paths))
+    }
+
+    /// The constraints on the arguments of a tool, if any.
+    pub fn constraints(&self, capability_id: &str, tool_name: &str) -> Option<&BTreeMap<String, Constraint>> {
+        self.capabilities.get(capability_id)?.constraints.get(tool_name)
+    }
+
+    /// Check if a specific tool is listed: allowed, or allowed once confirmed.
+    pub fn is_tool_allowed(&self, capability_id: &str, tool: &Tool) -> bool {
+        self.tool_access(capability_id, tool) != Access::Deny
+    }
+
+    /// Check if a specific resource (by URI) is allowed: only where the
+    /// `tool` it mirrors is allowed outright, since reads can't be
+    /// confirmed, and unless a per-resource override hides it.
+    pub fn is_resource_allowed(&self, capability_id: &str, uri: &str, tool: &Tool) -> bool {
         match self.capabilities.get(capability_id) {
             None => false,
-            Some(cap) => cap.tools.get(tool_name).copied().unwrap_or(cap.allowed),
+            Some(cap) => {
+                self.tool_access(capability_id, tool) == Access::Allow
+                    && cap.resources.get(uri).copied().unwrap_or(true)
+            }
         }
     }
 
@@ -37,7 +319,7 @@ impl PermissionsConfig {
     pub fn is_capability_allowed(&self, capability_id: &str) -> bool {
         self.capabilities
             .get(capability_id)
-            .is_some_and(|cap| cap.allowed)
+            .is_some_and(|cap| cap.allowed != Access::Deny)
     }
 }
 
@@ -46,48 +328,293 @@ impl Default for PermissionsConfig {
         Self {
             version: 1,
             capabilities: HashMap::new(),
+            annotations: AnnotationRules::default(),
+            profiles: HashMap::new(),
+            clients: HashMap::new(),
         }
     }
 }
 
-/// Find and load the permissions config file.
+/// Permissions file locations.
 /// Search order:
 ///   1. FAMILIAR_DAEMON_CONFIG env var
 ///   2. ~/.familiar/daemon/permissions.toml
 ///   3. ./config/permissions.toml (dev fallback)
-/// If no file is found, return default (deny-all).
-pub fn load() -> PermissionsConfig {
-    let candidates = [
+fn candidates() -> impl Iterator<Item = PathBuf> {
+    [
         std::env::var("FAMILIAR_DAEMON_CONFIG")
             .ok()
             .map(PathBuf::from),
         dirs::home_dir().map(|h| h.join(".familiar/daemon/permissions.toml")),
         Some(PathBuf::from("config/permissions.toml")),
-    ];
-
-    for candidate in candidates.into_iter().flatten() {
-        if candidate.exists() {
-            match fs::read_to_string(&candidate) {
-                Ok(content) => match toml::from_str::<PermissionsConfig>(&content) {
-                    Ok(config) => {
-                        info!(
-                            path = %candidate.display(),
-                            capabilities = config.capabilities.len(),
-                            "loaded permissions"
-                        );
-                        return config;
-                    }
-                    Err(e) => {
-                        warn!(path = %candidate.display(), error = %e, "failed to parse permissions");
-                    }
-                },
-                Err(e) => {
-                    warn!(path = %candidate.display(), error = %e, "failed to read permissions");
-                }
+    ]
+    .into_iter()
+    .flatten()
+}
+
+/// Find and load the permissions config file, trying each candidate in
+/// search order. If no file is found, return default (deny-all).
+pub fn load() -> PermissionsConfig {
+    for candidate in candidates().filter(|c| c.exists()) {
+        match load_file(&candidate) {
+            Ok(config) => {
+                info!(
+                    path = %candidate.display(),
+                    capabilities = config.capabilities.len(),
+                    "loaded permissions"
+                );
+                return config;
             }
+            Err(e) => warn!(path = %candidate.display(), error = %e, "failed to load permissions"),
         }
     }
 
     info!("no permissions file found, using deny-all defaults");
     PermissionsConfig::default()
 }
+
+/// Read and parse one permissions file.
+fn load_file(path: &Path) -> Result<PermissionsConfig, String> {
+    let content = fs::read_to_string(path).map_err(|e| format!("read failed: {e}"))?;
+    parse(&content)
+}
+
+/// Parse the contents of a permissions file.
+pub fn parse(content: &str) -> Result<PermissionsConfig, String> {
+    let mut config: PermissionsConfig = toml::from_str(content).map_err(|e| format!("parse failed: {e}"))?;
+    config.check_profiles()?;
+    for id in ALLOWED_BY_DEFAULT {
+        config.capabilities.entry(id.to_string()).or_insert_with(CapabilityPermission::allowed);
+    }
+    Ok(config)
+}
+
+/// Watch the permissions file and call `on_change` with every valid new
+/// version. The watched file is the first candidate that exists, re-checked
+/// on every poll so a file created later (e.g. ~/.familiar/daemon/) takes
+/// over. An invalid or removed file keeps the current permissions.
+pub fn watch(on_change: impl Fn(PermissionsConfig) + Send + 'static) {
+    thread::spawn(move || {
+        let mut last = fingerprint();
+        loop {
+            thread::sleep(WATCH_INTERVAL);
+            let current = fingerprint();
+            if current == last {
+                continue;
+            }
+            last = current.clone();
+
+            let Some((path, ..)) = current else {
+                warn!("permissions file removed, keeping current permissions");
+                continue;
+            };
+            match load_file(&path) {
+                Ok(config) => {
+                    info!(
+                        path = %path.display(),
+                        capabilities = config.capabilities.len(),
+                        "reloaded permissions"
+                    );
+                    on_change(config);
+                }
+                Err(e) => {
+                    error!(path = %path.display(), error = %e, "invalid permissions file, keeping current permissions");
+                }
+            }
+        }
+    });
+}
+
+/// The active permissions file with its modification time and size.
+fn fingerprint() -> Option<(PathBuf, Option<SystemTime>, u64)> {
+    let path = candidates().find(|c| c.exists())?;
+    let meta = fs::metadata(&path).ok();
+    let modified = meta.as_ref().and_then(|m| m.modified().ok());
+    let len = meta.map_or(0, |m| m.len());
+    Some((path, modified, len))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn tool(name: &str, annotations: ToolAnnotations) -> Tool {
+        Tool {
+            name: name.into(),
+            description: String::new(),
+            input_schema: json!({ "type": "object" }),
+            output_schema: None,
+            annotations,
+        }
+    }
+
+    const PROFILES: &str = r#"
+        [capabilities.clipboard]
+        allowed = true
+        max_per_minute = 10
+
+        [capabilities.terminal]
+        allowed = "ask"
+
+        [clients]
+        desktop = "trusted"
+        bot = "restricted"
+
+        [profiles.trusted]
+        token = "s3cret"
+        [profiles.trusted.capabilities.terminal]
+        allowed = true
+
+        [profiles.restricted.annotations]
+        read_only = true
+        [profiles.restricted.capabilities.clipboard]
+        allowed = false
+    "#;
+
+    #[test]
+    fn clients_get_their_profile_and_its_token() {
+        let config = parse(PROFILES).unwrap();
+        assert_eq!(config.client_profile("desktop"), Some(("trusted", Some("s3cret"))));
+        assert_eq!(config.client_profile("bot"), Some(("restricted", None)));
+        assert_eq!(config.client_profile("someone else"), None);
+    }
+
+    #[test]
+    fn profiles_replace_the_top_level_permissions() {
+        let config = parse(PROFILES).unwrap();
+        let paste = tool("clipboard_read", ToolAnnotations::read_only());
+        let run = tool("terminal_run", ToolAnnotations::destructive());
+
+        assert_eq!(config.tool_access("clipboard", &paste), Access::Allow);
+        assert_eq!(config.tool_access("terminal", &run), Access::Ask);
+
+        // Nothing is inherited from the top level
+        let trusted = config.profile(Some("trusted"));
+        assert_eq!(trusted.tool_access("terminal", &run), Access::Allow);
+        assert_eq!(trusted.tool_access("clipboard", &paste), Access::Deny);
+        assert_eq!(trusted.rate_limits("clipboard", "clipboard_read"), (None, None));
+
+        // Annotation rules beat the capability setting
+        let restricted = config.profile(Some("restricted"));
+        assert_eq!(restricted.tool_access("clipboard", &paste), Access::Allow);
+        let copy = tool("clipboard_write", ToolAnnotations::additive());
+        assert_eq!(restricted.tool_access("clipboard", &copy), Access::Deny);
+        assert!(!restricted.is_capability_allowed("clipboard"));
+    }
+
+    #[test]
+    fn a_missing_profile_allows_nothing() {
+        let config = parse(PROFILES).unwrap();
+        let gone = config.profile(Some("removed"));
+        assert!(!gone.is_capability_allowed("clipboard"));
+        assert_eq!(gone.tool_access("clipboard", &tool("clipboard_read", ToolAnnotations::read_only())), Access::Deny);
+    }
+
+    #[test]
+    fn profiles_must_exist_and_not_nest() {
+        let unknown = parse("[clients]\nbot = \"nobody\"").unwrap_err();
+        assert!(unknown.contains("unknown profile 'nobody'"), "{unknown}");
+        let nested = parse("[profiles.a.clients]\nbot = \"a\"").unwrap_err();
+        assert!(nested.contains("profile 'a' can't have profiles or clients"), "{nested}");
+        assert!(parse("[capabilities.clipboard]\nallowed = \"sometimes\"").is_err());
+    }
+
+    #[test]
+    fn tool_overrides_beat_annotations_and_the_strictest_annotation_wins() {
+        let config = parse(
+            r#"
+            [annotations]
+            read_only = true
+            destructive = "ask"
+            open_world = false
+
+            [capabilities.browser]
+            allowed = true
+            tools = { browser_read = false }
+            "#,
+        )
+        .unwrap();
+        let access = |name, annotations| config.tool_access("browser", &tool(name, annotations));
+        assert_eq!(access("browser_read", ToolAnnotations::read_only()), Access::Deny);
+        assert_eq!(access("browser_tabs", ToolAnnotations::read_only()), Access::Allow);
+        assert_eq!(access("browser_close", ToolAnnotations::destructive()), Access::Ask);
+        assert_eq!(access("browser_open", ToolAnnotations::destructive().open_world()), Access::Deny);
+        assert_eq!(access("browser_focus", ToolAnnotations::additive()), Access::Allow);
+        assert_eq!(config.tool_access("network", &tool("ping", ToolAnnotations::additive())), Access::Deny);
+    }
+
+    #[test]
+    fn resources_need_their_tool_allowed_outright() {
+        let config = parse(
+            r#"
+            [capabilities.clipboard]
+            allowed = true
+            resources = { "familiar://clipboard/history" = false }
+
+            [capabilities.terminal]
+            allowed = "ask"
+
+            [capabilities.window_mgmt]
+            allowed = true
+            tools = { window_list = false }
+
+            [capabilities.display]
+            allowed = true
+            tools = { display_info = "ask" }
+            "#,
+        )
+        .unwrap();
+        let read = |name| tool(name, ToolAnnotations::read_only());
+        assert!(config.is_resource_allowed("clipboard", "familiar://clipboard", &read("clipboard_read")));
+        assert!(!config.is_resource_allowed("clipboard", "familiar://clipboard/history", &read("clipboard_read")));
+        assert!(!config.is_resource_allowed("terminal", "familiar://terminal", &read("terminal_list")));
+        assert!(!config.is_resource_allowed("window_mgmt", "familiar://windows", &read("window_list")));
+        assert!(!config.is_resource_allowed("display", "familiar://display/info", &read("display_info")));
+        assert!(!config.is_resource_allowed("network", "familiar://network/info", &read("network_info")));
+    }
+
+    #[test]
+    fn reads_limits_rules_and_timeouts() {
+        let config = parse(
+            r#"
+            [capabilities.file_ops]
+            allowed = true
+            timeout_secs = 30
+            max_per_minute = 20
+            burst = 5
+            rate_limits = { file_trash = { max_per_minute = 2 } }
+            allowed_paths = ["~/Documents"]
+            denied_paths = ["~/**/.git"]
+            constraints = { file_list = { path = { not_in = ["/"] } } }
+            "#,
+        )
+        .unwrap();
+        let limit = |max_per_minute, burst| Some(RateLimit { max_per_minute, burst });
+        assert_eq!(config.rate_limits("file_ops", "file_trash"), (limit(20, Some(5)), limit(2, None)));
+        assert_eq!(config.rate_limits("file_ops", "file_list"), (limit(20, Some(5)), None));
+        assert_eq!(limit(2, None).unwrap().burst(), 2);
+        assert_eq!(limit(0, None).unwrap().burst(), 1);
+        assert_eq!(config.command_timeout("file_ops"), Some(Duration::from_secs(30)));
+        assert_eq!(config.command_timeout("clipboard"), None);
+        assert_eq!(config.path_rules("file_ops"), (&["~/Documents".to_string()][..], &["~/**/.git".to_string()][..]));
+        assert!(config.constraints("file_ops", "file_list").is_some_and(|c| c.contains_key("path")));
+        assert!(config.constraints("file_ops", "file_move").is_none());
+    }
+
+    #[test]
+    fn audit_is_allowed_at_the_top_level_but_not_in_profiles() {
+        let config = parse(
+            r#"
+            [profiles.agent.capabilities.clipboard]
+            allowed = true
+            "#,
+        )
+        .unwrap();
+        assert!(config.is_capability_allowed("audit"));
+        assert!(!config.profile(Some("agent")).is_capability_allowed("audit"));
+
+        let config = parse("[capabilities.audit]\nallowed = false").unwrap();
+        assert!(!config.is_capability_allowed("audit"));
+    }
+}
@@ -0,0 +1,134 @@
+/// Every name the input tools accept for each key, by macOS virtual
+/// keycode; the first is the key's canonical name. Shared by the macOS
+/// backend and `key_combo` constraints, so both read a key the same way.
+const KEYS: &[(u16, &[&str])] = &[
+    // Letters
+    (0x00, &["a"]),
+    (0x0B, &["b"]),
+    (0x08, &["c"]),
+    (0x02, &["d"]),
+    (0x0E, &["e"]),
+    (0x03, &["f"]),
+    (0x05, &["g"]),
+    (0x04, &["h"]),
+    (0x22, &["i"]),
+    (0x26, &["j"]),
+    (0x28, &["k"]),
+    (0x25, &["l"]),
+    (0x2E, &["m"]),
+    (0x2D, &["n"]),
+    (0x1F, &["o"]),
+    (0x23, &["p"]),
+    (0x0C, &["q"]),
+    (0x0F, &["r"]),
+    (0x01, &["s"]),
+    (0x11, &["t"]),
+    (0x20, &["u"]),
+    (0x09, &["v"]),
+    (0x0D, &["w"]),
+    (0x07, &["x"]),
+    (0x10, &["y"]),
+    (0x06, &["z"]),
+    // Numbers
+    (0x1D, &["0"]),
+    (0x12, &["1"]),
+    (0x13, &["2"]),
+    (0x14, &["3"]),
+    (0x15, &["4"]),
+    (0x17, &["5"]),
+    (0x16, &["6"]),
+    (0x1A, &["7"]),
+    (0x1C, &["8"]),
+    (0x19, &["9"]),
+    // Special keys
+    (0x24, &["return", "enter"]),
+    (0x30, &["tab"]),
+    (0x31, &["space"]),
+    (0x33, &["delete", "backspace"]),
+    (0x35, &["escape", "esc"]),
+    (0x75, &["forward_delete", "forwarddelete"]),
+    (0x73, &["home"]),
+    (0x77, &["end"]),
+    (0x74, &["pageup", "page_up"]),
+    (0x79, &["pagedown", "page_down"]),
+    // Arrow keys
+    (0x7B, &["left"]),
+    (0x7C, &["right"]),
+    (0x7D, &["down"]),
+    (0x7E, &["up"]),
+    // Function keys
+    (0x7A, &["f1"]),
+    (0x78, &["f2"]),
+    (0x63, &["f3"]),
+    (0x76, &["f4"]),
+    (0x60, &["f5"]),
+    (0x61, &["f6"]),
+    (0x62, &["f7"]),
+    (0x64, &["f8"]),
+    (0x65, &["f9"]),
+    (0x6D, &["f10"]),
+    (0x67, &["f11"]),
+    (0x6F, &["f12"]),
+    // Punctuation
+    (0x27, &["apostrophe", "quote", "'"]),
+    (0x2A, &["backslash", "\\"]),
+    (0x2B, &["comma", ","]),
+    (0x18, &["equals", "equal", "="]),
+    (0x32, &["grave", "backtick", "`"]),
+    (0x21, &["left_bracket", "leftbracket", "["]),
+    (0x1B, &["minus", "-"]),
+    (0x2F, &["period", "."]),
+    (0x1E, &["right_bracket", "rightbracket", "]"]),
+    (0x29, &["semicolon", ";"]),
+    (0x2C, &["slash", "/"]),
+];
+
+/// The macOS virtual keycode of a key name, ignoring case.
+#[cfg(any(target_os = "macos", test))]
+pub fn keycode(name: &str) -> Option<u16> {
+    find(name).map(|(code, _)| *code)
+}
+
+/// The canonical name of the key a name refers to, ignoring case, so that
+/// aliases compare equal (`-` and `minus`, `enter` and `return`).
+pub fn canonical_name(name: &str) -> Option<&'static str> {
+    find(name).map(|(_, names)| names[0])
+}
+
+fn find(name: &str) -> Option<&'static (u16, &'static [&'static str])> {
+    let lower = name.to_lowercase();
+    KEYS.iter().find(|(_, names)| names.contains(&lower.as_str()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn aliases_share_a_keycode_and_canonical_name() {
+        for aliases in [
+            &["-", "minus"][..],
+            &["=", "equal", "equals"],
+            &["`", "grave", "backtick"],
+            &["/", "slash"],
+            &["enter", "Return"],
+        ] {
+            let codes: Vec<_> = aliases.iter().map(|a| keycode(a)).collect();
+            assert!(codes[0].is_some() && codes.iter().all(|c| *c == codes[0]), "{aliases:?}");
+            let names: Vec<_> = aliases.iter().map(|a| canonical_name(a)).collect();
+            assert!(names.iter().all(|n| *n == names[0]), "{aliases:?}");
+        }
+        assert_eq!(keycode("A"), Some(0x00));
+        assert_eq!(canonical_name("="), Some("equals"));
+        assert_eq!(canonical_name("hyper"), None);
+    }
+
+    #[test]
+    fn names_refer_to_one_key() {
+        let mut names: Vec<&str> = KEYS.iter().flat_map(|(_, names)| names.iter().copied()).collect();
+        let count = names.len();
+        names.sort();
+        names.dedup();
+        assert_eq!(names.len(), count);
+    }
+}
@@ -10,10 +10,6 @@ use ax_helpers::AXUIElementRef;
 
 /// Read the UI element tree for an application, up to `max_depth` levels deep.
 pub fn read_tree(pid: i32, max_depth: usize) -> CallToolResult {
-    if let Err(e) = ax_helpers::ensure_trusted() {
-        return CallToolResult::error(e);
-    }
-
     let app = unsafe { ax_helpers::AXUIElementCreateApplication(pid) };
     if app.is_null() {
         return CallToolResult::error(format!("Failed to create AX element for pid {pid}"));
@@ -98,10 +94,6 @@ fn read_element(element: AXUIElementRef, depth: usize, max_depth: usize) -> Valu
 
 /// Get the currently focused UI element from the system-wide accessibility object.
 pub fn focused_element() -> CallToolResult {
-    if let Err(e) = ax_helpers::ensure_trusted() {
-        return CallToolResult::error(e);
-    }
-
     let system = unsafe { ax_helpers::AXUIElementCreateSystemWide() };
     if system.is_null() {
         return CallToolResult::error("Failed to create system-wide AX element");
@@ -158,10 +150,6 @@ pub fn focused_element() -> CallToolResult {
 
 /// Find an element by role (and optional title) and perform the AXPress action.
 pub fn click_element(pid: i32, role: &str, title: Option<&str>) -> CallToolResult {
-    if let Err(e) = ax_helpers::ensure_trusted() {
-        return CallToolResult::error(e);
-    }
-
     let app = unsafe { ax_helpers::AXUIElementCreateApplication(pid) };
     if app.is_null() {
         return CallToolResult::error(format!("Failed to create AX element for pid {pid}"));
@@ -197,10 +185,6 @@ pub fn click_element(pid: i32, role: &str, title: Option<&str>) -> CallToolResul
 
 /// Find an element by role (and optional title) and set its AXValue attribute.
 pub fn set_value(pid: i32, role: &str, title: Option<&str>, value: &str) -> CallToolResult {
-    if let Err(e) = ax_helpers::ensure_trusted() {
-        return CallToolResult::error(e);
-    }
-
     let app = unsafe { ax_helpers::AXUIElementCreateApplication(pid) };
     if app.is_null() {
         return CallToolResult::error(format!("Failed to create AX element for pid {pid}"));
@@ -243,10 +227,6 @@ pub fn set_value(pid: i32, role: &str, title: Option<&str>, value: &str) -> Call
 
 /// Find an element by role (and optional title) and return all its attribute names and values.
 pub fn element_info(pid: i32, role: &str, title: Option<&str>) -> CallToolResult {
-    if let Err(e) = ax_helpers::ensure_trusted() {
-        return CallToolResult::error(e);
-    }
-
     let app = unsafe { ax_helpers::AXUIElementCreateApplication(pid) };
     if app.is_null() {
         return CallToolResult::error(format!("Failed to create AX element for pid {pid}"));
@@ -324,10 +304,6 @@ pub fn find_elements(
     max_results: usize,
     max_depth: usize,
 ) -> CallToolResult {
-    if let Err(e) = ax_helpers::ensure_trusted() {
-        return CallToolResult::error(e);
-    }
-
     if role.is_none() && title_pattern.is_none() && value_pattern.is_none() {
         return CallToolResult::error("At least one of role, title_pattern, or value_pattern must be provided");
     }
@@ -351,7 +327,7 @@ pub fn find_elements(
         return CallToolResult::error(format!("No elements found matching: {}", desc_parts.join(", ")));
     }
 
-    CallToolResult::json(&json!(results))
+    CallToolResult::json(&json!({ "elements": results }))
 }
 
 fn find_matching_elements(
@@ -446,10 +422,6 @@ fn find_matching_elements(
 
 /// List available actions on an element found by role and optional title.
 pub fn get_actions(pid: i32, role: &str, title: Option<&str>) -> CallToolResult {
-    if let Err(e) = ax_helpers::ensure_trusted() {
-        return CallToolResult::error(e);
-    }
-
     let app = unsafe { ax_helpers::AXUIElementCreateApplication(pid) };
     if app.is_null() {
         return CallToolResult::error(format!("Failed to create AX element for pid {pid}"));
@@ -500,10 +472,6 @@ pub fn get_actions(pid: i32, role: &str, title: Option<&str>) -> CallToolResult
 
 /// Perform a named action on an element found by role and optional title.
 pub fn perform_action(pid: i32, role: &str, title: Option<&str>, action: &str) -> CallToolResult {
-    if let Err(e) = ax_helpers::ensure_trusted() {
-        return CallToolResult::error(e);
-    }
-
     let app = unsafe { ax_helpers::AXUIElementCreateApplication(pid) };
     if app.is_null() {
         return CallToolResult::error(format!("Failed to create AX element for pid {pid}"));
@@ -538,10 +506,6 @@ pub fn perform_action(pid: i32, role: &str, title: Option<&str>, action: &str) -
 
 /// Scroll within a scroll area element.
 pub fn scroll_element(pid: i32, role: &str, title: Option<&str>, direction: &str, amount: i32) -> CallToolResult {
-    if let Err(e) = ax_helpers::ensure_trusted() {
-        return CallToolResult::error(e);
-    }
-
     let app = unsafe { ax_helpers::AXUIElementCreateApplication(pid) };
     if app.is_null() {
         return CallToolResult::error(format!("Failed to create AX element for pid {pid}"));
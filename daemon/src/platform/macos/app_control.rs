@@ -1,10 +1,12 @@
 use std::process::Command;
+use crate::process::RunCommand;
 use serde_json::{json, Value};
 
+use crate::capabilities::ToolResult;
 use crate::mcp::types::CallToolResult;
 
 /// List running GUI applications using osascript.
-pub fn list_apps() -> CallToolResult {
+pub fn list_apps() -> ToolResult {
     let script = r#"
 tell application "System Events"
     set appList to ""
@@ -23,39 +25,35 @@ tell application "System Events"
 end tell
 "#;
 
-    match Command::new("osascript").arg("-e").arg(script).output() {
-        Ok(output) => {
-            if !output.status.success() {
-                let stderr = String::from_utf8_lossy(&output.stderr);
-                return CallToolResult::error(format!("osascript failed: {stderr}"));
+    let output = Command::new("osascript").arg("-e").arg(script).run()?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Ok(CallToolResult::error(format!("osascript failed: {stderr}")));
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let apps: Vec<Value> = stdout
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .filter_map(|line| {
+            let parts: Vec<&str> = line.split("|||").collect();
+            if parts.len() >= 4 {
+                Some(json!({
+                    "name": parts[0].trim(),
+                    "pid": parts[1].trim().parse::<i64>().unwrap_or(0),
+                    "bundle_id": parts[2].trim(),
+                    "frontmost": parts[3].trim() == "true",
+                }))
+            } else {
+                None
             }
-            let stdout = String::from_utf8_lossy(&output.stdout);
-            let apps: Vec<Value> = stdout
-                .lines()
-                .filter(|line| !line.trim().is_empty())
-                .filter_map(|line| {
-                    let parts: Vec<&str> = line.split("|||").collect();
-                    if parts.len() >= 4 {
-                        Some(json!({
-                            "name": parts[0].trim(),
-                            "pid": parts[1].trim().parse::<i64>().unwrap_or(0),
-                            "bundle_id": parts[2].trim(),
-                            "frontmost": parts[3].trim() == "true",
-                        }))
-                    } else {
-                        None
-                    }
-                })
-                .collect();
+        })
+        .collect();
 
-            CallToolResult::json(&json!(apps))
-        }
-        Err(e) => CallToolResult::error(format!("Failed to run osascript: {e}")),
-    }
+    Ok(CallToolResult::json(&json!({ "apps": apps })))
 }
 
 /// Get info for a specific app by name.
-pub fn app_info(name: &str) -> CallToolResult {
+pub fn app_info(name: &str) -> ToolResult {
     let script = format!(
         r#"
 tell application "System Events"
@@ -78,80 +76,64 @@ end tell
 "#
     );
 
-    match Command::new("osascript").arg("-e").arg(&script).output() {
-        Ok(output) => {
-            if !output.status.success() {
-                let stderr = String::from_utf8_lossy(&output.stderr);
-                return CallToolResult::error(format!("osascript failed: {stderr}"));
-            }
-            let stdout = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    let output = Command::new("osascript").arg("-e").arg(&script).run()?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Ok(CallToolResult::error(format!("osascript failed: {stderr}")));
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout).trim().to_string();
 
-            if stdout.starts_with("ERROR:") {
-                return CallToolResult::error(format!(
-                    "App '{}' not found: {}",
-                    name,
-                    &stdout[6..]
-                ));
-            }
+    if stdout.starts_with("ERROR:") {
+        return Ok(CallToolResult::error(format!(
+            "App '{}' not found: {}",
+            name,
+            &stdout[6..]
+        )));
+    }
 
-            let parts: Vec<&str> = stdout.split("|||").collect();
-            if parts.len() >= 5 {
-                CallToolResult::json(&json!({
-                    "name": parts[0].trim(),
-                    "pid": parts[1].trim().parse::<i64>().unwrap_or(0),
-                    "bundle_id": parts[2].trim(),
-                    "frontmost": parts[3].trim() == "true",
-                    "visible": parts[4].trim() == "true",
-                }))
-            } else {
-                CallToolResult::error(format!("Unexpected output format: {stdout}"))
-            }
-        }
-        Err(e) => CallToolResult::error(format!("Failed to run osascript: {e}")),
+    let parts: Vec<&str> = stdout.split("|||").collect();
+    if parts.len() >= 5 {
+        Ok(CallToolResult::json(&json!({
+            "name": parts[0].trim(),
+            "pid": parts[1].trim().parse::<i64>().unwrap_or(0),
+            "bundle_id": parts[2].trim(),
+            "frontmost": parts[3].trim() == "true",
+            "visible": parts[4].trim() == "true",
+        })))
+    } else {
+        Ok(CallToolResult::error(format!("Unexpected output format: {stdout}")))
     }
 }
 
 /// Launch an application by name.
-pub fn launch_app(name: &str) -> CallToolResult {
-    match Command::new("open").args(["-a", name]).output() {
-        Ok(output) => {
-            if output.status.success() {
-                CallToolResult::text(format!("Launched '{name}'"))
-            } else {
-                let stderr = String::from_utf8_lossy(&output.stderr);
-                CallToolResult::error(format!("Failed to launch '{name}': {stderr}"))
-            }
-        }
-        Err(e) => CallToolResult::error(format!("Failed to run open: {e}")),
+pub fn launch_app(name: &str) -> ToolResult {
+    let output = Command::new("open").args(["-a", name]).run()?;
+    if output.status.success() {
+        Ok(CallToolResult::text(format!("Launched '{name}'")))
+    } else {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        Ok(CallToolResult::error(format!("Failed to launch '{name}': {stderr}")))
     }
 }
 
 /// Quit an application by name. If force is true, use killall instead of graceful quit.
-pub fn quit_app(name: &str, force: bool) -> CallToolResult {
+pub fn quit_app(name: &str, force: bool) -> ToolResult {
     if force {
-        match Command::new("killall").arg(name).output() {
-            Ok(output) => {
-                if output.status.success() {
-                    CallToolResult::text(format!("Force-killed '{name}'"))
-                } else {
-                    let stderr = String::from_utf8_lossy(&output.stderr);
-                    CallToolResult::error(format!("Failed to kill '{name}': {stderr}"))
-                }
-            }
-            Err(e) => CallToolResult::error(format!("Failed to run killall: {e}")),
+        let output = Command::new("killall").arg(name).run()?;
+        if output.status.success() {
+            Ok(CallToolResult::text(format!("Force-killed '{name}'")))
+        } else {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            Ok(CallToolResult::error(format!("Failed to kill '{name}': {stderr}")))
         }
     } else {
         let script = format!(r#"tell application "{name}" to quit"#);
-        match Command::new("osascript").arg("-e").arg(&script).output() {
-            Ok(output) => {
-                if output.status.success() {
-                    CallToolResult::text(format!("Quit '{name}'"))
-                } else {
-                    let stderr = String::from_utf8_lossy(&output.stderr);
-                    CallToolResult::error(format!("Failed to quit '{name}': {stderr}"))
-                }
-            }
-            Err(e) => CallToolResult::error(format!("Failed to run osascript: {e}")),
+        let output = Command::new("osascript").arg("-e").arg(&script).run()?;
+        if output.status.success() {
+            Ok(CallToolResult::text(format!("Quit '{name}'")))
+        } else {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            Ok(CallToolResult::error(format!("Failed to quit '{name}': {stderr}")))
         }
     }
 }
@@ -5,6 +5,8 @@ use core_foundation::string::CFString;
 use std::ffi::c_void;
 use std::ptr;
 
+use crate::error::DaemonError;
+
 // ── AX FFI Types ────────────────────────────────────────────────────────────
 
 pub type AXUIElementRef = *const c_void;
@@ -88,8 +90,9 @@ pub fn is_trusted() -> bool {
     unsafe { AXIsProcessTrusted() }
 }
 
-/// Check if trusted, prompting the user if not.
-pub fn ensure_trusted() -> Result<(), String> {
+/// Check if trusted, prompting the user if not. Capabilities call this
+/// before dispatching to the AX and event-posting backends.
+pub fn ensure_trusted() -> Result<(), DaemonError> {
     if is_trusted() {
         return Ok(());
     }
@@ -106,7 +109,10 @@ pub fn ensure_trusted() -> Result<(), String> {
     if trusted {
         Ok(())
     } else {
-        Err("Accessibility permission required. Please grant access in System Settings > Privacy & Security > Accessibility.".into())
+        Err(DaemonError::PermissionDenied {
+            permission: "accessibility".into(),
+            message: "Accessibility permission required. Please grant access in System Settings > Privacy & Security > Accessibility.".into(),
+        })
     }
 }
 
@@ -1,5 +1,7 @@
 use std::process::Command;
+use crate::process::RunCommand;
 use serde_json::json;
+use crate::capabilities::ToolResult;
 use crate::mcp::types::CallToolResult;
 
 /// Characters that are rejected from URLs to prevent command injection.
@@ -18,36 +20,31 @@ fn validate_url(url: &str) -> Result<(), String> {
 }
 
 /// Open a URL in the default or specified browser.
-pub fn open_url(url: &str, browser: Option<&str>) -> CallToolResult {
+pub fn open_url(url: &str, browser: Option<&str>) -> ToolResult {
     if let Err(msg) = validate_url(url) {
-        return CallToolResult::error(msg);
+        return Ok(CallToolResult::error(msg));
     }
 
     let output = if let Some(browser_name) = browser {
         Command::new("open")
             .args(["-a", browser_name, url])
-            .output()
+            .run()?
     } else {
         Command::new("open")
             .arg(url)
-            .output()
+            .run()?
     };
 
-    match output {
-        Ok(out) => {
-            if !out.status.success() {
-                let stderr = String::from_utf8_lossy(&out.stderr);
-                return CallToolResult::error(format!("open command failed: {stderr}"));
-            }
-            let target = browser.unwrap_or("default browser");
-            CallToolResult::text(format!("Opened {url} in {target}"))
-        }
-        Err(e) => CallToolResult::error(format!("Failed to run open: {e}")),
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Ok(CallToolResult::error(format!("open command failed: {stderr}")));
     }
+    let target = browser.unwrap_or("default browser");
+    Ok(CallToolResult::text(format!("Opened {url} in {target}")))
 }
 
 /// List all open tabs in the specified browser (or try Safari then Chrome).
-pub fn get_tabs(browser: Option<&str>) -> CallToolResult {
+pub fn get_tabs(browser: Option<&str>) -> ToolResult {
     let browsers: Vec<&str> = match browser {
         Some(b) => vec![b],
         None => vec!["Safari", "Google Chrome"],
@@ -86,7 +83,7 @@ end tell"#
             ),
         };
 
-        match Command::new("osascript").args(["-e", &script]).output() {
+        match Command::new("osascript").args(["-e", &script]).run() {
             Ok(output) => {
                 if !output.status.success() {
                     // This browser might not be running; try next one
@@ -94,20 +91,20 @@ end tell"#
                 }
                 let raw = String::from_utf8_lossy(&output.stdout);
                 let tabs = parse_tab_list(&raw);
-                return CallToolResult::json(&json!({
+                return Ok(CallToolResult::json(&json!({
                     "browser": browser_name,
                     "tabs": tabs
-                }));
+                })));
             }
             Err(_) => continue,
         }
     }
 
-    CallToolResult::error("No supported browser is running (tried Safari, Google Chrome)")
+    Ok(CallToolResult::error("No supported browser is running (tried Safari, Google Chrome)"))
 }
 
 /// Get the active (frontmost) tab in the specified browser (or try Safari then Chrome).
-pub fn get_active_tab(browser: Option<&str>) -> CallToolResult {
+pub fn get_active_tab(browser: Option<&str>) -> ToolResult {
     let browsers: Vec<&str> = match browser {
         Some(b) => vec![b],
         None => vec!["Safari", "Google Chrome"],
@@ -126,24 +123,24 @@ pub fn get_active_tab(browser: Option<&str>) -> CallToolResult {
             ),
         };
 
-        match Command::new("osascript").args(["-e", &script]).output() {
+        match Command::new("osascript").args(["-e", &script]).run() {
             Ok(output) => {
                 if !output.status.success() {
                     continue;
                 }
                 let raw = String::from_utf8_lossy(&output.stdout).trim().to_string();
                 let (title, url) = parse_single_tab(&raw);
-                return CallToolResult::json(&json!({
+                return Ok(CallToolResult::json(&json!({
                     "browser": browser_name,
                     "title": title,
                     "url": url
-                }));
+                })));
             }
             Err(_) => continue,
         }
     }
 
-    CallToolResult::error("No supported browser is running (tried Safari, Google Chrome)")
+    Ok(CallToolResult::error("No supported browser is running (tried Safari, Google Chrome)"))
 }
 
 /// Parse the AppleScript tab list output into a JSON array of {title, url} objects.
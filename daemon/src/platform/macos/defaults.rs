@@ -1,5 +1,7 @@
 use std::process::Command;
+use crate::process::RunCommand;
 use serde_json::json;
+use crate::capabilities::ToolResult;
 use crate::mcp::types::CallToolResult;
 
 /// Returns true if the string is safe to pass as a CLI argument
@@ -10,13 +12,13 @@ fn is_safe(s: &str) -> bool {
 }
 
 /// Read a defaults value for a domain and optional key.
-pub fn read_default(domain: &str, key: Option<&str>) -> CallToolResult {
+pub fn read_default(domain: &str, key: Option<&str>) -> ToolResult {
     if !is_safe(domain) {
-        return CallToolResult::error("Domain contains unsafe characters");
+        return Ok(CallToolResult::error("Domain contains unsafe characters"));
     }
     if let Some(k) = key {
         if !is_safe(k) {
-            return CallToolResult::error("Key contains unsafe characters");
+            return Ok(CallToolResult::error("Key contains unsafe characters"));
         }
     }
 
@@ -26,29 +28,32 @@ pub fn read_default(domain: &str, key: Option<&str>) -> CallToolResult {
         cmd.arg(k);
     }
 
-    match cmd.output() {
-        Ok(output) => {
-            if !output.status.success() {
-                let stderr = String::from_utf8_lossy(&output.stderr);
-                return CallToolResult::error(format!("defaults read failed: {stderr}"));
-            }
-            let raw = String::from_utf8_lossy(&output.stdout);
-            CallToolResult::text(raw.trim())
-        }
-        Err(e) => CallToolResult::error(format!("Failed to run defaults: {e}")),
+    let output = cmd.run()?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Ok(CallToolResult::error(format!("defaults read failed: {stderr}")));
     }
+    let raw = String::from_utf8_lossy(&output.stdout);
+    Ok(CallToolResult::text(raw.trim()))
 }
 
-/// Write a defaults value with a given type.
-pub fn write_default(domain: &str, key: &str, value_type: &str, value: &str) -> CallToolResult {
+/// Check that a domain and key are safe to pass to `defaults`.
+fn check_key(domain: &str, key: &str) -> Result<(), String> {
     if !is_safe(domain) {
-        return CallToolResult::error("Domain contains unsafe characters");
+        return Err("Domain contains unsafe characters".into());
     }
     if !is_safe(key) {
-        return CallToolResult::error("Key contains unsafe characters");
+        return Err("Key contains unsafe characters".into());
     }
+    Ok(())
+}
+
+/// Check the arguments of a write, returning the type flag and value it
+/// passes to `defaults write`.
+fn check_write(domain: &str, key: &str, value_type: &str, value: &str) -> Result<(&'static str, String), String> {
+    check_key(domain, key)?;
     if !is_safe(value) {
-        return CallToolResult::error("Value contains unsafe characters");
+        return Err("Value contains unsafe characters".into());
     }
 
     let type_flag = match value_type {
@@ -57,7 +62,7 @@ pub fn write_default(domain: &str, key: &str, value_type: &str, value: &str) ->
         "float" => "-float",
         "bool" => "-bool",
         _ => {
-            return CallToolResult::error(format!(
+            return Err(format!(
                 "Invalid value_type '{value_type}'. Must be one of: string, int, float, bool"
             ));
         }
@@ -68,7 +73,7 @@ pub fn write_default(domain: &str, key: &str, value_type: &str, value: &str) ->
             "true" | "yes" | "1" => "TRUE".to_string(),
             "false" | "no" | "0" => "FALSE".to_string(),
             _ => {
-                return CallToolResult::error(format!(
+                return Err(format!(
                     "Invalid bool value '{value}'. Must be one of: true, false, yes, no, 1, 0"
                 ));
             }
@@ -76,97 +81,112 @@ pub fn write_default(domain: &str, key: &str, value_type: &str, value: &str) ->
     } else {
         value.to_string()
     };
+    Ok((type_flag, normalized_value))
+}
 
-    match Command::new("defaults")
+/// Write a defaults value with a given type.
+pub fn write_default(domain: &str, key: &str, value_type: &str, value: &str) -> ToolResult {
+    let (type_flag, normalized_value) = match check_write(domain, key, value_type, value) {
+        Ok(typed) => typed,
+        Err(e) => return Ok(CallToolResult::error(e)),
+    };
+
+    let output = Command::new("defaults")
         .args(["write", domain, key, type_flag, &normalized_value])
-        .output()
-    {
-        Ok(output) => {
-            if !output.status.success() {
-                let stderr = String::from_utf8_lossy(&output.stderr);
-                return CallToolResult::error(format!("defaults write failed: {stderr}"));
-            }
-            CallToolResult::text(format!("Wrote {domain} {key} = {normalized_value} ({value_type})"))
-        }
-        Err(e) => CallToolResult::error(format!("Failed to run defaults: {e}")),
+        .run()?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Ok(CallToolResult::error(format!("defaults write failed: {stderr}")));
     }
+    Ok(CallToolResult::text(format!("Wrote {domain} {key} = {normalized_value} ({value_type})")))
 }
 
 /// Delete a defaults key from a domain.
-pub fn delete_default(domain: &str, key: &str) -> CallToolResult {
-    if !is_safe(domain) {
-        return CallToolResult::error("Domain contains unsafe characters");
-    }
-    if !is_safe(key) {
-        return CallToolResult::error("Key contains unsafe characters");
+pub fn delete_default(domain: &str, key: &str) -> ToolResult {
+    if let Err(e) = check_key(domain, key) {
+        return Ok(CallToolResult::error(e));
     }
 
-    match Command::new("defaults")
+    let output = Command::new("defaults")
         .args(["delete", domain, key])
-        .output()
-    {
-        Ok(output) => {
-            if !output.status.success() {
-                let stderr = String::from_utf8_lossy(&output.stderr);
-                return CallToolResult::error(format!("defaults delete failed: {stderr}"));
-            }
-            CallToolResult::text(format!("Deleted {domain} {key}"))
-        }
-        Err(e) => CallToolResult::error(format!("Failed to run defaults: {e}")),
+        .run()?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Ok(CallToolResult::error(format!("defaults delete failed: {stderr}")));
     }
+    Ok(CallToolResult::text(format!("Deleted {domain} {key}")))
+}
+
+/// Describe a write (`typed` is the value type and value) or a delete
+/// (`typed` is None) without making it: the arguments are checked as the
+/// change would check them and the key's current value is read.
+pub fn plan_change(domain: &str, key: &str, typed: Option<(&str, &str)>) -> ToolResult {
+    let checked = match typed {
+        Some((value_type, value)) => check_write(domain, key, value_type, value).map(|(_, value)| Some(value)),
+        None => check_key(domain, key).map(|()| None),
+    };
+    let value = match checked {
+        Ok(value) => value,
+        Err(e) => return Ok(CallToolResult::error(e)),
+    };
+    let current = match Command::new("defaults").args(["read", domain, key]).run()? {
+        output if output.status.success() => Some(String::from_utf8_lossy(&output.stdout).trim().to_string()),
+        _ => None,
+    };
+
+    let effect = match (&value, &current) {
+        (Some(value), Some(current)) => format!("Would change {domain} {key} from {current} to {value}"),
+        (Some(value), None) => format!("Would set {domain} {key} (not set now) to {value}"),
+        (None, Some(current)) => format!("Would delete {domain} {key}, now {current}"),
+        (None, None) => return Ok(CallToolResult::error(format!("Can't delete {domain} {key}: it isn't set"))),
+    };
+    crate::capabilities::planned(
+        effect,
+        json!({ "domain": domain, "key": key, "current": current, "value": value }),
+    )
 }
 
 /// List all defaults domains as a JSON array.
-pub fn list_domains() -> CallToolResult {
-    match Command::new("defaults")
+pub fn list_domains() -> ToolResult {
+    let output = Command::new("defaults")
         .arg("domains")
-        .output()
-    {
-        Ok(output) => {
-            if !output.status.success() {
-                let stderr = String::from_utf8_lossy(&output.stderr);
-                return CallToolResult::error(format!("defaults domains failed: {stderr}"));
-            }
-            let raw = String::from_utf8_lossy(&output.stdout);
-            let domains: Vec<&str> = raw
-                .trim()
-                .split(", ")
-                .map(|s| s.trim())
-                .filter(|s| !s.is_empty())
-                .collect();
-            let json_array = json!(domains);
-            CallToolResult::json(&json_array)
-        }
-        Err(e) => CallToolResult::error(format!("Failed to run defaults: {e}")),
+        .run()?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Ok(CallToolResult::error(format!("defaults domains failed: {stderr}")));
     }
+    let raw = String::from_utf8_lossy(&output.stdout);
+    let domains: Vec<&str> = raw
+        .trim()
+        .split(", ")
+        .map(|s| s.trim())
+        .filter(|s| !s.is_empty())
+        .collect();
+    let json_array = json!(domains);
+    Ok(CallToolResult::json(&json_array))
 }
 
 /// Search all defaults for a keyword. Truncates output to 100 lines.
-pub fn find_default(keyword: &str) -> CallToolResult {
+pub fn find_default(keyword: &str) -> ToolResult {
     if !is_safe(keyword) {
-        return CallToolResult::error("Keyword contains unsafe characters");
+        return Ok(CallToolResult::error("Keyword contains unsafe characters"));
     }
 
-    match Command::new("defaults")
+    let output = Command::new("defaults")
         .args(["find", keyword])
-        .output()
-    {
-        Ok(output) => {
-            if !output.status.success() {
-                let stderr = String::from_utf8_lossy(&output.stderr);
-                return CallToolResult::error(format!("defaults find failed: {stderr}"));
-            }
-            let raw = String::from_utf8_lossy(&output.stdout);
-            let lines: Vec<&str> = raw.lines().collect();
-            let truncated = if lines.len() > 100 {
-                let mut result = lines[..100].join("\n");
-                result.push_str(&format!("\n\n... truncated ({} total lines)", lines.len()));
-                result
-            } else {
-                raw.trim().to_string()
-            };
-            CallToolResult::text(truncated)
-        }
-        Err(e) => CallToolResult::error(format!("Failed to run defaults: {e}")),
+        .run()?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Ok(CallToolResult::error(format!("defaults find failed: {stderr}")));
     }
+    let raw = String::from_utf8_lossy(&output.stdout);
+    let lines: Vec<&str> = raw.lines().collect();
+    let truncated = if lines.len() > 100 {
+        let mut result = lines[..100].join("\n");
+        result.push_str(&format!("\n\n... truncated ({} total lines)", lines.len()));
+        result
+    } else {
+        raw.trim().to_string()
+    };
+    Ok(CallToolResult::text(truncated))
 }
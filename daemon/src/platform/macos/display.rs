@@ -1,34 +1,31 @@
 use std::process::Command;
+use crate::process::RunCommand;
 use serde_json::{json, Value};
 
+use crate::capabilities::ToolResult;
 use crate::mcp::types::CallToolResult;
 
 /// Get the current display brightness by parsing ioreg output.
-pub fn get_brightness() -> CallToolResult {
-    match Command::new("ioreg")
+pub fn get_brightness() -> ToolResult {
+    let output = Command::new("ioreg")
         .args(["-rc", "AppleBacklightDisplay"])
-        .output()
-    {
-        Ok(output) => {
-            let stdout = String::from_utf8_lossy(&output.stdout);
+        .run()?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
 
-            // Look for "brightness" = { ... "value" = N ... } pattern
-            // The ioreg output typically has: "brightness" = {"min"=0,"max"=1,"value"=0.5}
-            // or a simpler flat integer form depending on macOS version.
-            if let Some(brightness) = parse_brightness(&stdout) {
-                CallToolResult::json(&json!({
-                    "brightness": brightness,
-                    "source": "ioreg"
-                }))
-            } else {
-                // Fallback: check if there's no backlight display (e.g. external monitor)
-                CallToolResult::error(
-                    "Could not read brightness. This may be an external display \
-                     without backlight control, or the ioreg format is unexpected."
-                )
-            }
-        }
-        Err(e) => CallToolResult::error(format!("Failed to run ioreg: {e}")),
+    // Look for "brightness" = { ... "value" = N ... } pattern
+    // The ioreg output typically has: "brightness" = {"min"=0,"max"=1,"value"=0.5}
+    // or a simpler flat integer form depending on macOS version.
+    if let Some(brightness) = parse_brightness(&stdout) {
+        Ok(CallToolResult::json(&json!({
+            "brightness": brightness,
+            "source": "ioreg"
+        })))
+    } else {
+        // Fallback: check if there's no backlight display (e.g. external monitor)
+        Ok(CallToolResult::error(
+            "Could not read brightness. This may be an external display \
+             without backlight control, or the ioreg format is unexpected."
+        ))
     }
 }
 
@@ -69,53 +66,51 @@ fn parse_brightness(output: &str) -> Option<f64> {
 }
 
 /// Get display information via system_profiler.
-pub fn get_info() -> CallToolResult {
+pub fn get_info() -> ToolResult {
     match Command::new("system_profiler")
         .args(["SPDisplaysDataType", "-json"])
-        .output()
+        .run()?
     {
-        Ok(output) if output.status.success() => {
+        output if output.status.success() => {
             let stdout = String::from_utf8_lossy(&output.stdout);
             match serde_json::from_str::<Value>(&stdout) {
-                Ok(parsed) => CallToolResult::json(&parsed),
-                Err(_) => CallToolResult::text(stdout),
+                Ok(parsed) => Ok(CallToolResult::json(&parsed)),
+                Err(_) => Ok(CallToolResult::text(stdout)),
             }
         }
-        Ok(output) => {
+        output => {
             let stderr = String::from_utf8_lossy(&output.stderr);
-            CallToolResult::error(format!("system_profiler failed: {stderr}"))
+            Ok(CallToolResult::error(format!("system_profiler failed: {stderr}")))
         }
-        Err(e) => CallToolResult::error(format!("Failed to run system_profiler: {e}")),
     }
 }
 
 /// Get the current dark mode setting.
-pub fn get_dark_mode() -> CallToolResult {
+pub fn get_dark_mode() -> ToolResult {
     match Command::new("defaults")
         .args(["read", "-g", "AppleInterfaceStyle"])
-        .output()
+        .run()?
     {
-        Ok(output) if output.status.success() => {
+        output if output.status.success() => {
             let stdout = String::from_utf8_lossy(&output.stdout).trim().to_string();
             let is_dark = stdout == "Dark";
-            CallToolResult::json(&json!({
+            Ok(CallToolResult::json(&json!({
                 "dark_mode": is_dark,
                 "style": if is_dark { "dark" } else { "light" }
-            }))
+            })))
         }
-        Ok(_) => {
+        _ => {
             // Non-zero exit means the key doesn't exist = light mode
-            CallToolResult::json(&json!({
+            Ok(CallToolResult::json(&json!({
                 "dark_mode": false,
                 "style": "light"
-            }))
+            })))
         }
-        Err(e) => CallToolResult::error(format!("Failed to read dark mode setting: {e}")),
     }
 }
 
 /// Set the dark mode on or off via osascript.
-pub fn set_dark_mode(enabled: bool) -> CallToolResult {
+pub fn set_dark_mode(enabled: bool) -> ToolResult {
     let script = format!(
         "tell application \"System Events\" to tell appearance preferences to set dark mode to {}",
         if enabled { "true" } else { "false" }
@@ -123,18 +118,17 @@ pub fn set_dark_mode(enabled: bool) -> CallToolResult {
 
     match Command::new("osascript")
         .args(["-e", &script])
-        .output()
+        .run()?
     {
-        Ok(output) if output.status.success() => {
-            CallToolResult::text(format!(
+        output if output.status.success() => {
+            Ok(CallToolResult::text(format!(
                 "Dark mode {}",
                 if enabled { "enabled" } else { "disabled" }
-            ))
+            )))
         }
-        Ok(output) => {
+        output => {
             let stderr = String::from_utf8_lossy(&output.stderr);
-            CallToolResult::error(format!("Failed to set dark mode: {stderr}"))
+            Ok(CallToolResult::error(format!("Failed to set dark mode: {stderr}")))
         }
-        Err(e) => CallToolResult::error(format!("Failed to run osascript: {e}")),
     }
 }
@@ -1,7 +1,9 @@
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::process::Command;
+use crate::process::RunCommand;
 use serde_json::{json, Value};
+use crate::capabilities::{planned, Progress, ToolResult};
 use crate::mcp::types::CallToolResult;
 
 /// List files in a directory.
@@ -56,10 +58,10 @@ pub fn mkdir(path: &str) -> CallToolResult {
 }
 
 /// Move/rename a file or directory.
-pub fn move_file(source: &str, destination: &str) -> CallToolResult {
+pub fn move_file(source: &str, destination: &str) -> ToolResult {
     let src = Path::new(source);
     if !src.exists() {
-        return CallToolResult::error(format!("Source does not exist: {source}"));
+        return Ok(CallToolResult::error(format!("Source does not exist: {source}")));
     }
 
     let dst = Path::new(destination);
@@ -71,78 +73,95 @@ pub fn move_file(source: &str, destination: &str) -> CallToolResult {
     };
 
     match fs::rename(src, &final_dst) {
-        Ok(()) => CallToolResult::text(format!(
+        Ok(()) => Ok(CallToolResult::text(format!(
             "Moved {} → {}",
             source,
             final_dst.to_string_lossy()
-        )),
+        ))),
         Err(_) => {
             // rename fails across mount points, fall back to cp + rm
             match Command::new("mv")
                 .args([source, &final_dst.to_string_lossy()])
-                .output()
+                .run()?
             {
-                Ok(output) if output.status.success() => CallToolResult::text(format!(
+                output if output.status.success() => Ok(CallToolResult::text(format!(
                     "Moved {} → {}",
                     source,
                     final_dst.to_string_lossy()
-                )),
-                Ok(output) => {
+                ))),
+                output => {
                     let stderr = String::from_utf8_lossy(&output.stderr);
-                    CallToolResult::error(format!("mv failed: {stderr}"))
+                    Ok(CallToolResult::error(format!("mv failed: {stderr}")))
                 }
-                Err(e) => CallToolResult::error(format!("Failed to move: {e}")),
             }
         }
     }
 }
 
-/// Copy a file or directory.
-pub fn copy_file(source: &str, destination: &str) -> CallToolResult {
+/// Copy a file or directory. Directory copies report progress per entry.
+pub fn copy_file(source: &str, destination: &str, progress: &Progress) -> ToolResult {
     let src = Path::new(source);
     if !src.exists() {
-        return CallToolResult::error(format!("Source does not exist: {source}"));
+        return Ok(CallToolResult::error(format!("Source does not exist: {source}")));
     }
 
-    let flag = if src.is_dir() { "-R" } else { "" };
-    let mut args = vec![];
-    if !flag.is_empty() {
-        args.push(flag);
-    }
-    args.push(source);
-    args.push(destination);
+    let output = if src.is_dir() {
+        // `cp -v` prints one line per copied entry, the top directory included
+        let total = 1 + count_entries(src);
+        let step = (total / 100).max(1);
+        let mut copied = 0;
+        Command::new("cp")
+            .args(["-Rv", source, destination])
+            .run_streaming(|line| {
+                copied += 1;
+                if copied <= total && (copied % step == 0 || copied == total) {
+                    progress.report(copied, Some(total), line);
+                }
+            })?
+    } else {
+        Command::new("cp").args([source, destination]).run()?
+    };
 
-    match Command::new("cp").args(&args).output() {
-        Ok(output) if output.status.success() => {
-            CallToolResult::text(format!("Copied {source} → {destination}"))
-        }
-        Ok(output) => {
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            CallToolResult::error(format!("cp failed: {stderr}"))
-        }
-        Err(e) => CallToolResult::error(format!("Failed to copy: {e}")),
+    if output.status.success() {
+        Ok(CallToolResult::text(format!("Copied {source} → {destination}")))
+    } else {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        Ok(CallToolResult::error(format!("cp failed: {stderr}")))
     }
 }
 
+/// Number of entries below `dir`, recursively (symlinks are not followed).
+fn count_entries(dir: &Path) -> u64 {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return 0;
+    };
+    entries
+        .flatten()
+        .map(|entry| match entry.file_type() {
+            Ok(t) if t.is_dir() => 1 + count_entries(&entry.path()),
+            _ => 1,
+        })
+        .sum()
+}
+
 /// Reveal a file in Finder.
-pub fn reveal_in_finder(path: &str) -> CallToolResult {
-    match Command::new("open").args(["-R", path]).output() {
-        Ok(output) if output.status.success() => {
-            CallToolResult::text(format!("Revealed in Finder: {path}"))
+pub fn reveal_in_finder(path: &str) -> ToolResult {
+    match Command::new("open").args(["-R", path]).run()? {
+        output if output.status.success() => {
+            Ok(CallToolResult::text(format!("Revealed in Finder: {path}")))
         }
-        Ok(output) => {
+        output => {
             let stderr = String::from_utf8_lossy(&output.stderr);
-            CallToolResult::error(format!("Failed to reveal: {stderr}"))
+            Ok(CallToolResult::error(format!("Failed to reveal: {stderr}")))
         }
-        Err(e) => CallToolResult::error(format!("Failed to reveal: {e}")),
     }
 }
 
 /// Trash a file (move to macOS Trash instead of permanent delete).
-pub fn trash(path: &str) -> CallToolResult {
+pub fn trash(path: &str) -> ToolResult {
     let p = Path::new(path);
     if !p.exists() {
-        return CallToolResult::error(format!("Path does not exist: {path}"));
+        return Ok(CallToolResult::error(format!("Path does not exist: {path}")));
     }
 
     // Use Finder's "move to trash" via osascript for safety
@@ -151,14 +170,91 @@ pub fn trash(path: &str) -> CallToolResult {
         path.replace('"', "'")
     );
 
-    match Command::new("osascript").args(["-e", &script]).output() {
-        Ok(output) if output.status.success() => {
-            CallToolResult::text(format!("Moved to Trash: {path}"))
+    match Command::new("osascript").args(["-e", &script]).run()? {
+        output if output.status.success() => {
+            Ok(CallToolResult::text(format!("Moved to Trash: {path}")))
         }
-        Ok(output) => {
+        output => {
             let stderr = String::from_utf8_lossy(&output.stderr);
-            CallToolResult::error(format!("Failed to trash: {stderr}"))
+            Ok(CallToolResult::error(format!("Failed to trash: {stderr}")))
         }
-        Err(e) => CallToolResult::error(format!("Failed to trash: {e}")),
     }
 }
+
+// ── Dry runs ────────────────────────────────────────────────────────────
+
+/// What is at `path` now, or null if nothing is.
+fn describe(path: &Path) -> Value {
+    let shown = path.to_string_lossy();
+    match fs::symlink_metadata(path) {
+        Ok(m) if m.is_dir() => json!({ "path": shown, "type": "directory", "entries": count_entries(path) }),
+        Ok(m) if m.file_type().is_symlink() => json!({ "path": shown, "type": "symlink" }),
+        Ok(m) => json!({ "path": shown, "type": "file", "size": m.len() }),
+        Err(_) => Value::Null,
+    }
+}
+
+/// How a description of `path` reads in a sentence.
+fn summary(description: &Value) -> String {
+    match description["type"].as_str() {
+        Some("directory") => format!("directory of {} entries", description["entries"]),
+        Some("file") => format!("file of {} bytes", description["size"]),
+        Some(other) => other.to_string(),
+        None => "nothing".to_string(),
+    }
+}
+
+/// Where a move or copy of `source` to `destination` ends up: inside
+/// `destination` when it is a directory, as the tools do.
+fn final_destination(source: &Path, destination: &str) -> PathBuf {
+    let dst = Path::new(destination);
+    if dst.is_dir() {
+        dst.join(source.file_name().unwrap_or_default())
+    } else {
+        dst.to_path_buf()
+    }
+}
+
+/// Describe `mkdir` without creating anything.
+pub fn plan_mkdir(path: &str) -> ToolResult {
+    let dir = Path::new(path);
+    if dir.is_dir() {
+        return planned(format!("{path} already exists; nothing would change"), describe(dir));
+    }
+    if dir.exists() {
+        return Ok(CallToolResult::error(format!(
+            "Failed to create directory: {path} exists and is not a directory"
+        )));
+    }
+    let missing = dir.ancestors().take_while(|d| !d.as_os_str().is_empty() && !d.exists()).count();
+    let effect = match missing {
+        1 => format!("Would create directory {path}"),
+        n => format!("Would create directory {path} and {} missing parent directories", n - 1),
+    };
+    planned(effect, json!({ "path": path, "created": missing }))
+}
+
+/// Describe `move_file` or `copy_file` without touching anything.
+pub fn plan_transfer(verb: &str, source: &str, destination: &str) -> ToolResult {
+    let src = Path::new(source);
+    if !src.exists() {
+        return Ok(CallToolResult::error(format!("Source does not exist: {source}")));
+    }
+    let final_dst = final_destination(src, destination);
+    let (from, to) = (describe(src), describe(&final_dst));
+    let mut effect = format!("Would {verb} {source} ({}) → {}", summary(&from), final_dst.to_string_lossy());
+    if !to.is_null() {
+        effect.push_str(&format!(", replacing the {} there", summary(&to)));
+    }
+    planned(effect, json!({ "source": from, "destination": final_dst.to_string_lossy(), "replaces": to }))
+}
+
+/// Describe `trash` without moving anything.
+pub fn plan_trash(path: &str) -> ToolResult {
+    let p = Path::new(path);
+    if !p.exists() {
+        return Ok(CallToolResult::error(format!("Path does not exist: {path}")));
+    }
+    let target = describe(p);
+    planned(format!("Would move {path} ({}) to the Trash", summary(&target)), target)
+}
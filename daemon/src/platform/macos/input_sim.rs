@@ -1,4 +1,5 @@
 use std::process::Command;
+use crate::process::RunCommand;
 use std::thread;
 use std::time::Duration;
 
@@ -7,97 +8,17 @@ use core_graphics::event::{
 };
 use core_graphics::event_source::{CGEventSource, CGEventSourceStateID};
 use core_graphics::geometry::CGPoint;
+use serde_json::{json, Value};
 
+use crate::capabilities::{planned, ToolResult};
 use crate::mcp::types::CallToolResult;
-use crate::platform::macos::ax_helpers;
+use crate::platform::macos::cg_helpers;
 
 // ── Keycode Lookup ─────────────────────────────────────────────────────────
 
 /// Map a human-readable key name to a macOS virtual keycode.
 pub fn keycode_for_name(name: &str) -> Option<CGKeyCode> {
-    let lower = name.to_lowercase();
-    match lower.as_str() {
-        // Letters
-        "a" => Some(0x00),
-        "b" => Some(0x0B),
-        "c" => Some(0x08),
-        "d" => Some(0x02),
-        "e" => Some(0x0E),
-        "f" => Some(0x03),
-        "g" => Some(0x05),
-        "h" => Some(0x04),
-        "i" => Some(0x22),
-        "j" => Some(0x26),
-        "k" => Some(0x28),
-        "l" => Some(0x25),
-        "m" => Some(0x2E),
-        "n" => Some(0x2D),
-        "o" => Some(0x1F),
-        "p" => Some(0x23),
-        "q" => Some(0x0C),
-        "r" => Some(0x0F),
-        "s" => Some(0x01),
-        "t" => Some(0x11),
-        "u" => Some(0x20),
-        "v" => Some(0x09),
-        "w" => Some(0x0D),
-        "x" => Some(0x07),
-        "y" => Some(0x10),
-        "z" => Some(0x06),
-        // Numbers
-        "0" => Some(0x1D),
-        "1" => Some(0x12),
-        "2" => Some(0x13),
-        "3" => Some(0x14),
-        "4" => Some(0x15),
-        "5" => Some(0x17),
-        "6" => Some(0x16),
-        "7" => Some(0x1A),
-        "8" => Some(0x1C),
-        "9" => Some(0x19),
-        // Special keys
-        "return" | "enter" => Some(0x24),
-        "tab" => Some(0x30),
-        "space" => Some(0x31),
-        "delete" | "backspace" => Some(0x33),
-        "escape" | "esc" => Some(0x35),
-        "forward_delete" | "forwarddelete" => Some(0x75),
-        "home" => Some(0x73),
-        "end" => Some(0x77),
-        "pageup" | "page_up" => Some(0x74),
-        "pagedown" | "page_down" => Some(0x79),
-        // Arrow keys
-        "left" => Some(0x7B),
-        "right" => Some(0x7C),
-        "down" => Some(0x7D),
-        "up" => Some(0x7E),
-        // Function keys
-        "f1" => Some(0x7A),
-        "f2" => Some(0x78),
-        "f3" => Some(0x63),
-        "f4" => Some(0x76),
-        "f5" => Some(0x60),
-        "f6" => Some(0x61),
-        "f7" => Some(0x62),
-        "f8" => Some(0x64),
-        "f9" => Some(0x65),
-        "f10" => Some(0x6D),
-        "f11" => Some(0x67),
-        "f12" => Some(0x6F),
-        // Punctuation
-        "apostrophe" | "quote" | "'" => Some(0x27),
-        "backslash" | "\\" => Some(0x2A),
-        "comma" | "," => Some(0x2B),
-        "equals" | "equal" | "=" => Some(0x18),
-        "grave" | "backtick" | "`" => Some(0x32),
-        "left_bracket" | "leftbracket" | "[" => Some(0x21),
-        "minus" | "-" => Some(0x1B),
-        "period" | "." => Some(0x2F),
-        "right_bracket" | "rightbracket" | "]" => Some(0x1E),
-        "semicolon" | ";" => Some(0x29),
-        "slash" | "/" => Some(0x2C),
-        _ => None,
-    }
+    crate::platform::keys::keycode(name)
 }
 
 /// Parse modifier names to CGEventFlags.
@@ -120,10 +41,6 @@ fn modifier_flags(modifiers: &[String]) -> CGEventFlags {
 
 /// Simulate a key press with optional modifiers.
 pub fn key_press(key: &str, modifiers: &[String]) -> CallToolResult {
-    if let Err(e) = ax_helpers::ensure_trusted() {
-        return CallToolResult::error(e);
-    }
-
     let keycode = match keycode_for_name(key) {
         Some(kc) => kc,
         None => return CallToolResult::error(format!("Unknown key name: '{key}'")),
@@ -167,13 +84,9 @@ pub fn key_press(key: &str, modifiers: &[String]) -> CallToolResult {
 // ── Type Text ──────────────────────────────────────────────────────────────
 
 /// Type a string of text using osascript for Unicode support.
-pub fn type_text(text: &str, delay_ms: u64) -> CallToolResult {
-    if let Err(e) = ax_helpers::ensure_trusted() {
-        return CallToolResult::error(e);
-    }
-
+pub fn type_text(text: &str, delay_ms: u64) -> ToolResult {
     if text.is_empty() {
-        return CallToolResult::text("No text to type");
+        return Ok(CallToolResult::text("No text to type"));
     }
 
     // For longer text or when delay is requested, chunk by line to allow delays
@@ -184,13 +97,10 @@ pub fn type_text(text: &str, delay_ms: u64) -> CallToolResult {
                 "tell application \"System Events\" to keystroke \"{}\"",
                 sanitized
             );
-            match Command::new("osascript").args(["-e", &script]).output() {
-                Ok(output) if output.status.success() => {}
-                Ok(output) => {
-                    let stderr = String::from_utf8_lossy(&output.stderr);
-                    return CallToolResult::error(format!("osascript failed: {stderr}"));
-                }
-                Err(e) => return CallToolResult::error(format!("Failed to run osascript: {e}")),
+            let output = Command::new("osascript").args(["-e", &script]).run()?;
+            if !output.status.success() {
+                let stderr = String::from_utf8_lossy(&output.stderr);
+                return Ok(CallToolResult::error(format!("osascript failed: {stderr}")));
             }
             if delay_ms > 0 {
                 thread::sleep(Duration::from_millis(delay_ms));
@@ -202,17 +112,14 @@ pub fn type_text(text: &str, delay_ms: u64) -> CallToolResult {
             "tell application \"System Events\" to keystroke \"{}\"",
             sanitized
         );
-        match Command::new("osascript").args(["-e", &script]).output() {
-            Ok(output) if output.status.success() => {}
-            Ok(output) => {
-                let stderr = String::from_utf8_lossy(&output.stderr);
-                return CallToolResult::error(format!("osascript failed: {stderr}"));
-            }
-            Err(e) => return CallToolResult::error(format!("Failed to run osascript: {e}")),
+        let output = Command::new("osascript").args(["-e", &script]).run()?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Ok(CallToolResult::error(format!("osascript failed: {stderr}")));
         }
     }
 
-    CallToolResult::text(format!("Typed {} characters", text.len()))
+    Ok(CallToolResult::text(format!("Typed {} characters", text.len())))
 }
 
 /// Escape text for embedding in an AppleScript string literal.
@@ -224,10 +131,6 @@ fn sanitize_for_applescript(text: &str) -> String {
 
 /// Move the mouse cursor to (x, y).
 pub fn mouse_move(x: f64, y: f64) -> CallToolResult {
-    if let Err(e) = ax_helpers::ensure_trusted() {
-        return CallToolResult::error(e);
-    }
-
     let source = match CGEventSource::new(CGEventSourceStateID::HIDSystemState) {
         Ok(s) => s,
         Err(_) => return CallToolResult::error("Failed to create CGEventSource"),
@@ -247,10 +150,6 @@ pub fn mouse_move(x: f64, y: f64) -> CallToolResult {
 
 /// Click at (x, y) with the given button, N times.
 pub fn mouse_click(x: f64, y: f64, button: &str, clicks: u32) -> CallToolResult {
-    if let Err(e) = ax_helpers::ensure_trusted() {
-        return CallToolResult::error(e);
-    }
-
     let (down_type, up_type, cg_button) = match button.to_lowercase().as_str() {
         "left" => (CGEventType::LeftMouseDown, CGEventType::LeftMouseUp, CGMouseButton::Left),
         "right" => (CGEventType::RightMouseDown, CGEventType::RightMouseUp, CGMouseButton::Right),
@@ -292,10 +191,6 @@ pub fn mouse_click(x: f64, y: f64, button: &str, clicks: u32) -> CallToolResult
 
 /// Scroll at (x, y) by delta_y (vertical) and delta_x (horizontal).
 pub fn scroll(x: f64, y: f64, delta_y: i32, delta_x: i32) -> CallToolResult {
-    if let Err(e) = ax_helpers::ensure_trusted() {
-        return CallToolResult::error(e);
-    }
-
     // First move the mouse to the scroll position
     let source = match CGEventSource::new(CGEventSourceStateID::HIDSystemState) {
         Ok(s) => s,
@@ -350,10 +245,6 @@ unsafe extern "C" {
 
 /// Drag from (from_x, from_y) to (to_x, to_y) over duration_ms.
 pub fn drag(from_x: f64, from_y: f64, to_x: f64, to_y: f64, button: &str, duration_ms: u64) -> CallToolResult {
-    if let Err(e) = ax_helpers::ensure_trusted() {
-        return CallToolResult::error(e);
-    }
-
     let (down_type, drag_type, up_type, cg_button) = match button.to_lowercase().as_str() {
         "left" => (
             CGEventType::LeftMouseDown,
@@ -423,10 +314,6 @@ pub fn drag(from_x: f64, from_y: f64, to_x: f64, to_y: f64, button: &str, durati
 
 /// Press a keyboard shortcut from a combo string like "cmd+shift+s".
 pub fn hotkey(combo: &str) -> CallToolResult {
-    if let Err(e) = ax_helpers::ensure_trusted() {
-        return CallToolResult::error(e);
-    }
-
     let parts: Vec<&str> = combo.split('+').map(|s| s.trim()).collect();
     if parts.is_empty() {
         return CallToolResult::error("Empty hotkey combo");
@@ -440,3 +327,49 @@ pub fn hotkey(combo: &str) -> CallToolResult {
     // treat it as a lone key press
     key_press(key_name, &modifier_names)
 }
+
+// ── Dry Runs ────────────────────────────────────────────────────────────
+
+/// Describe key events without posting them. They reach the frontmost
+/// app, taken to be the owner of the frontmost normal window.
+pub fn plan_keys(key: Option<&str>, action: &str) -> ToolResult {
+    if let Some(key) = key
+        && keycode_for_name(key).is_none()
+    {
+        return Ok(CallToolResult::error(format!("Unknown key name: '{key}'")));
+    }
+    let front = cg_helpers::list_windows().into_iter().find(|w| w.layer == 0);
+    let effect = match &front {
+        Some(w) => format!("Would {action} in {} (pid {})", w.owner_name, w.pid),
+        None => format!("Would {action}, with no app window open to receive it"),
+    };
+    planned(effect, front.map_or(Value::Null, |w| json!(w)))
+}
+
+/// Describe mouse events at `points` without posting them. Each point
+/// reaches the frontmost window under it.
+pub fn plan_pointer(points: &[(f64, f64)], action: &str) -> ToolResult {
+    let windows = cg_helpers::list_windows();
+    let targets: Vec<Value> = points
+        .iter()
+        .map(|&(x, y)| {
+            windows
+                .iter()
+                .find(|w| x >= w.x && x < w.x + w.width && y >= w.y && y < w.y + w.height)
+                .map_or(Value::Null, |w| json!(w))
+        })
+        .collect();
+    let over: Vec<String> = targets
+        .iter()
+        .map(|t| match t["owner_name"].as_str() {
+            Some(owner) => format!("window {} ('{}' of {owner})", t["id"], t["title"].as_str().unwrap_or("")),
+            None => "the desktop".to_string(),
+        })
+        .collect();
+    let effect = format!("Would {action}, over {}", over.join(" then "));
+    let target = match targets.as_slice() {
+        [target] => target.clone(),
+        _ => Value::Array(targets),
+    };
+    planned(effect, target)
+}
@@ -1,5 +1,7 @@
 use std::process::Command;
+use crate::process::RunCommand;
 use serde_json::json;
+use crate::capabilities::{Progress, ToolResult};
 use crate::mcp::types::CallToolResult;
 
 /// Get network information: hostname, local IPs, public IP, gateway, DNS.
@@ -7,7 +9,7 @@ pub fn get_info() -> CallToolResult {
     let mut info = json!({});
 
     // Hostname
-    match Command::new("hostname").output() {
+    match Command::new("hostname").run() {
         Ok(output) if output.status.success() => {
             info["hostname"] = json!(String::from_utf8_lossy(&output.stdout).trim().to_string());
         }
@@ -18,7 +20,7 @@ pub fn get_info() -> CallToolResult {
 
     // Local IPs from ifconfig
     let mut local_ips: Vec<String> = Vec::new();
-    if let Ok(output) = Command::new("ifconfig").output() {
+    if let Ok(output) = Command::new("ifconfig").run() {
         if output.status.success() {
             let raw = String::from_utf8_lossy(&output.stdout);
             for line in raw.lines() {
@@ -37,7 +39,7 @@ pub fn get_info() -> CallToolResult {
     // Public IP via curl ifconfig.me (short timeout)
     match Command::new("curl")
         .args(["-s", "--max-time", "5", "https://ifconfig.me"])
-        .output()
+        .run()
     {
         Ok(output) if output.status.success() => {
             let ip = String::from_utf8_lossy(&output.stdout).trim().to_string();
@@ -53,7 +55,7 @@ pub fn get_info() -> CallToolResult {
     }
 
     // Default gateway via `route get default`
-    match Command::new("route").args(["get", "default"]).output() {
+    match Command::new("route").args(["get", "default"]).run() {
         Ok(output) if output.status.success() => {
             let raw = String::from_utf8_lossy(&output.stdout);
             let mut gateway = None;
@@ -72,7 +74,7 @@ pub fn get_info() -> CallToolResult {
     }
 
     // DNS servers via scutil
-    match Command::new("scutil").args(["--dns"]).output() {
+    match Command::new("scutil").args(["--dns"]).run() {
         Ok(output) if output.status.success() => {
             let raw = String::from_utf8_lossy(&output.stdout);
             let mut dns_servers: Vec<String> = Vec::new();
@@ -100,21 +102,21 @@ pub fn get_info() -> CallToolResult {
 }
 
 /// Get current WiFi connection details via system_profiler and networksetup.
-pub fn get_wifi() -> CallToolResult {
+pub fn get_wifi() -> ToolResult {
     let interface = get_wifi_interface().unwrap_or_else(|| "en0".into());
 
     // Use system_profiler as primary source (networksetup redacts SSID on macOS 15+)
     let output = match Command::new("system_profiler")
         .args(["SPAirPortDataType"])
-        .output()
+        .run()?
     {
-        Ok(o) if o.status.success() => String::from_utf8_lossy(&o.stdout).to_string(),
-        _ => return CallToolResult::error("Failed to query WiFi via system_profiler"),
+        o if o.status.success() => String::from_utf8_lossy(&o.stdout).to_string(),
+        _ => return Ok(CallToolResult::error("Failed to query WiFi via system_profiler")),
     };
 
     // Check connection status
     if !output.contains("Status: Connected") {
-        return CallToolResult::text("WiFi is off or not connected to any network.");
+        return Ok(CallToolResult::text("WiFi is off or not connected to any network."));
     }
 
     let mut info = json!({ "interface": interface, "status": "connected" });
@@ -189,14 +191,14 @@ pub fn get_wifi() -> CallToolResult {
         }
     }
 
-    CallToolResult::json(&info)
+    Ok(CallToolResult::json(&info))
 }
 
 /// Find the WiFi interface name via networksetup.
 fn get_wifi_interface() -> Option<String> {
     let output = Command::new("networksetup")
         .args(["-listallhardwareports"])
-        .output()
+        .run()
         .ok()?;
     if !output.status.success() {
         return None;
@@ -218,149 +220,98 @@ fn get_wifi_interface() -> Option<String> {
 }
 
 /// Ping a host with a given count. Validates host to prevent command injection.
-pub fn ping(host: &str, count: u32) -> CallToolResult {
+/// Reports progress once per packet sent.
+pub fn ping(host: &str, count: u32, progress: &Progress) -> ToolResult {
     let host = host.trim();
 
     if host.is_empty() {
-        return CallToolResult::error("Host parameter is required.");
+        return Ok(CallToolResult::error("Host parameter is required."));
     }
 
     // Command injection prevention: reject dangerous characters
     const FORBIDDEN: &[char] = &[';', '|', '&', '$', '`', '\n', '\r'];
     if host.chars().any(|c| FORBIDDEN.contains(&c)) {
-        return CallToolResult::error(
+        return Ok(CallToolResult::error(
             "Invalid host: contains forbidden characters (; | & $ ` or newlines).",
-        );
+        ));
     }
 
     let count = count.max(1).min(100);
     let count_str = count.to_string();
 
-    match Command::new("ping")
+    let mut packets = 0;
+    let output = Command::new("ping")
         .args(["-c", &count_str, "-t", "5", host])
-        .output()
-    {
-        Ok(output) => {
-            let stdout = String::from_utf8_lossy(&output.stdout);
-            let stderr = String::from_utf8_lossy(&output.stderr);
-
-            if !output.status.success() && stdout.trim().is_empty() {
-                return CallToolResult::error(format!(
-                    "Ping failed: {}",
-                    if stderr.trim().is_empty() {
-                        "unknown error"
-                    } else {
-                        stderr.trim()
-                    }
-                ));
+        .run_streaming(|line| {
+            // One line per packet: a reply ("64 bytes from …") or a timeout
+            if line.contains("icmp_seq") {
+                packets += 1;
+                progress.report(packets, Some(count as u64), line);
             }
+        })?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
 
-            // Return full output (stdout may contain partial results even on failure)
-            let mut result = stdout.to_string();
-            if !stderr.trim().is_empty() {
-                result.push_str(&format!("\n{stderr}"));
+    if !output.status.success() && stdout.trim().is_empty() {
+        return Ok(CallToolResult::error(format!(
+            "Ping failed: {}",
+            if stderr.trim().is_empty() {
+                "unknown error"
+            } else {
+                stderr.trim()
             }
-            CallToolResult::text(result.trim())
-        }
-        Err(e) => CallToolResult::error(format!("Failed to run ping: {e}")),
+        )));
     }
+
+    // Return full output (stdout may contain partial results even on failure)
+    let mut result = stdout.to_string();
+    if !stderr.trim().is_empty() {
+        result.push_str(&format!("\n{stderr}"));
+    }
+    Ok(CallToolResult::text(result.trim()))
 }
 
 /// List all network interfaces with their IPs, MAC addresses, and status.
-pub fn get_interfaces() -> CallToolResult {
-    match Command::new("ifconfig").output() {
-        Ok(output) => {
-            if !output.status.success() {
-                let stderr = String::from_utf8_lossy(&output.stderr);
-                return CallToolResult::error(format!("ifconfig failed: {stderr}"));
-            }
-
-            let raw = String::from_utf8_lossy(&output.stdout);
-            let mut interfaces: Vec<serde_json::Value> = Vec::new();
-            let mut current_name: Option<String> = None;
-            let mut current_flags = String::new();
-            let mut current_is_up = false;
-            let mut current_inet: Vec<String> = Vec::new();
-            let mut current_inet6: Vec<String> = Vec::new();
-            let mut current_mac: Option<String> = None;
-
-            let flush = |name: &Option<String>,
-                         flags: &str,
-                         is_up: bool,
-                         inet: &[String],
-                         inet6: &[String],
-                         mac: &Option<String>,
-                         interfaces: &mut Vec<serde_json::Value>| {
-                if let Some(iface_name) = name {
-                    interfaces.push(json!({
-                        "name": iface_name,
-                        "flags": flags,
-                        "up": is_up,
-                        "inet": inet,
-                        "inet6": inet6,
-                        "mac": mac,
-                    }));
-                }
-            };
-
-            for line in raw.lines() {
-                // Interface header line: "en0: flags=8863<UP,...> mtu 1500"
-                if !line.starts_with('\t') && !line.starts_with(' ') && line.contains(": flags=")
-                {
-                    // Flush previous interface
-                    flush(
-                        &current_name,
-                        &current_flags,
-                        current_is_up,
-                        &current_inet,
-                        &current_inet6,
-                        &current_mac,
-                        &mut interfaces,
-                    );
-
-                    let iface_name = line.split(':').next().unwrap_or("").to_string();
-                    current_name = Some(iface_name);
+pub fn get_interfaces() -> ToolResult {
+    let output = Command::new("ifconfig").run()?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Ok(CallToolResult::error(format!("ifconfig failed: {stderr}")));
+    }
 
-                    // Extract flags string
-                    if let Some(flags_start) = line.find("flags=") {
-                        let flags_rest = &line[flags_start..];
-                        if let Some(end) = flags_rest.find(' ') {
-                            current_flags = flags_rest[..end].to_string();
-                        } else {
-                            current_flags = flags_rest.to_string();
-                        }
-                    } else {
-                        current_flags = String::new();
-                    }
+    let raw = String::from_utf8_lossy(&output.stdout);
+    let mut interfaces: Vec<serde_json::Value> = Vec::new();
+    let mut current_name: Option<String> = None;
+    let mut current_flags = String::new();
+    let mut current_is_up = false;
+    let mut current_inet: Vec<String> = Vec::new();
+    let mut current_inet6: Vec<String> = Vec::new();
+    let mut current_mac: Option<String> = None;
 
-                    current_is_up = line.contains("<UP") || line.contains(",UP,") || line.contains(",UP>");
-                    current_inet = Vec::new();
-                    current_inet6 = Vec::new();
-                    current_mac = None;
-                } else {
-                    let trimmed = line.trim();
-                    if let Some(rest) = trimmed.strip_prefix("inet ") {
-                        let ip = rest.split_whitespace().next().unwrap_or("");
-                        if !ip.is_empty() {
-                            current_inet.push(ip.to_string());
-                        }
-                    } else if let Some(rest) = trimmed.strip_prefix("inet6 ") {
-                        let ip = rest.split_whitespace().next().unwrap_or("");
-                        // Strip %scope_id suffix for cleaner display
-                        let ip = ip.split('%').next().unwrap_or(ip);
-                        if !ip.is_empty() {
-                            current_inet6.push(ip.to_string());
-                        }
-                    } else if let Some(rest) = trimmed.strip_prefix("ether ") {
-                        let mac = rest.split_whitespace().next().unwrap_or("");
-                        if !mac.is_empty() {
-                            current_mac = Some(mac.to_string());
-                        }
-                    }
-                }
-            }
+    let flush = |name: &Option<String>,
+                 flags: &str,
+                 is_up: bool,
+                 inet: &[String],
+                 inet6: &[String],
+                 mac: &Option<String>,
+                 interfaces: &mut Vec<serde_json::Value>| {
+        if let Some(iface_name) = name {
+            interfaces.push(json!({
+                "name": iface_name,
+                "flags": flags,
+                "up": is_up,
+                "inet": inet,
+                "inet6": inet6,
+                "mac": mac,
+            }));
+        }
+    };
 
-            // Flush last interface
+    for line in raw.lines() {
+        // Interface header line: "en0: flags=8863<UP,...> mtu 1500"
+        if !line.starts_with('\t') && !line.starts_with(' ') && line.contains(": flags=")
+        {
+            // Flush previous interface
             flush(
                 &current_name,
                 &current_flags,
@@ -371,8 +322,58 @@ pub fn get_interfaces() -> CallToolResult {
                 &mut interfaces,
             );
 
-            CallToolResult::json(&json!(interfaces))
+            let iface_name = line.split(':').next().unwrap_or("").to_string();
+            current_name = Some(iface_name);
+
+            // Extract flags string
+            if let Some(flags_start) = line.find("flags=") {
+                let flags_rest = &line[flags_start..];
+                if let Some(end) = flags_rest.find(' ') {
+                    current_flags = flags_rest[..end].to_string();
+                } else {
+                    current_flags = flags_rest.to_string();
+                }
+            } else {
+                current_flags = String::new();
+            }
+
+            current_is_up = line.contains("<UP") || line.contains(",UP,") || line.contains(",UP>");
+            current_inet = Vec::new();
+            current_inet6 = Vec::new();
+            current_mac = None;
+        } else {
+            let trimmed = line.trim();
+            if let Some(rest) = trimmed.strip_prefix("inet ") {
+                let ip = rest.split_whitespace().next().unwrap_or("");
+                if !ip.is_empty() {
+                    current_inet.push(ip.to_string());
+                }
+            } else if let Some(rest) = trimmed.strip_prefix("inet6 ") {
+                let ip = rest.split_whitespace().next().unwrap_or("");
+                // Strip %scope_id suffix for cleaner display
+                let ip = ip.split('%').next().unwrap_or(ip);
+                if !ip.is_empty() {
+                    current_inet6.push(ip.to_string());
+                }
+            } else if let Some(rest) = trimmed.strip_prefix("ether ") {
+                let mac = rest.split_whitespace().next().unwrap_or("");
+                if !mac.is_empty() {
+                    current_mac = Some(mac.to_string());
+                }
+            }
         }
-        Err(e) => CallToolResult::error(format!("Failed to run ifconfig: {e}")),
     }
+
+    // Flush last interface
+    flush(
+        &current_name,
+        &current_flags,
+        current_is_up,
+        &current_inet,
+        &current_inet6,
+        &current_mac,
+        &mut interfaces,
+    );
+
+    Ok(CallToolResult::json(&json!(interfaces)))
 }
@@ -1,9 +1,11 @@
 use std::process::Command;
+use crate::process::RunCommand;
+use crate::capabilities::ToolResult;
 use crate::mcp::types::CallToolResult;
 
 /// Send a native macOS notification via osascript.
 /// This avoids needing UNUserNotificationCenter entitlements.
-pub fn send(title: &str, body: &str, subtitle: Option<&str>) -> CallToolResult {
+pub fn send(title: &str, body: &str, subtitle: Option<&str>) -> ToolResult {
     // Sanitize inputs — strip quotes to avoid breaking the AppleScript string literals
     let clean = |s: &str| s.replace('"', "'").replace('\\', "");
 
@@ -19,15 +21,14 @@ pub fn send(title: &str, body: &str, subtitle: Option<&str>) -> CallToolResult {
 
     match Command::new("osascript")
         .args(["-e", &script])
-        .output()
+        .run()?
     {
-        Ok(output) if output.status.success() => {
-            CallToolResult::text("Notification sent")
+        output if output.status.success() => {
+            Ok(CallToolResult::text("Notification sent"))
         }
-        Ok(output) => {
+        output => {
             let stderr = String::from_utf8_lossy(&output.stderr);
-            CallToolResult::error(format!("osascript failed: {stderr}"))
+            Ok(CallToolResult::error(format!("osascript failed: {stderr}")))
         }
-        Err(e) => CallToolResult::error(format!("Failed to send notification: {e}")),
     }
 }
@@ -1,17 +1,24 @@
 use std::path::{Path, PathBuf};
 use std::process::Command;
+use crate::process::RunCommand;
 use std::fs;
-use serde_json::Value;
+use serde_json::{json, Value};
+use crate::capabilities::{Progress, ToolResult};
+use crate::error::DaemonError;
 use crate::mcp::types::CallToolResult;
+use tracing::{info, warn};
 
 /// Get the path to the compiled OCR binary, compiling it if needed.
-fn ocr_binary_path() -> Result<PathBuf, String> {
-    let scripts_dir = get_scripts_dir()?;
+fn ocr_binary_path() -> Result<PathBuf, DaemonError> {
+    let scripts_dir = get_scripts_dir().map_err(DaemonError::Platform)?;
     let swift_source = scripts_dir.join("ocr.swift");
     let binary = scripts_dir.join(".ocr_compiled");
 
     if !swift_source.exists() {
-        return Err(format!("OCR Swift helper not found at {}", swift_source.display()));
+        return Err(DaemonError::Platform(format!(
+            "OCR Swift helper not found at {}",
+            swift_source.display()
+        )));
     }
 
     // Recompile if binary doesn't exist or is older than source
@@ -31,6 +38,7 @@ fn ocr_binary_path() -> Result<PathBuf, String> {
     };
 
     if needs_compile {
+        info!(source = %swift_source.display(), "compiling OCR helper");
         let output = Command::new("swiftc")
             .args([
                 "-O",
@@ -39,12 +47,12 @@ fn ocr_binary_path() -> Result<PathBuf, String> {
                 swift_source.to_str().unwrap(),
                 "-o", binary.to_str().unwrap(),
             ])
-            .output()
-            .map_err(|e| format!("Failed to run swiftc: {e}"))?;
+            .run()?;
 
         if !output.status.success() {
             let stderr = String::from_utf8_lossy(&output.stderr);
-            return Err(format!("Failed to compile OCR helper: {stderr}"));
+            warn!(status = %output.status, "swiftc failed: {stderr}");
+            return Err(DaemonError::Platform(format!("Failed to compile OCR helper: {stderr}")));
         }
     }
 
@@ -70,23 +78,19 @@ fn get_scripts_dir() -> Result<PathBuf, String> {
 }
 
 /// Run the OCR binary on an image file and parse the JSON output.
-fn run_ocr(image_path: &Path) -> CallToolResult {
-    let binary = match ocr_binary_path() {
-        Ok(b) => b,
-        Err(e) => return CallToolResult::error(e),
-    };
+/// Reports stages 1 and 2 of 3 (helper, recognition) on `progress`.
+fn run_ocr(image_path: &Path, progress: &Progress) -> ToolResult {
+    progress.report(1, Some(3), "Preparing OCR helper");
+    let binary = ocr_binary_path()?;
 
-    let output = match Command::new(&binary)
+    progress.report(2, Some(3), "Recognizing text");
+    let output = Command::new(&binary)
         .arg(image_path.to_str().unwrap_or(""))
-        .output()
-    {
-        Ok(o) => o,
-        Err(e) => return CallToolResult::error(format!("Failed to run OCR binary: {e}")),
-    };
+        .run()?;
 
     if !output.status.success() {
         let stderr = String::from_utf8_lossy(&output.stderr);
-        return CallToolResult::error(format!("OCR failed: {stderr}"));
+        return Ok(CallToolResult::error(format!("OCR failed: {stderr}")));
     }
 
     let stdout = String::from_utf8_lossy(&output.stdout);
@@ -94,68 +98,62 @@ fn run_ocr(image_path: &Path) -> CallToolResult {
         Ok(val) => {
             // Check if it's an error response
             if let Some(err) = val.get("error") {
-                return CallToolResult::error(err.as_str().unwrap_or("Unknown OCR error").to_string());
+                return Ok(CallToolResult::error(err.as_str().unwrap_or("Unknown OCR error").to_string()));
             }
-            CallToolResult::json(&val)
+            Ok(CallToolResult::json(&json!({ "lines": val })))
         }
-        Err(_) => CallToolResult::text(stdout.to_string()),
+        Err(_) => Ok(CallToolResult::text(stdout.to_string())),
     }
 }
 
 // ── OCR Screen ──────────────────────────────────────────────────────────────
 
-pub fn ocr_screen(display: u32) -> CallToolResult {
+pub fn ocr_screen(display: u32, progress: &Progress) -> ToolResult {
     // Take a screenshot first, then OCR it
+    progress.report(0, Some(3), &format!("Capturing display {display}"));
     let tmp_path = format!("/tmp/familiar-ocr-screen-{}.png", std::process::id());
 
     let output = Command::new("screencapture")
         .args(["-x", "-D", &display.to_string(), &tmp_path])
-        .output();
+        .run()?;
 
-    match output {
-        Ok(o) if o.status.success() => {
-            let result = run_ocr(Path::new(&tmp_path));
-            let _ = fs::remove_file(&tmp_path);
-            result
-        }
-        Ok(o) => {
-            let stderr = String::from_utf8_lossy(&o.stderr);
-            CallToolResult::error(format!("Screenshot failed: {stderr}"))
-        }
-        Err(e) => CallToolResult::error(format!("Failed to run screencapture: {e}")),
+    if output.status.success() {
+        let result = run_ocr(Path::new(&tmp_path), progress);
+        let _ = fs::remove_file(&tmp_path);
+        progress.report(3, Some(3), "Done");
+        result
+    } else {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        Ok(CallToolResult::error(format!("Screenshot failed: {stderr}")))
     }
 }
 
 // ── OCR Region ──────────────────────────────────────────────────────────────
 
-pub fn ocr_region(x: f64, y: f64, width: f64, height: f64) -> CallToolResult {
+pub fn ocr_region(x: f64, y: f64, width: f64, height: f64) -> ToolResult {
     let tmp_path = format!("/tmp/familiar-ocr-region-{}.png", std::process::id());
     let rect = format!("{},{},{},{}", x as i32, y as i32, width as i32, height as i32);
 
     let output = Command::new("screencapture")
         .args(["-x", "-R", &rect, &tmp_path])
-        .output();
+        .run()?;
 
-    match output {
-        Ok(o) if o.status.success() => {
-            let result = run_ocr(Path::new(&tmp_path));
-            let _ = fs::remove_file(&tmp_path);
-            result
-        }
-        Ok(o) => {
-            let stderr = String::from_utf8_lossy(&o.stderr);
-            CallToolResult::error(format!("Screenshot failed: {stderr}"))
-        }
-        Err(e) => CallToolResult::error(format!("Failed to run screencapture: {e}")),
+    if output.status.success() {
+        let result = run_ocr(Path::new(&tmp_path), &Progress::none());
+        let _ = fs::remove_file(&tmp_path);
+        result
+    } else {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        Ok(CallToolResult::error(format!("Screenshot failed: {stderr}")))
     }
 }
 
 // ── OCR Image ───────────────────────────────────────────────────────────────
 
-pub fn ocr_image(path: &str) -> CallToolResult {
+pub fn ocr_image(path: &str) -> ToolResult {
     let image_path = Path::new(path);
     if !image_path.exists() {
-        return CallToolResult::error(format!("Image file not found: {path}"));
+        return Ok(CallToolResult::error(format!("Image file not found: {path}")));
     }
-    run_ocr(image_path)
+    run_ocr(image_path, &Progress::none())
 }
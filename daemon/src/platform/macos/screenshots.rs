@@ -1,10 +1,12 @@
 use std::fs;
 use std::process::Command;
+use crate::process::RunCommand;
 use std::time::{SystemTime, UNIX_EPOCH};
 
 use base64::Engine;
 use base64::engine::general_purpose::STANDARD;
 
+use crate::capabilities::ToolResult;
 use crate::mcp::types::CallToolResult;
 
 /// Generate a unique temp file path for a screenshot.
@@ -32,7 +34,7 @@ fn read_and_cleanup(path: &str) -> CallToolResult {
 }
 
 /// Capture the full screen (or a specific display).
-pub fn capture_screen(display_id: Option<u32>) -> CallToolResult {
+pub fn capture_screen(display_id: Option<u32>) -> ToolResult {
     let path = temp_path();
     let mut cmd = Command::new("screencapture");
     cmd.args(["-x", "-t", "png"]);
@@ -43,50 +45,47 @@ pub fn capture_screen(display_id: Option<u32>) -> CallToolResult {
 
     cmd.arg(&path);
 
-    match cmd.output() {
-        Ok(output) if output.status.success() => read_and_cleanup(&path),
-        Ok(output) => {
+    match cmd.run()? {
+        output if output.status.success() => Ok(read_and_cleanup(&path)),
+        output => {
             let _ = fs::remove_file(&path);
             let stderr = String::from_utf8_lossy(&output.stderr);
-            CallToolResult::error(format!("screencapture failed: {stderr}"))
+            Ok(CallToolResult::error(format!("screencapture failed: {stderr}")))
         }
-        Err(e) => CallToolResult::error(format!("Failed to run screencapture: {e}")),
     }
 }
 
 /// Capture a specific window by its window ID.
-pub fn capture_window(window_id: u32) -> CallToolResult {
+pub fn capture_window(window_id: u32) -> ToolResult {
     let path = temp_path();
 
     match Command::new("screencapture")
         .args(["-x", "-t", "png", "-l", &window_id.to_string(), &path])
-        .output()
+        .run()?
     {
-        Ok(output) if output.status.success() => read_and_cleanup(&path),
-        Ok(output) => {
+        output if output.status.success() => Ok(read_and_cleanup(&path)),
+        output => {
             let _ = fs::remove_file(&path);
             let stderr = String::from_utf8_lossy(&output.stderr);
-            CallToolResult::error(format!("screencapture failed: {stderr}"))
+            Ok(CallToolResult::error(format!("screencapture failed: {stderr}")))
         }
-        Err(e) => CallToolResult::error(format!("Failed to run screencapture: {e}")),
     }
 }
 
 /// Capture a rectangular region of the screen.
-pub fn capture_region(x: i32, y: i32, width: u32, height: u32) -> CallToolResult {
+pub fn capture_region(x: i32, y: i32, width: u32, height: u32) -> ToolResult {
     let path = temp_path();
     let region = format!("{x},{y},{width},{height}");
 
     match Command::new("screencapture")
         .args(["-x", "-t", "png", "-R", &region, &path])
-        .output()
+        .run()?
     {
-        Ok(output) if output.status.success() => read_and_cleanup(&path),
-        Ok(output) => {
+        output if output.status.success() => Ok(read_and_cleanup(&path)),
+        output => {
             let _ = fs::remove_file(&path);
             let stderr = String::from_utf8_lossy(&output.stderr);
-            CallToolResult::error(format!("screencapture failed: {stderr}"))
+            Ok(CallToolResult::error(format!("screencapture failed: {stderr}")))
         }
-        Err(e) => CallToolResult::error(format!("Failed to run screencapture: {e}")),
     }
 }
@@ -3,12 +3,13 @@
 // This module adds macOS-only features like battery via IOKit.
 
 use std::process::Command;
+use crate::process::RunCommand;
 
 /// Get battery info via `pmset -g batt` (simplest reliable approach on macOS).
 pub fn get_battery() -> Option<(f64, bool)> {
     let output = Command::new("pmset")
         .args(["-g", "batt"])
-        .output()
+        .run()
         .ok()?;
 
     let text = String::from_utf8_lossy(&output.stdout);
@@ -1,19 +1,29 @@
 use std::process::Command;
+use crate::process::RunCommand;
 use serde_json::json;
+use crate::capabilities::ToolResult;
 use crate::mcp::types::CallToolResult;
 
 /// Check if tmux is available and running.
 fn has_tmux() -> bool {
     Command::new("tmux")
         .args(["list-sessions"])
-        .output()
+        .run()
         .map(|o| o.status.success())
         .unwrap_or(false)
 }
 
+/// `session:window.pane` targets are tmux panes, anything else a
+/// Terminal.app window index. A tmux target fails with a missing
+/// dependency error when tmux isn't installed, rather than falling back
+/// to a Terminal.app window it doesn't name.
+fn is_tmux_target(target: &str) -> bool {
+    target.contains(':') || target.contains('.')
+}
+
 // ── List Sessions ───────────────────────────────────────────────────────────
 
-pub fn list_sessions() -> CallToolResult {
+pub fn list_sessions() -> ToolResult {
     if has_tmux() {
         list_tmux_sessions()
     } else {
@@ -21,17 +31,13 @@ pub fn list_sessions() -> CallToolResult {
     }
 }
 
-fn list_tmux_sessions() -> CallToolResult {
-    let output = match Command::new("tmux")
+fn list_tmux_sessions() -> ToolResult {
+    let output = Command::new("tmux")
         .args(["list-sessions", "-F", "#{session_name}:#{session_windows}:#{session_attached}"])
-        .output()
-    {
-        Ok(o) => o,
-        Err(e) => return CallToolResult::error(format!("Failed to run tmux: {e}")),
-    };
+        .run()?;
 
     if !output.status.success() {
-        return CallToolResult::error("No tmux sessions found");
+        return Ok(CallToolResult::error("No tmux sessions found"));
     }
 
     let stdout = String::from_utf8_lossy(&output.stdout);
@@ -52,7 +58,7 @@ fn list_tmux_sessions() -> CallToolResult {
     // Also list panes
     let pane_output = Command::new("tmux")
         .args(["list-panes", "-a", "-F", "#{session_name}:#{window_index}.#{pane_index} #{pane_current_command} #{pane_width}x#{pane_height}"])
-        .output();
+        .run();
 
     let mut panes = Vec::new();
     if let Ok(o) = pane_output {
@@ -71,14 +77,14 @@ fn list_tmux_sessions() -> CallToolResult {
         }
     }
 
-    CallToolResult::json(&json!({
+    Ok(CallToolResult::json(&json!({
         "backend": "tmux",
         "sessions": sessions,
         "panes": panes,
-    }))
+    })))
 }
 
-fn list_terminal_app_windows() -> CallToolResult {
+fn list_terminal_app_windows() -> ToolResult {
     let script = r#"
         tell application "Terminal"
             set windowList to {}
@@ -89,53 +95,47 @@ fn list_terminal_app_windows() -> CallToolResult {
         end tell
     "#;
 
-    let output = match Command::new("osascript").args(["-e", script]).output() {
-        Ok(o) => o,
-        Err(e) => return CallToolResult::error(format!("Failed to run osascript: {e}")),
-    };
+    let output = Command::new("osascript").args(["-e", script]).run()?;
 
     if !output.status.success() {
-        return CallToolResult::text("No Terminal.app windows found. Use terminal_create to open one.");
+        return Ok(CallToolResult::text("No Terminal.app windows found. Use terminal_create to open one."));
     }
 
     let stdout = String::from_utf8_lossy(&output.stdout);
-    CallToolResult::json(&json!({
+    Ok(CallToolResult::json(&json!({
         "backend": "Terminal.app",
         "raw": stdout.trim(),
-    }))
+    })))
 }
 
 // ── Send Keys ───────────────────────────────────────────────────────────────
 
-pub fn send_keys(target: &str, keys: &str, literal: bool) -> CallToolResult {
-    if has_tmux() && (target.contains(':') || target.contains('.')) {
+pub fn send_keys(target: &str, keys: &str, literal: bool) -> ToolResult {
+    if is_tmux_target(target) {
         send_tmux_keys(target, keys, literal)
     } else {
         send_terminal_app_keys(target, keys)
     }
 }
 
-fn send_tmux_keys(target: &str, keys: &str, literal: bool) -> CallToolResult {
+fn send_tmux_keys(target: &str, keys: &str, literal: bool) -> ToolResult {
     let mut args = vec!["send-keys", "-t", target];
     if literal {
         args.push("-l");
     }
     args.push(keys);
 
-    let output = match Command::new("tmux").args(&args).output() {
-        Ok(o) => o,
-        Err(e) => return CallToolResult::error(format!("Failed to run tmux: {e}")),
-    };
+    let output = Command::new("tmux").args(&args).run()?;
 
     if !output.status.success() {
         let stderr = String::from_utf8_lossy(&output.stderr);
-        return CallToolResult::error(format!("tmux send-keys failed: {stderr}"));
+        return Ok(CallToolResult::error(format!("tmux send-keys failed: {stderr}")));
     }
 
-    CallToolResult::text(format!("Sent keys to tmux pane {target}"))
+    Ok(CallToolResult::text(format!("Sent keys to tmux pane {target}")))
 }
 
-fn send_terminal_app_keys(target: &str, keys: &str) -> CallToolResult {
+fn send_terminal_app_keys(target: &str, keys: &str) -> ToolResult {
     let window_idx = target.parse::<i32>().unwrap_or(1);
     let escaped = keys.replace('\\', "\\\\").replace('"', "\\\"");
     let script = format!(
@@ -144,49 +144,42 @@ fn send_terminal_app_keys(target: &str, keys: &str) -> CallToolResult {
         end tell"#
     );
 
-    let output = match Command::new("osascript").args(["-e", &script]).output() {
-        Ok(o) => o,
-        Err(e) => return CallToolResult::error(format!("Failed to run osascript: {e}")),
-    };
+    let output = Command::new("osascript").args(["-e", &script]).run()?;
 
     if !output.status.success() {
         let stderr = String::from_utf8_lossy(&output.stderr);
-        return CallToolResult::error(format!("Terminal.app send failed: {stderr}"));
+        return Ok(CallToolResult::error(format!("Terminal.app send failed: {stderr}")));
     }
 
-    CallToolResult::text(format!("Sent keys to Terminal.app window {window_idx}"))
+    Ok(CallToolResult::text(format!("Sent keys to Terminal.app window {window_idx}")))
 }
 
 // ── Capture ─────────────────────────────────────────────────────────────────
 
-pub fn capture(target: &str, lines: usize) -> CallToolResult {
-    if has_tmux() && (target.contains(':') || target.contains('.')) {
+pub fn capture(target: &str, lines: usize) -> ToolResult {
+    if is_tmux_target(target) {
         capture_tmux(target, lines)
     } else {
         capture_terminal_app(target)
     }
 }
 
-fn capture_tmux(target: &str, lines: usize) -> CallToolResult {
+fn capture_tmux(target: &str, lines: usize) -> ToolResult {
     let start_line = format!("-{}", lines);
-    let output = match Command::new("tmux")
+    let output = Command::new("tmux")
         .args(["capture-pane", "-t", target, "-p", "-S", &start_line])
-        .output()
-    {
-        Ok(o) => o,
-        Err(e) => return CallToolResult::error(format!("Failed to run tmux: {e}")),
-    };
+        .run()?;
 
     if !output.status.success() {
         let stderr = String::from_utf8_lossy(&output.stderr);
-        return CallToolResult::error(format!("tmux capture-pane failed: {stderr}"));
+        return Ok(CallToolResult::error(format!("tmux capture-pane failed: {stderr}")));
     }
 
     let stdout = String::from_utf8_lossy(&output.stdout);
-    CallToolResult::text(stdout.to_string())
+    Ok(CallToolResult::text(stdout.to_string()))
 }
 
-fn capture_terminal_app(target: &str) -> CallToolResult {
+fn capture_terminal_app(target: &str) -> ToolResult {
     let window_idx = target.parse::<i32>().unwrap_or(1);
     let script = format!(
         r#"tell application "Terminal"
@@ -195,23 +188,20 @@ fn capture_terminal_app(target: &str) -> CallToolResult {
         end tell"#
     );
 
-    let output = match Command::new("osascript").args(["-e", &script]).output() {
-        Ok(o) => o,
-        Err(e) => return CallToolResult::error(format!("Failed to run osascript: {e}")),
-    };
+    let output = Command::new("osascript").args(["-e", &script]).run()?;
 
     if !output.status.success() {
         let stderr = String::from_utf8_lossy(&output.stderr);
-        return CallToolResult::error(format!("Terminal.app capture failed: {stderr}"));
+        return Ok(CallToolResult::error(format!("Terminal.app capture failed: {stderr}")));
     }
 
     let stdout = String::from_utf8_lossy(&output.stdout);
-    CallToolResult::text(stdout.to_string())
+    Ok(CallToolResult::text(stdout.to_string()))
 }
 
 // ── Create Session ──────────────────────────────────────────────────────────
 
-pub fn create_session(name: Option<&str>, command: Option<&str>, directory: Option<&str>) -> CallToolResult {
+pub fn create_session(name: Option<&str>, command: Option<&str>, directory: Option<&str>) -> ToolResult {
     if has_tmux() || name.is_some() {
         create_tmux_session(name, command, directory)
     } else {
@@ -219,7 +209,7 @@ pub fn create_session(name: Option<&str>, command: Option<&str>, directory: Opti
     }
 }
 
-fn create_tmux_session(name: Option<&str>, command: Option<&str>, directory: Option<&str>) -> CallToolResult {
+fn create_tmux_session(name: Option<&str>, command: Option<&str>, directory: Option<&str>) -> ToolResult {
     let session_name = name.unwrap_or("familiar");
 
     // Check if tmux server is running; if not, this will start it
@@ -234,28 +224,25 @@ fn create_tmux_session(name: Option<&str>, command: Option<&str>, directory: Opt
         args.push(cmd);
     }
 
-    let output = match Command::new("tmux").args(&args).output() {
-        Ok(o) => o,
-        Err(e) => return CallToolResult::error(format!("Failed to run tmux: {e}")),
-    };
+    let output = Command::new("tmux").args(&args).run()?;
 
     if !output.status.success() {
         let stderr = String::from_utf8_lossy(&output.stderr);
         // If session already exists, that's OK
         if stderr.contains("duplicate session") {
-            return CallToolResult::text(format!("tmux session '{session_name}' already exists"));
+            return Ok(CallToolResult::text(format!("tmux session '{session_name}' already exists")));
         }
-        return CallToolResult::error(format!("tmux new-session failed: {stderr}"));
+        return Ok(CallToolResult::error(format!("tmux new-session failed: {stderr}")));
     }
 
-    CallToolResult::json(&json!({
+    Ok(CallToolResult::json(&json!({
         "backend": "tmux",
         "session": session_name,
         "target": format!("{session_name}:0.0"),
-    }))
+    })))
 }
 
-fn create_terminal_app_window(command: Option<&str>, directory: Option<&str>) -> CallToolResult {
+fn create_terminal_app_window(command: Option<&str>, directory: Option<&str>) -> ToolResult {
     let mut script_parts = Vec::new();
 
     script_parts.push("tell application \"Terminal\"".to_string());
@@ -279,18 +266,15 @@ fn create_terminal_app_window(command: Option<&str>, directory: Option<&str>) ->
     script_parts.push("end tell".to_string());
 
     let script = script_parts.join("\n");
-    let output = match Command::new("osascript").args(["-e", &script]).output() {
-        Ok(o) => o,
-        Err(e) => return CallToolResult::error(format!("Failed to run osascript: {e}")),
-    };
+    let output = Command::new("osascript").args(["-e", &script]).run()?;
 
     if !output.status.success() {
         let stderr = String::from_utf8_lossy(&output.stderr);
-        return CallToolResult::error(format!("Terminal.app create failed: {stderr}"));
+        return Ok(CallToolResult::error(format!("Terminal.app create failed: {stderr}")));
     }
 
-    CallToolResult::json(&json!({
+    Ok(CallToolResult::json(&json!({
         "backend": "Terminal.app",
         "created": true,
-    }))
+    })))
 }
@@ -1,4 +1,5 @@
 use std::process::Command;
+use crate::process::RunCommand;
 use core_foundation::base::{CFRelease, CFTypeRef};
 
 use crate::mcp::types::CallToolResult;
@@ -20,15 +21,11 @@ pub fn list_windows(app_name: Option<&str>) -> CallToolResult {
         }
         None => windows,
     };
-    CallToolResult::json(&json!(filtered))
+    CallToolResult::json(&json!({ "windows": filtered }))
 }
 
 /// Focus a window by window ID.
 pub fn focus_window(window_id: u32) -> CallToolResult {
-    if let Err(e) = ax_helpers::ensure_trusted() {
-        return CallToolResult::error(e);
-    }
-
     let win = match cg_helpers::get_window(window_id) {
         Some(w) => w,
         None => return CallToolResult::error(format!("Window {window_id} not found")),
@@ -53,7 +50,7 @@ pub fn focus_window(window_id: u32) -> CallToolResult {
         r#"tell application "System Events" to set frontmost of (first process whose unix id is {}) to true"#,
         win.pid
     );
-    let _ = Command::new("osascript").arg("-e").arg(&script).output();
+    let _ = Command::new("osascript").arg("-e").arg(&script).run();
 
     unsafe { CFRelease(app_ref as CFTypeRef) };
 
@@ -74,10 +71,6 @@ pub fn focus_window(window_id: u32) -> CallToolResult {
 
 /// Move a window by window ID to (x, y).
 pub fn move_window(window_id: u32, x: f64, y: f64) -> CallToolResult {
-    if let Err(e) = ax_helpers::ensure_trusted() {
-        return CallToolResult::error(e);
-    }
-
     let win = match cg_helpers::get_window(window_id) {
         Some(w) => w,
         None => return CallToolResult::error(format!("Window {window_id} not found")),
@@ -118,10 +111,6 @@ pub fn move_window(window_id: u32, x: f64, y: f64) -> CallToolResult {
 
 /// Resize a window by window ID to (width, height).
 pub fn resize_window(window_id: u32, width: f64, height: f64) -> CallToolResult {
-    if let Err(e) = ax_helpers::ensure_trusted() {
-        return CallToolResult::error(e);
-    }
-
     let win = match cg_helpers::get_window(window_id) {
         Some(w) => w,
         None => return CallToolResult::error(format!("Window {window_id} not found")),
@@ -162,10 +151,6 @@ pub fn resize_window(window_id: u32, width: f64, height: f64) -> CallToolResult
 
 /// Minimize a window by window ID.
 pub fn minimize_window(window_id: u32) -> CallToolResult {
-    if let Err(e) = ax_helpers::ensure_trusted() {
-        return CallToolResult::error(e);
-    }
-
     let win = match cg_helpers::get_window(window_id) {
         Some(w) => w,
         None => return CallToolResult::error(format!("Window {window_id} not found")),
@@ -203,10 +188,6 @@ pub fn minimize_window(window_id: u32) -> CallToolResult {
 
 /// Close a window by window ID.
 pub fn close_window(window_id: u32) -> CallToolResult {
-    if let Err(e) = ax_helpers::ensure_trusted() {
-        return CallToolResult::error(e);
-    }
-
     let win = match cg_helpers::get_window(window_id) {
         Some(w) => w,
         None => return CallToolResult::error(format!("Window {window_id} not found")),
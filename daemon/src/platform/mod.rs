@@ -1,3 +1,5 @@
+pub mod keys;
+
 #[cfg(target_os = "macos")]
 pub mod macos;
 
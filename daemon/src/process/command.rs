@@ -0,0 +1,311 @@
+use std::cell::Cell;
+use std::io::{self, Read, Write};
+use std::process::{Command, ExitStatus, Output, Stdio};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::thread;
+use std::time::Duration;
+
+use tracing::{debug, warn};
+
+use super::{kill, CancelToken, CURRENT, DEFAULT_TIMEOUT, TIMEOUT};
+use crate::error::DaemonError;
+
+/// Most bytes of stdout (and of stderr) kept per command.
+const MAX_OUTPUT: usize = 16 * 1024 * 1024;
+
+/// Longest line handed to `run_streaming`'s callback; longer ones are
+/// handed over in pieces.
+const MAX_LINE: usize = 64 * 1024;
+
+impl CancelToken {
+    fn register(&self, pid: u32) {
+        self.children().push(pid);
+        // Cancelled between spawn and registration
+        if self.is_cancelled() {
+            kill(pid);
+        }
+    }
+
+    fn unregister(&self, pid: u32) {
+        self.children().retain(|p| *p != pid);
+    }
+
+    /// Kill `pid` if it is still registered: once it is unregistered it may
+    /// have been reaped, and its id reused by an unrelated process.
+    fn kill_child(&self, pid: u32) {
+        if self.children().contains(&pid) {
+            kill(pid);
+        }
+    }
+}
+
+fn current() -> Option<CancelToken> {
+    CURRENT.with(|c| c.borrow().clone())
+}
+
+fn timeout() -> Duration {
+    TIMEOUT.with(Cell::get).unwrap_or(DEFAULT_TIMEOUT)
+}
+
+/// Runs commands for the platform backends. Every command is limited:
+/// it is killed, together with anything it started, when it outlives the
+/// current timeout (see `with_timeout`) or the request is cancelled, and
+/// output beyond `MAX_OUTPUT` per stream is dropped. Exit statuses are
+/// left to the caller.
+pub trait RunCommand {
+    /// `Command::output` within the limits.
+    fn run(&mut self) -> Result<Output, DaemonError>;
+
+    /// Like `run`, with `input` written to the command's stdin.
+    fn run_with_input(&mut self, input: &[u8]) -> Result<Output, DaemonError>;
+
+    /// Like `run`, but hands each line of stdout to `on_line` as it is
+    /// produced (for progress reporting). The full output is still
+    /// returned.
+    fn run_streaming(&mut self, on_line: impl FnMut(&str)) -> Result<Output, DaemonError>;
+}
+
+impl RunCommand for Command {
+    fn run(&mut self) -> Result<Output, DaemonError> {
+        execute(self, None, &mut |_| {})
+    }
+
+    fn run_with_input(&mut self, input: &[u8]) -> Result<Output, DaemonError> {
+        execute(self, Some(input), &mut |_| {})
+    }
+
+    fn run_streaming(&mut self, mut on_line: impl FnMut(&str)) -> Result<Output, DaemonError> {
+        execute(self, None, &mut on_line)
+    }
+}
+
+fn execute(command: &mut Command, input: Option<&[u8]>, on_line: &mut dyn FnMut(&str)) -> Result<Output, DaemonError> {
+    let program = command.get_program().to_string_lossy().into_owned();
+    let token = current().unwrap_or_default();
+    if token.is_cancelled() {
+        return Err(cancelled().into());
+    }
+
+    command
+        .stdin(if input.is_some() { Stdio::piped() } else { Stdio::null() })
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    // Own process group, so a timeout also kills what the command started
+    #[cfg(unix)]
+    std::os::unix::process::CommandExt::process_group(command, 0);
+    let mut child = command.spawn().map_err(|e| DaemonError::spawn(&program, e))?;
+    let pid = child.id();
+    token.register(pid);
+
+    let timeout = timeout();
+    let (exited, exit) = mpsc::channel::<()>();
+    let watchdog_token = token.clone();
+    let watchdog = thread::spawn(move || {
+        let expired = exit.recv_timeout(timeout) == Err(RecvTimeoutError::Timeout);
+        if expired {
+            watchdog_token.kill_child(pid);
+        }
+        expired
+    });
+
+    let writer = input.map(|input| {
+        let mut stdin = child.stdin.take().expect("stdin is piped");
+        let input = input.to_vec();
+        // Fails only if the command exits without reading it all
+        thread::spawn(move || stdin.write_all(&input))
+    });
+    // Drain stderr on the side so a chatty child can't block on a full pipe
+    let stderr_pipe = child.stderr.take().expect("stderr is piped");
+    let stderr_reader = thread::spawn(move || read_capped(stderr_pipe, MAX_OUTPUT, &mut |_| {}));
+    let stdout_pipe = child.stdout.take().expect("stdout is piped");
+    let (stdout, stdout_truncated) = read_capped(stdout_pipe, MAX_OUTPUT, on_line);
+
+    // Unregister before reaping, so neither a cancellation nor the watchdog
+    // can kill a process that took over the pid
+    wait_exited(pid);
+    token.unregister(pid);
+    let status = child.wait();
+    let _ = exited.send(());
+    // The watchdog may fire as the command exits on its own, killing only
+    // its leftovers: the command still finished in time
+    let timed_out = watchdog.join().unwrap_or(false) && !status.as_ref().is_ok_and(exited_by_itself);
+    let (stderr, stderr_truncated) = stderr_reader.join().unwrap_or_default();
+    if let Some(writer) = writer {
+        let _ = writer.join();
+    }
+
+    if token.is_cancelled() {
+        debug!(pid, %program, "child process killed by cancellation");
+        return Err(cancelled().into());
+    }
+    if timed_out {
+        warn!(pid, %program, ?timeout, "child process killed by timeout");
+        return Err(DaemonError::Timeout {
+            operation: format!("'{program}'"),
+            after: timeout,
+        });
+    }
+    if stdout_truncated || stderr_truncated {
+        warn!(%program, max_bytes = MAX_OUTPUT, "command output truncated");
+    }
+    Ok(Output { status: status?, stdout, stderr })
+}
+
+/// Whether the command exited, rather than being killed by a signal.
+#[cfg(unix)]
+fn exited_by_itself(status: &ExitStatus) -> bool {
+    std::os::unix::process::ExitStatusExt::signal(status).is_none()
+}
+
+#[cfg(not(unix))]
+fn exited_by_itself(_status: &ExitStatus) -> bool {
+    false
+}
+
+/// Read `pipe` to the end in fixed-size chunks, keeping the first `limit`
+/// bytes and handing each line to `on_line`. Past the limit the pipe is
+/// still drained, so the command can't block on it, but nothing more is
+/// kept. Returns the output and whether anything was dropped.
+fn read_capped(mut pipe: impl Read, limit: usize, on_line: &mut dyn FnMut(&str)) -> (Vec<u8>, bool) {
+    let mut output = Vec::new();
+    let mut truncated = false;
+    let mut chunk = [0u8; 8192];
+    let mut line = Vec::new();
+    loop {
+        let n = match pipe.read(&mut chunk) {
+            Ok(0) => break,
+            Ok(n) => n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(_) => break,
+        };
+        let room = limit - output.len();
+        truncated |= n > room;
+        output.extend_from_slice(&chunk[..n.min(room)]);
+
+        for piece in chunk[..n].split_inclusive(|b| *b == b'\n') {
+            line.extend_from_slice(piece);
+            if piece.ends_with(b"\n") || line.len() >= MAX_LINE {
+                on_line(String::from_utf8_lossy(&line).trim_end());
+                line.clear();
+            }
+        }
+    }
+    if !line.is_empty() {
+        on_line(String::from_utf8_lossy(&line).trim_end());
+    }
+    (output, truncated)
+}
+
+/// Block until the child `pid` has exited, leaving it to be reaped.
+#[cfg(unix)]
+fn wait_exited(pid: u32) {
+    let mut info: libc::siginfo_t = unsafe { std::mem::zeroed() };
+    loop {
+        let result = unsafe {
+            libc::waitid(libc::P_PID, pid as libc::id_t, &mut info, libc::WEXITED | libc::WNOWAIT)
+        };
+        if result == 0 || io::Error::last_os_error().kind() != io::ErrorKind::Interrupted {
+            break;
+        }
+    }
+}
+
+#[cfg(not(unix))]
+fn wait_exited(_pid: u32) {}
+
+fn cancelled() -> io::Error {
+    io::Error::new(io::ErrorKind::Interrupted, "request cancelled")
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::io::Cursor;
+
+    use super::*;
+    use crate::process::{with_timeout, with_token};
+
+    fn read(input: &[u8], limit: usize) -> (Vec<u8>, bool, Vec<String>) {
+        let mut lines = Vec::new();
+        let (output, truncated) = read_capped(Cursor::new(input), limit, &mut |l| lines.push(l.to_string()));
+        (output, truncated, lines)
+    }
+
+    #[test]
+    fn read_capped_keeps_everything_under_the_limit() {
+        let (output, truncated, lines) = read(b"one\ntwo\nthree", 100);
+        assert_eq!(output, b"one\ntwo\nthree");
+        assert!(!truncated);
+        assert_eq!(lines, ["one", "two", "three"]);
+    }
+
+    #[test]
+    fn read_capped_stops_buffering_at_the_limit() {
+        let input = b"x".repeat(100_000);
+        let (output, truncated, _) = read(&input, 10);
+        assert_eq!(output, b"x".repeat(10));
+        assert!(truncated);
+        assert!(!read(b"exactly", 7).1);
+    }
+
+    #[test]
+    fn read_capped_splits_long_lines() {
+        let input = b"y".repeat(MAX_LINE + 10);
+        let (_, _, lines) = read(&input, MAX_OUTPUT);
+        assert_eq!(lines.iter().map(String::len).collect::<Vec<_>>(), [MAX_LINE, 10]);
+    }
+
+    #[test]
+    fn runs_commands() {
+        let output = Command::new("sh").args(["-c", "echo out; echo err >&2"]).run().unwrap();
+        assert!(output.status.success());
+        assert_eq!(output.stdout, b"out\n");
+        assert_eq!(output.stderr, b"err\n");
+
+        let output = Command::new("cat").run_with_input(b"piped").unwrap();
+        assert_eq!(output.stdout, b"piped");
+
+        let mut lines = Vec::new();
+        Command::new("printf").arg("a\\nb\\n").run_streaming(|l| lines.push(l.to_string())).unwrap();
+        assert_eq!(lines, ["a", "b"]);
+    }
+
+    #[test]
+    fn missing_program_is_a_missing_dependency() {
+        let e = Command::new("familiar-no-such-program").run().unwrap_err();
+        assert_eq!(e.kind(), "missing_dependency");
+    }
+
+    #[test]
+    fn commands_are_killed_after_the_timeout() {
+        let started = std::time::Instant::now();
+        let e = with_timeout(Duration::from_millis(100), || Command::new("sleep").arg("5").run()).unwrap_err();
+        assert_eq!(e.kind(), "timeout");
+        assert!(started.elapsed() < Duration::from_secs(4));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn commands_killed_by_a_signal_did_not_exit_by_themselves() {
+        use std::os::unix::process::ExitStatusExt;
+
+        assert!(exited_by_itself(&ExitStatus::from_raw(0)));
+        assert!(exited_by_itself(&ExitStatus::from_raw(1 << 8)));
+        assert!(!exited_by_itself(&ExitStatus::from_raw(libc::SIGKILL)));
+    }
+
+    #[test]
+    fn commands_are_killed_on_cancellation() {
+        let token = CancelToken::default();
+        let canceller = token.clone();
+        thread::spawn(move || {
+            thread::sleep(Duration::from_millis(100));
+            canceller.cancel();
+        });
+        let started = std::time::Instant::now();
+        let e = with_token(&token, || Command::new("sleep").arg("5").run()).unwrap_err();
+        assert!(matches!(e, DaemonError::Io(e) if e.kind() == io::ErrorKind::Interrupted));
+        assert!(started.elapsed() < Duration::from_secs(4));
+        assert!(token.children().is_empty());
+    }
+}
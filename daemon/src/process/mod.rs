@@ -0,0 +1,84 @@
+use std::cell::{Cell, RefCell};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+
+// Only the macOS backends (and the tests) spawn commands; the rest of the
+// daemon uses the cancellation tokens and timeouts.
+#[cfg(any(target_os = "macos", test))]
+mod command;
+
+#[cfg(target_os = "macos")]
+pub use command::RunCommand;
+
+/// How long a command may run, unless the capability running it says
+/// otherwise.
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Cancellation handle for one in-flight request. Cancelling marks the
+/// request and kills any child processes it is waiting on.
+#[derive(Clone, Default)]
+pub struct CancelToken {
+    inner: Arc<CancelState>,
+}
+
+#[derive(Default)]
+struct CancelState {
+    cancelled: AtomicBool,
+    children: Mutex<Vec<u32>>,
+}
+
+impl CancelToken {
+    pub fn cancel(&self) {
+        self.inner.cancelled.store(true, Ordering::SeqCst);
+        for pid in self.children().iter() {
+            kill(*pid);
+        }
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.inner.cancelled.load(Ordering::SeqCst)
+    }
+
+    fn children(&self) -> MutexGuard<'_, Vec<u32>> {
+        self.inner.children.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+thread_local! {
+    static CURRENT: RefCell<Option<CancelToken>> = const { RefCell::new(None) };
+    static TIMEOUT: Cell<Option<Duration>> = const { Cell::new(None) };
+}
+
+/// Run `f` with `token` as the current thread's cancellation token, so
+/// commands spawned inside it can be killed from another thread.
+pub fn with_token<R>(token: &CancelToken, f: impl FnOnce() -> R) -> R {
+    let previous = CURRENT.with(|c| c.replace(Some(token.clone())));
+    let result = f();
+    CURRENT.with(|c| *c.borrow_mut() = previous);
+    result
+}
+
+/// Whether the request running on this thread has been cancelled.
+pub fn is_cancelled() -> bool {
+    CURRENT.with(|c| c.borrow().as_ref().is_some_and(CancelToken::is_cancelled))
+}
+
+/// Run `f` with `timeout` as the limit for each command it runs.
+pub fn with_timeout<R>(timeout: Duration, f: impl FnOnce() -> R) -> R {
+    let previous = TIMEOUT.with(|t| t.replace(Some(timeout)));
+    let result = f();
+    TIMEOUT.with(|t| t.set(previous));
+    result
+}
+
+/// Kill the process group `pid` leads (every command gets its own).
+#[cfg(unix)]
+fn kill(pid: u32) {
+    unsafe {
+        libc::kill(-(pid as libc::pid_t), libc::SIGKILL);
+    }
+}
+
+#[cfg(not(unix))]
+fn kill(_pid: u32) {}
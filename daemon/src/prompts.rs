@@ -0,0 +1,455 @@
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::time::Instant;
+use tracing::{info, warn};
+
+use crate::audit::{self, AuditLog};
+use crate::capabilities::{no_confirmation, CallContext, CapabilityRegistry, Progress};
+use crate::error::DaemonError;
+use crate::mcp::types::{CallToolResult, Prompt, PromptArgument};
+
+/// A user-defined prompt template, one per TOML file.
+///
+/// The template text may reference arguments as `{{arg_name}}` and embed
+/// live daemon data as `{{tool:tool_name key=value ...}}`, resolved through
+/// the capability registry (and therefore its permissions) at render time.
+/// Only read-only tools can be embedded.
+#[derive(Debug, Deserialize)]
+pub struct PromptTemplate {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub arguments: Vec<TemplateArgument>,
+    #[serde(default = "default_role")]
+    pub role: String,
+    pub template: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TemplateArgument {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub required: bool,
+}
+
+fn default_role() -> String {
+    "user".into()
+}
+
+impl PromptTemplate {
+    /// The MCP prompt definition advertised in prompts/list.
+    pub fn definition(&self) -> Prompt {
+        Prompt {
+            name: self.name.clone(),
+            description: self.description.clone(),
+            arguments: self
+                .arguments
+                .iter()
+                .map(|a| PromptArgument {
+                    name: a.name.clone(),
+                    description: a.description.clone(),
+                    required: a.required,
+                })
+                .collect(),
+        }
+    }
+
+    /// The tool argument a prompt argument is passed to, as in
+    /// `{{tool:terminal_capture target={{target}}}}`, for completing it
+    /// like the tool's own argument.
+    pub fn tool_argument(&self, argument: &str) -> Option<(&str, &str)> {
+        parse(&self.template).into_iter().find_map(|part| match part {
+            Part::Tool { name, arguments } => arguments
+                .into_iter()
+                .find(|(_, value)| reference(value) == Some(argument))
+                .map(|(key, _)| (name, key)),
+            _ => None,
+        })
+    }
+
+    /// Render the template: substitute arguments, and replace tool
+    /// placeholders with the output of the tools, called as `call` (the
+    /// client's profile, and whether it is a dry run) and recorded in
+    /// `audit` for `client`. Placeholders are taken from the template
+    /// alone, never from argument values, and only read-only tools are
+    /// called. Fails only if a required argument is missing.
+    pub fn render(
+        &self,
+        args: &HashMap<String, String>,
+        registry: &CapabilityRegistry,
+        call: CallContext<'_>,
+        audit: &AuditLog,
+        client: &str,
+    ) -> Result<String, String> {
+        let mut values = HashMap::new();
+        for arg in &self.arguments {
+            let value = match args.get(&arg.name) {
+                Some(v) => v.as_str(),
+                None if arg.required => {
+                    return Err(format!("Missing required argument: {}", arg.name));
+                }
+                None => "",
+            };
+            values.insert(arg.name.as_str(), value);
+        }
+
+        let mut out = String::with_capacity(self.template.len());
+        for part in parse(&self.template) {
+            match part {
+                Part::Text(text) => out.push_str(text),
+                Part::Argument(name) => match values.get(name) {
+                    Some(value) => out.push_str(value),
+                    // Not an argument: braces that are part of the text
+                    None => out.push_str(&format!("{{{{{name}}}}}")),
+                },
+                Part::Tool { name, arguments } => {
+                    let output = call_tool(name, &arguments, &values, registry, call, audit, client);
+                    out.push_str(&output);
+                }
+            }
+        }
+        Ok(out)
+    }
+}
+
+/// A piece of a template.
+#[derive(Debug, PartialEq)]
+enum Part<'a> {
+    Text(&'a str),
+    /// `{{name}}`
+    Argument(&'a str),
+    /// `{{tool:name key=value ...}}`, where a value may contain `{{argument}}`
+    Tool { name: &'a str, arguments: Vec<(&'a str, &'a str)> },
+}
+
+/// Split a template into text, arguments and tool placeholders.
+fn parse(template: &str) -> Vec<Part<'_>> {
+    let mut parts = Vec::new();
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        let Some(len) = placeholder_len(&rest[start..]) else {
+            break;
+        };
+        if start > 0 {
+            parts.push(Part::Text(&rest[..start]));
+        }
+        let inner = &rest[start + 2..start + len - 2];
+        parts.push(match inner.strip_prefix("tool:") {
+            Some(spec) => {
+                let mut words = spec.split_whitespace();
+                Part::Tool {
+                    name: words.next().unwrap_or(""),
+                    arguments: words.filter_map(|w| w.split_once('=')).collect(),
+                }
+            }
+            None => Part::Argument(inner.trim()),
+        });
+        rest = &rest[start + len..];
+    }
+    if !rest.is_empty() {
+        parts.push(Part::Text(rest));
+    }
+    parts
+}
+
+/// Length of the placeholder `text` starts with, through its closing `}}`,
+/// skipping the `{{argument}}` references inside a tool placeholder.
+fn placeholder_len(text: &str) -> Option<usize> {
+    let mut at = 2;
+    loop {
+        let close = at + text[at..].find("}}")?;
+        match text[at..close].find("{{") {
+            Some(open) => at += open + 2 + text[at + open + 2..].find("}}")? + 2,
+            None => return Some(close + 2),
+        }
+    }
+}
+
+/// The argument a tool argument value passes on whole, as in `target={{target}}`.
+fn reference(value: &str) -> Option<&str> {
+    let name = value.strip_prefix("{{")?.strip_suffix("}}")?;
+    (!name.contains("{{")).then_some(name.trim())
+}
+
+/// `text` with its `{{argument}}` references replaced, in one pass.
+fn substitute(text: &str, values: &HashMap<&str, &str>) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("{{")
+        && let Some(len) = rest[start..].find("}}")
+    {
+        let name = rest[start + 2..start + len].trim();
+        out.push_str(&rest[..start]);
+        out.push_str(values.get(name).copied().unwrap_or(&rest[start..start + len + 2]));
+        rest = &rest[start + len + 2..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// The output of one tool placeholder, or a note saying why there is none.
+fn call_tool(
+    tool_name: &str,
+    arguments: &[(&str, &str)],
+    values: &HashMap<&str, &str>,
+    registry: &CapabilityRegistry,
+    call: CallContext<'_>,
+    audit: &AuditLog,
+    client: &str,
+) -> String {
+    let Some(tool) = registry.list_tools(call.profile).into_iter().find(|t| t.name == tool_name) else {
+        return format!("[{tool_name} failed: {}]", DaemonError::UnknownTool(tool_name.into()));
+    };
+    if !tool.annotations.read_only {
+        warn!(tool = %tool_name, "prompt templates may only call read-only tools");
+        return format!("[{tool_name} failed: prompt templates may only call read-only tools]");
+    }
+
+    let arguments: Map<String, Value> = arguments
+        .iter()
+        .map(|(key, value)| {
+            let schema = &tool.input_schema["properties"][*key];
+            (key.to_string(), bind(value, values, schema))
+        })
+        .collect();
+    let arguments = Value::Object(arguments);
+
+    let started = Instant::now();
+    let result = registry.call_tool(call, tool_name, &arguments, &Progress::none(), &no_confirmation);
+    audit.record(client, tool_name, &arguments, audit::status(&result, call.dry_run), started.elapsed());
+
+    let result = result.unwrap_or_else(|e| CallToolResult::error(e.to_string()));
+    let output = result.text_output();
+    if result.is_error.unwrap_or(false) {
+        format!("[{tool_name} failed: {output}]")
+    } else {
+        output.trim_end().to_string()
+    }
+}
+
+/// The value of a tool argument written as `value` in the template, with
+/// `{{argument}}` references replaced by what the client passed. String
+/// arguments (per `schema`) are taken as written; others are parsed as
+/// JSON when possible (numbers, booleans), from client values only as
+/// numbers and booleans.
+fn bind(value: &str, values: &HashMap<&str, &str>, schema: &Value) -> Value {
+    let is_string = schema["type"] == "string";
+    if let Some(name) = reference(value)
+        && let Some(value) = values.get(name)
+    {
+        return match serde_json::from_str::<Value>(value) {
+            Ok(parsed @ (Value::Number(_) | Value::Bool(_))) if !is_string => parsed,
+            _ => Value::String(value.to_string()),
+        };
+    }
+    if value.contains("{{") {
+        return Value::String(substitute(value, values));
+    }
+    match serde_json::from_str::<Value>(value) {
+        Ok(parsed) if !is_string => parsed,
+        _ => Value::String(value.to_string()),
+    }
+}
+
+/// Load all prompt templates.
+/// Search order (first existing directory wins):
+///   1. ~/.familiar/daemon/prompts/*.toml
+///   2. ./config/prompts/*.toml (dev fallback)
+pub fn load() -> Vec<PromptTemplate> {
+    let candidates = [
+        dirs::home_dir().map(|h| h.join(".familiar/daemon/prompts")),
+        Some(PathBuf::from("config/prompts")),
+    ];
+
+    let Some(dir) = candidates.into_iter().flatten().find(|d| d.is_dir()) else {
+        info!("no prompts directory found");
+        return Vec::new();
+    };
+
+    let mut paths: Vec<PathBuf> = match fs::read_dir(&dir) {
+        Ok(entries) => entries
+            .filter_map(|e| e.ok().map(|e| e.path()))
+            .filter(|p| p.extension().is_some_and(|ext| ext == "toml"))
+            .collect(),
+        Err(e) => {
+            warn!(path = %dir.display(), error = %e, "failed to read prompts directory");
+            return Vec::new();
+        }
+    };
+    paths.sort();
+
+    let mut prompts: Vec<PromptTemplate> = Vec::new();
+    for path in paths {
+        match fs::read_to_string(&path) {
+            Ok(content) => match toml::from_str::<PromptTemplate>(&content) {
+                Ok(prompt) if prompts.iter().any(|p| p.name == prompt.name) => {
+                    warn!(path = %path.display(), name = %prompt.name, "duplicate prompt name, skipped");
+                }
+                Ok(prompt) => prompts.push(prompt),
+                Err(e) => {
+                    warn!(path = %path.display(), error = %e, "failed to parse prompt");
+                }
+            },
+            Err(e) => {
+                warn!(path = %path.display(), error = %e, "failed to read prompt");
+            }
+        }
+    }
+
+    info!(path = %dir.display(), prompts = prompts.len(), "loaded prompts");
+    prompts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::capabilities::{CapabilityProvider, ToolResult};
+    use crate::config::AuditSettings;
+    use crate::mcp::types::{Tool, ToolAnnotations};
+    use crate::permissions::PermissionsConfig;
+    use serde_json::json;
+
+    /// `echo` answers with its arguments; `erase` changes state.
+    struct Echo;
+
+    impl CapabilityProvider for Echo {
+        fn id(&self) -> &str {
+            "echo"
+        }
+
+        fn name(&self) -> &str {
+            "Echo"
+        }
+
+        fn tools(&self) -> Vec<Tool> {
+            let tool = |name: &str, annotations| Tool {
+                name: name.into(),
+                description: String::new(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "target": { "type": "string" },
+                        "lines": { "type": "integer" }
+                    }
+                }),
+                output_schema: None,
+                annotations,
+            };
+            vec![tool("echo", ToolAnnotations::read_only()), tool("erase", ToolAnnotations::destructive())]
+        }
+
+        fn call(&self, tool_name: &str, arguments: &Value, _progress: &Progress) -> Option<ToolResult> {
+            match tool_name {
+                "echo" => Some(Ok(CallToolResult::text(arguments.to_string()))),
+                "erase" => Some(Ok(CallToolResult::text("erased"))),
+                _ => None,
+            }
+        }
+    }
+
+    fn registry() -> CapabilityRegistry {
+        let permissions: PermissionsConfig = toml::from_str("[capabilities.echo]\nallowed = true").unwrap();
+        let mut registry = CapabilityRegistry::new(permissions);
+        registry.register(Box::new(Echo));
+        registry
+    }
+
+    fn audit(dir: &tempfile::TempDir) -> AuditLog {
+        AuditLog::new(AuditSettings {
+            path: dir.path().join("audit.jsonl").to_string_lossy().into_owned(),
+            ..AuditSettings::default()
+        })
+    }
+
+    fn template(template: &str) -> PromptTemplate {
+        toml::from_str(&format!(
+            "name = \"test\"\ntemplate = '''{template}'''\n[[arguments]]\nname = \"target\"\nrequired = true"
+        ))
+        .unwrap()
+    }
+
+    fn render(template: &str, target: &str) -> Result<String, String> {
+        let dir = tempfile::tempdir().unwrap();
+        let args = HashMap::from([("target".to_string(), target.to_string())]);
+        self::template(template).render(&args, &registry(), CallContext::default(), &audit(&dir), "test")
+    }
+
+    #[test]
+    fn substitutes_arguments() {
+        assert_eq!(render("Look at {{target}} and {{ target }}.", "main").unwrap(), "Look at main and main.");
+        assert_eq!(render("Keep {{other}} as is", "main").unwrap(), "Keep {{other}} as is");
+    }
+
+    #[test]
+    fn missing_required_argument_fails() {
+        let dir = tempfile::tempdir().unwrap();
+        let result = template("{{target}}").render(
+            &HashMap::new(),
+            &registry(),
+            CallContext::default(),
+            &audit(&dir),
+            "test",
+        );
+        assert_eq!(result.unwrap_err(), "Missing required argument: target");
+    }
+
+    #[test]
+    fn binds_arguments_to_tool_values() {
+        let text = render("{{tool:echo target={{target}} lines=100}}", "main").unwrap();
+        assert_eq!(serde_json::from_str::<Value>(&text).unwrap(), json!({ "target": "main", "lines": 100 }));
+
+        // String arguments stay strings, whatever they look like
+        let text = render("{{tool:echo target={{target}}}}", "42").unwrap();
+        assert_eq!(serde_json::from_str::<Value>(&text).unwrap(), json!({ "target": "42" }));
+    }
+
+    #[test]
+    fn argument_values_cannot_add_tool_arguments() {
+        let text = render("{{tool:echo target={{target}}}}", "main lines=5").unwrap();
+        assert_eq!(serde_json::from_str::<Value>(&text).unwrap(), json!({ "target": "main lines=5" }));
+    }
+
+    #[test]
+    fn argument_values_cannot_add_tool_placeholders() {
+        let text = render("Session {{target}}", "{{tool:erase}}").unwrap();
+        assert_eq!(text, "Session {{tool:erase}}");
+        let text = render("{{tool:echo target=x-{{target}}}}", "{{tool:erase}}").unwrap();
+        assert_eq!(serde_json::from_str::<Value>(&text).unwrap(), json!({ "target": "x-{{tool:erase}}" }));
+    }
+
+    #[test]
+    fn only_read_only_tools_are_called() {
+        let text = render("{{tool:erase}}", "main").unwrap();
+        assert_eq!(text, "[erase failed: prompt templates may only call read-only tools]");
+        let text = render("{{tool:missing}}", "main").unwrap();
+        assert_eq!(text, "[missing failed: Tool 'missing' not found or not permitted]");
+    }
+
+    #[test]
+    fn tool_calls_are_audited() {
+        let dir = tempfile::tempdir().unwrap();
+        let args = HashMap::from([("target".to_string(), "main".to_string())]);
+        let audit = audit(&dir);
+        template("{{tool:echo target={{target}}}}")
+            .render(&args, &registry(), CallContext::default(), &audit, "client")
+            .unwrap();
+        let entries = &audit.query(&json!({ "limit": 10 })).unwrap()["entries"];
+        assert_eq!(entries[0]["client"], "client");
+        assert_eq!(entries[0]["tool"], "echo");
+        assert_eq!(entries[0]["arguments"], json!({ "target": "main" }));
+        assert_eq!(entries[0]["status"], "ok");
+    }
+
+    #[test]
+    fn finds_the_tool_argument_of_a_prompt_argument() {
+        let prompt = template("Tail:\n{{tool:echo lines=10 target={{target}}}}");
+        assert_eq!(prompt.tool_argument("target"), Some(("echo", "target")));
+        assert_eq!(prompt.tool_argument("lines"), None);
+    }
+}
@@ -0,0 +1,351 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+use serde_json::Value;
+
+use super::path::Reference;
+
+/// The `if` expression of a workflow step, parsed before the workflow
+/// runs. Values are `$variables` or paths into them like `$app.pid`
+/// (null when not set), quoted text, numbers, `true`, `false` and `null`;
+/// they compare with `==`, `!=`, `<`, `<=`, `>`, `>=` and `contains`, and
+/// combine with `&&`, `||`, `!` (or `and`, `or`, `not`) and parentheses.
+/// On its own, a value holds unless it is null, false, zero or empty.
+pub struct Condition(Expr);
+
+enum Expr {
+    Literal(Value),
+    Var(Reference),
+    Not(Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Compare(Box<Expr>, Op, Box<Expr>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Op {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Contains,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Literal(Value),
+    Var(Reference),
+    Op(Op),
+    Not,
+    And,
+    Or,
+    Open,
+    Close,
+}
+
+impl Condition {
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut parser = Parser {
+            tokens: tokenize(text)?,
+            pos: 0,
+            end: text.len(),
+        };
+        let expr = parser.or()?;
+        match parser.tokens.get(parser.pos) {
+            Some((_, at)) => Err(format!("unexpected '{}' at position {at}", &text[*at..])),
+            None => Ok(Self(expr)),
+        }
+    }
+
+    /// Whether the condition holds for the workflow's variables.
+    pub fn holds(&self, vars: &HashMap<String, Value>) -> bool {
+        truthy(&eval(&self.0, vars))
+    }
+}
+
+/// Split an expression into tokens, each with its byte position.
+fn tokenize(text: &str) -> Result<Vec<(Token, usize)>, String> {
+    const SYMBOLS: &[(&str, Token)] = &[
+        ("&&", Token::And),
+        ("||", Token::Or),
+        ("==", Token::Op(Op::Eq)),
+        ("!=", Token::Op(Op::Ne)),
+        ("<=", Token::Op(Op::Le)),
+        (">=", Token::Op(Op::Ge)),
+        ("<", Token::Op(Op::Lt)),
+        (">", Token::Op(Op::Gt)),
+        ("!", Token::Not),
+        ("(", Token::Open),
+        (")", Token::Close),
+    ];
+
+    let mut tokens = Vec::new();
+    let mut pos = 0;
+    while let Some(c) = text[pos..].chars().next() {
+        let rest = &text[pos..];
+        if c.is_whitespace() {
+            pos += c.len_utf8();
+            continue;
+        }
+        let (token, len) = if let Some((symbol, token)) = SYMBOLS.iter().find(|(s, _)| rest.starts_with(s)) {
+            (token.clone(), symbol.len())
+        } else if c == '"' || c == '\'' {
+            string(rest).ok_or_else(|| format!("unterminated text at position {pos}"))?
+        } else if c == '$' {
+            let (reference, len) = Reference::parse(rest).map_err(|e| format!("invalid variable at position {pos}: {e}"))?;
+            (Token::Var(reference), len)
+        } else if c.is_ascii_digit() || c == '-' {
+            let len = rest[1..]
+                .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+                .map_or(rest.len(), |i| i + 1);
+            let number = serde_json::from_str::<Value>(&rest[..len])
+                .ok()
+                .filter(Value::is_number)
+                .ok_or_else(|| format!("invalid number '{}' at position {pos}", &rest[..len]))?;
+            (Token::Literal(number), len)
+        } else {
+            let len = word_len(rest);
+            let token = match &rest[..len] {
+                "contains" => Token::Op(Op::Contains),
+                "and" => Token::And,
+                "or" => Token::Or,
+                "not" => Token::Not,
+                "true" => Token::Literal(Value::Bool(true)),
+                "false" => Token::Literal(Value::Bool(false)),
+                "null" => Token::Literal(Value::Null),
+                "" => return Err(format!("unexpected '{c}' at position {pos}")),
+                word => {
+                    return Err(format!(
+                        "unknown word '{word}' at position {pos} (variables start with $, text is quoted)"
+                    ));
+                }
+            };
+            (token, len)
+        };
+        tokens.push((token, pos));
+        pos += len;
+    }
+    Ok(tokens)
+}
+
+/// Length of the name at the start of `text`.
+fn word_len(text: &str) -> usize {
+    text.find(|c: char| !(c.is_alphanumeric() || c == '_')).unwrap_or(text.len())
+}
+
+/// A quoted string at the start of `text` and its length with the quotes.
+/// A backslash escapes the next character.
+fn string(text: &str) -> Option<(Token, usize)> {
+    let mut chars = text.char_indices();
+    let (_, quote) = chars.next()?;
+    let mut value = String::new();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '\\' => value.push(chars.next()?.1),
+            c if c == quote => return Some((Token::Literal(Value::String(value)), i + 1)),
+            c => value.push(c),
+        }
+    }
+    None
+}
+
+struct Parser {
+    tokens: Vec<(Token, usize)>,
+    pos: usize,
+    /// Length of the text, for errors at its end.
+    end: usize,
+}
+
+impl Parser {
+    fn eat(&mut self, token: &Token) -> bool {
+        let found = self.tokens.get(self.pos).is_some_and(|(t, _)| t == token);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn or(&mut self) -> Result<Expr, String> {
+        let mut left = self.and()?;
+        while self.eat(&Token::Or) {
+            left = Expr::Or(Box::new(left), Box::new(self.and()?));
+        }
+        Ok(left)
+    }
+
+    fn and(&mut self) -> Result<Expr, String> {
+        let mut left = self.unary()?;
+        while self.eat(&Token::And) {
+            left = Expr::And(Box::new(left), Box::new(self.unary()?));
+        }
+        Ok(left)
+    }
+
+    fn unary(&mut self) -> Result<Expr, String> {
+        if self.eat(&Token::Not) {
+            return Ok(Expr::Not(Box::new(self.unary()?)));
+        }
+        let left = self.operand()?;
+        match self.tokens.get(self.pos) {
+            Some((Token::Op(op), _)) => {
+                let op = *op;
+                self.pos += 1;
+                Ok(Expr::Compare(Box::new(left), op, Box::new(self.operand()?)))
+            }
+            _ => Ok(left),
+        }
+    }
+
+    fn operand(&mut self) -> Result<Expr, String> {
+        let Some((token, at)) = self.tokens.get(self.pos).cloned() else {
+            return Err(format!("expected a value at position {}", self.end));
+        };
+        self.pos += 1;
+        match token {
+            Token::Literal(value) => Ok(Expr::Literal(value)),
+            Token::Var(reference) => Ok(Expr::Var(reference)),
+            Token::Open => {
+                let expr = self.or()?;
+                if !self.eat(&Token::Close) {
+                    let at = self.tokens.get(self.pos).map_or(self.end, |(_, at)| *at);
+                    return Err(format!("expected ')' at position {at}"));
+                }
+                Ok(expr)
+            }
+            _ => Err(format!("expected a value at position {at}")),
+        }
+    }
+}
+
+fn eval(expr: &Expr, vars: &HashMap<String, Value>) -> Value {
+    match expr {
+        Expr::Literal(value) => value.clone(),
+        Expr::Var(reference) => reference.resolve(vars).unwrap_or(Value::Null),
+        Expr::Not(e) => Value::Bool(!truthy(&eval(e, vars))),
+        Expr::And(l, r) => Value::Bool(truthy(&eval(l, vars)) && truthy(&eval(r, vars))),
+        Expr::Or(l, r) => Value::Bool(truthy(&eval(l, vars)) || truthy(&eval(r, vars))),
+        Expr::Compare(l, op, r) => Value::Bool(compare(&eval(l, vars), *op, &eval(r, vars))),
+    }
+}
+
+fn truthy(value: &Value) -> bool {
+    match value {
+        Value::Null => false,
+        Value::Bool(b) => *b,
+        Value::Number(n) => n.as_f64().is_some_and(|n| n != 0.0),
+        Value::String(s) => !s.is_empty(),
+        Value::Array(items) => !items.is_empty(),
+        Value::Object(map) => !map.is_empty(),
+    }
+}
+
+fn compare(left: &Value, op: Op, right: &Value) -> bool {
+    match op {
+        Op::Eq => equal(left, right),
+        Op::Ne => !equal(left, right),
+        Op::Lt => order(left, right).is_some_and(Ordering::is_lt),
+        Op::Le => order(left, right).is_some_and(Ordering::is_le),
+        Op::Gt => order(left, right).is_some_and(Ordering::is_gt),
+        Op::Ge => order(left, right).is_some_and(Ordering::is_ge),
+        Op::Contains => contains(left, right),
+    }
+}
+
+/// Equality, with 1 and 1.0 equal.
+pub(super) fn equal(left: &Value, right: &Value) -> bool {
+    match (left, right) {
+        (Value::Number(a), Value::Number(b)) => a.as_f64() == b.as_f64(),
+        _ => left == right,
+    }
+}
+
+/// Numbers and texts order among themselves; nothing else orders.
+fn order(left: &Value, right: &Value) -> Option<Ordering> {
+    match (left, right) {
+        (Value::Number(a), Value::Number(b)) => a.as_f64()?.partial_cmp(&b.as_f64()?),
+        (Value::String(a), Value::String(b)) => Some(a.cmp(b)),
+        _ => None,
+    }
+}
+
+/// Text contains a substring; a list or object contains a value equal to
+/// `needle` anywhere inside it, so `$apps contains "Slack"` finds the app
+/// named Slack in app_list's result.
+fn contains(haystack: &Value, needle: &Value) -> bool {
+    let inside = |value: &Value| {
+        equal(value, needle) || matches!(value, Value::Array(_) | Value::Object(_)) && contains(value, needle)
+    };
+    match (haystack, needle) {
+        (Value::String(text), Value::String(part)) => text.contains(part.as_str()),
+        (Value::String(text), Value::Number(n)) => text.contains(&n.to_string()),
+        (Value::Array(items), _) => items.iter().any(inside),
+        (Value::Object(map), _) => map.values().any(inside),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn vars() -> HashMap<String, Value> {
+        HashMap::from([
+            ("apps".to_string(), json!({ "apps": [{ "name": "Slack", "pid": 7 }, { "name": "Mail", "pid": 9 }] })),
+            ("count".to_string(), json!(3)),
+            ("title".to_string(), json!("Inbox (3)")),
+            ("empty".to_string(), json!([])),
+        ])
+    }
+
+    fn holds(text: &str) -> bool {
+        Condition::parse(text).unwrap_or_else(|e| panic!("{text}: {e}")).holds(&vars())
+    }
+
+    #[test]
+    fn compares_values() {
+        assert!(holds("$count == 3.0"));
+        assert!(holds("$count != 4"));
+        assert!(holds("$count >= 3 && $count < 10"));
+        assert!(holds("$title > 'Alpha'"));
+        assert!(!holds("$title > 1"));
+        assert!(holds("$apps.apps[0].name == \"Slack\""));
+        assert!(holds("$missing == null"));
+        assert!(holds("-1 < 0"));
+    }
+
+    #[test]
+    fn contains_searches_text_and_nested_values() {
+        assert!(holds("$apps contains \"Slack\""));
+        assert!(holds("$apps contains 9"));
+        assert!(!holds("$apps contains \"Notes\""));
+        assert!(holds("$title contains 'Inbox'"));
+        assert!(holds("$title contains 3"));
+    }
+
+    #[test]
+    fn combines_with_precedence() {
+        assert!(holds("$count == 1 || $count == 3 && $title contains 'Inbox'"));
+        assert!(!holds("($count == 1 || $count == 3) && $title contains 'Outbox'"));
+        assert!(holds("not $empty and ($count or false)"));
+        assert!(holds("!!$count"));
+        assert!(!holds("$missing"));
+        assert!(!holds("0"));
+        assert!(!holds("''"));
+    }
+
+    #[test]
+    fn reports_where_parsing_failed() {
+        let error = |text| Condition::parse(text).err().unwrap_or_else(|| panic!("{text} parsed"));
+        assert_eq!(error("$a == "), "expected a value at position 6");
+        assert_eq!(error("($a == 1"), "expected ')' at position 8");
+        assert_eq!(error("$a == 1 1"), "unexpected '1' at position 8");
+        assert_eq!(error("$a == 'open"), "unterminated text at position 6");
+        assert!(error("$a == Slack").starts_with("unknown word 'Slack' at position 6"));
+        assert!(error("$a == 1.2.3").starts_with("invalid number '1.2.3'"));
+        assert!(error("${a b} == 1").starts_with("invalid variable at position 0"));
+        assert_eq!(error("$a = 1"), "unexpected '=' at position 3");
+    }
+}
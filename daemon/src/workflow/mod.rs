@@ -0,0 +1,714 @@
+use std::collections::HashMap;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use serde_json::{json, Value};
+use tracing::debug;
+
+use crate::capabilities::{schema, CallContext, CapabilityRegistry, Confirm, Progress};
+use crate::error::DaemonError;
+use crate::mcp::types::{CallToolResult, Tool, ToolAnnotations};
+use crate::process;
+use condition::Condition;
+use path::Reference;
+
+mod condition;
+mod path;
+
+/// Name of the meta-tool that runs workflows.
+pub const TOOL: &str = "workflow_run";
+
+/// Most tool calls a workflow may make, `finally` steps, retries and every
+/// pass through a foreach loop included.
+const MAX_STEPS: usize = 20;
+
+/// Retries of a step with `on_error: retry` that doesn't set `retries`.
+const DEFAULT_RETRIES: u64 = 2;
+
+/// Longest wait between two attempts of a step.
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+/// Error kinds that the same call would hit again, so retrying is pointless.
+const PERMANENT_ERRORS: &[&str] = &[
+    "invalid_argument",
+    "unknown_tool",
+    "permission_denied",
+    "policy_violation",
+    "not_supported_on_platform",
+    "missing_dependency",
+];
+
+/// What to do when a step has failed, after its retries.
+#[derive(Clone, Copy, PartialEq)]
+enum OnError {
+    Stop,
+    Continue,
+    Retry,
+}
+
+/// A step of the workflow, checked before any of them runs.
+struct Step<'a> {
+    action: Action<'a>,
+    output_var: Option<&'a str>,
+    condition: Option<(&'a str, Condition)>,
+    on_error: OnError,
+    retries: u64,
+    backoff: Duration,
+}
+
+/// What a step does.
+enum Action<'a> {
+    /// Call a tool with these arguments.
+    Tool(&'a str, Value),
+    /// Run the steps once for each item of the referenced list, with the
+    /// item in the variable.
+    Foreach {
+        list: (&'a str, Reference),
+        var: &'a str,
+        steps: Vec<Step<'a>>,
+    },
+}
+
+impl Step<'_> {
+    fn name(&self) -> &str {
+        match &self.action {
+            Action::Tool(tool, _) => tool,
+            Action::Foreach { .. } => "foreach",
+        }
+    }
+}
+
+fn step_schema() -> Value {
+    json!({
+        "type": "object",
+        "properties": {
+            "tool": {
+                "type": "string",
+                "description": "Tool name to call"
+            },
+            "arguments": {
+                "type": "object",
+                "description": "Arguments to pass to the tool. Use $var_name, or a path into it like $windows.windows[0].id or ${apps[?name==\"Safari\"][0].pid} (a [?...] filter always gives a list), to reference previous step outputs."
+            },
+            "output_var": {
+                "type": "string",
+                "description": "Variable name to store this step's result (optional)"
+            },
+            "foreach": {
+                "type": "string",
+                "description": "Instead of a tool, run `steps` once for each item of the list this references, e.g. `$windows.windows`, with the item in the `as` variable. A value that isn't a list counts as a list of itself, null as an empty list."
+            },
+            "as": {
+                "type": "string",
+                "description": "Variable holding the current item of a foreach (default item)"
+            },
+            "steps": {
+                "type": "array",
+                "items": { "type": "object" },
+                "description": "Steps a foreach runs for each item, written like top-level steps"
+            },
+            "if": {
+                "type": "string",
+                "description": "Run the step only if this holds, e.g. `$apps contains \"Slack\"`. Values ($variables and paths into them, quoted text, numbers, true, false, null) compare with == != < <= > >= and contains, and combine with && || ! and parentheses. Variables that aren't set are null."
+            },
+            "on_error": {
+                "type": "string",
+                "enum": ["stop", "continue", "retry"],
+                "default": "stop",
+                "description": "When the step fails: stop the workflow, continue with the next step, or retry the step and stop if it still fails"
+            },
+            "retries": {
+                "type": "integer",
+                "minimum": 0,
+                "maximum": 10,
+                "description": "Extra attempts after a failure (default 2 with on_error retry, else 0), each counting toward the workflow's step limit. Invalid arguments, unknown or denied tools and unsupported tools aren't retried."
+            },
+            "backoff_ms": {
+                "type": "integer",
+                "minimum": 0,
+                "maximum": 30000,
+                "default": 500,
+                "description": "Wait before the first retry in milliseconds, doubled for each further one"
+            }
+        }
+    })
+}
+
+pub fn tool() -> Tool {
+    let step = step_schema();
+    let result = json!({
+        "type": "object",
+        "properties": {
+            "step": { "type": "integer" },
+            "tool": { "type": "string" },
+            "status": { "type": "string", "enum": ["ok", "error", "skipped"] },
+            "attempts": { "type": "integer" },
+            "condition": { "type": "string" },
+            "foreach": { "type": "string" },
+            "iterations": { "type": "array", "items": { "type": "array" } },
+            "output": { "type": "string" },
+            "is_error": { "type": "boolean" }
+        },
+        "required": ["step", "tool", "status", "output", "is_error"]
+    });
+
+    Tool {
+        name: TOOL.into(),
+        description: format!("Execute a sequence of daemon tools atomically with variable passing between steps. Each step's result is stored in its output_var and available as $var_name in subsequent step arguments and `if` conditions, with paths like $windows.windows[0].id reaching inside it. A foreach step runs nested steps for each item of a list; every step run and retry counts toward the limit of {MAX_STEPS}. Steps can be skipped by a condition, retried or continued past on error, and `finally` steps run at the end even when a step stopped the workflow."),
+        input_schema: json!({
+            "type": "object",
+            "properties": {
+                "steps": {
+                    "type": "array",
+                    "items": step.clone(),
+                    "description": "Ordered list of tool calls to execute"
+                },
+                "finally": {
+                    "type": "array",
+                    "items": step,
+                    "description": "Steps to run after the others however they ended, e.g. to clean up"
+                }
+            },
+            "required": ["steps"]
+        }),
+        output_schema: Some(json!({
+            "type": "object",
+            "properties": {
+                "completed_steps": { "type": "integer" },
+                "skipped_steps": { "type": "integer" },
+                "total_steps": { "type": "integer" },
+                "stopped_on_error": { "type": "boolean" },
+                "results": { "type": "array", "items": result.clone() },
+                "finally_results": { "type": "array", "items": result }
+            },
+            "required": ["completed_steps", "total_steps", "results"]
+        })),
+        annotations: ToolAnnotations::destructive().open_world(),
+    }
+}
+
+/// Run a workflow: its steps in order, then its `finally` steps. A step
+/// that fails stops the workflow unless its `on_error` says to continue.
+pub fn run(
+    registry: &CapabilityRegistry,
+    call: CallContext<'_>,
+    arguments: &Value,
+    progress: &Progress,
+    confirm: &Confirm<'_>,
+) -> CallToolResult {
+    let steps = match arguments["steps"].as_array() {
+        Some(s) => s,
+        None => return CallToolResult::error("'steps' must be an array"),
+    };
+
+    if steps.is_empty() {
+        return CallToolResult::error("'steps' array is empty");
+    }
+
+    let finally = arguments["finally"].as_array().map_or(&[][..], Vec::as_slice);
+    let (steps, finally) = match (parse_steps(steps, "Step "), parse_steps(finally, "Finally step ")) {
+        (Ok(steps), Ok(finally)) => (steps, finally),
+        (Err(e), _) | (_, Err(e)) => return CallToolResult::error(e),
+    };
+
+    // The finally steps keep their share, however far a loop runs
+    let reserved = count(&finally);
+    if count(&steps) + reserved > MAX_STEPS {
+        return CallToolResult::error(format!(
+            "Workflow limited to {MAX_STEPS} steps maximum, finally steps included"
+        ));
+    }
+
+    let mut runner = Runner {
+        registry,
+        call,
+        confirm,
+        vars: HashMap::new(),
+        budget: MAX_STEPS - reserved,
+        limit_reached: false,
+    };
+    let total = (steps.len() + finally.len()) as u64;
+
+    let mut step_results: Vec<Value> = Vec::new();
+    let report = |i: usize, step: &Step| {
+        progress.report(i as u64, Some(total), &format!("Step {}: {}", i + 1, step.name()));
+    };
+    let stopped = match runner.run_block(&steps, "step", &mut step_results, &report) {
+        Ok(stopped) => stopped,
+        Err(e) => return CallToolResult::error(e),
+    };
+
+    runner.budget += reserved;
+    runner.limit_reached = false;
+    let mut finally_results: Vec<Value> = Vec::new();
+    let report = |i: usize, step: &Step| {
+        let done = (steps.len() + i) as u64;
+        progress.report(done, Some(total), &format!("Finally step {}: {}", i + 1, step.name()));
+    };
+    if let Err(e) = runner.run_block(&finally, "finally step", &mut finally_results, &report) {
+        return CallToolResult::error(e);
+    }
+
+    progress.report(total, Some(total), "Workflow complete");
+    let skipped = step_results.iter().filter(|r| r["status"] == "skipped").count();
+    let mut output = json!({
+        "completed_steps": step_results.len() - skipped,
+        "skipped_steps": skipped,
+        "total_steps": steps.len(),
+        "results": step_results,
+    });
+    if stopped {
+        output["stopped_on_error"] = json!(true);
+    }
+    if !finally.is_empty() {
+        output["finally_results"] = json!(finally_results);
+    }
+    CallToolResult::json(&output)
+}
+
+/// Check the steps of a block before running any, so a typo in the last
+/// step's condition doesn't surface after the first ones ran. Steps are
+/// named by `label` and their number, e.g. `Step 2.1` inside a foreach.
+fn parse_steps<'a>(steps: &'a [Value], label: &str) -> Result<Vec<Step<'a>>, String> {
+    steps
+        .iter()
+        .enumerate()
+        .map(|(i, step)| {
+            let at = format!("{label}{}", i + 1);
+            let on_error = match step["on_error"].as_str() {
+                Some("continue") => OnError::Continue,
+                Some("retry") => OnError::Retry,
+                _ => OnError::Stop,
+            };
+
+            let action = match (step.get("tool"), step.get("foreach")) {
+                (Some(tool), None) => {
+                    let tool = tool.as_str().ok_or_else(|| format!("{at}: 'tool' must be a string"))?;
+
+                    // Don't allow recursive workflow calls
+                    if tool == TOOL {
+                        return Err(format!("{at}: recursive workflow_run not allowed"));
+                    }
+                    Action::Tool(tool, step.get("arguments").cloned().unwrap_or(json!({})))
+                }
+                (None, Some(list)) => {
+                    let text = list.as_str().ok_or_else(|| format!("{at}: 'foreach' must be a string"))?;
+                    let list = match Reference::parse(text) {
+                        Ok((list, len)) if len == text.len() => list,
+                        Ok(_) => return Err(format!("{at}: 'foreach' must be a single reference like $windows.windows")),
+                        Err(e) => return Err(format!("{at}: invalid 'foreach': {e}")),
+                    };
+                    // A failed pass can't be retried without redoing the
+                    // ones before it, and a loop has no result of its own
+                    if on_error == OnError::Retry || step.get("retries").is_some() {
+                        return Err(format!("{at}: a foreach can't be retried; retry the steps inside it"));
+                    }
+                    if step.get("output_var").is_some() {
+                        return Err(format!("{at}: a foreach has no output_var; set it on the steps inside it"));
+                    }
+                    let nested = match step["steps"].as_array() {
+                        Some(nested) if !nested.is_empty() => nested,
+                        _ => return Err(format!("{at}: a foreach needs a non-empty 'steps' array")),
+                    };
+                    for (j, nested) in nested.iter().enumerate() {
+                        schema::validate(&step_schema(), &mut nested.clone())
+                            .map_err(|e| format!("{at}.{}: {}", j + 1, DaemonError::from(e)))?;
+                    }
+                    Action::Foreach {
+                        list: (text, list),
+                        var: step["as"].as_str().unwrap_or("item"),
+                        steps: parse_steps(nested, &format!("{at}."))?,
+                    }
+                }
+                _ => return Err(format!("{at}: needs either 'tool' or 'foreach'")),
+            };
+
+            let condition = match step["if"].as_str() {
+                Some(text) => {
+                    let condition = Condition::parse(text).map_err(|e| format!("{at}: invalid 'if': {e}"))?;
+                    Some((text, condition))
+                }
+                None => None,
+            };
+            let default_retries = if on_error == OnError::Retry { DEFAULT_RETRIES } else { 0 };
+
+            Ok(Step {
+                action,
+                output_var: step["output_var"].as_str(),
+                condition,
+                on_error,
+                retries: step["retries"].as_u64().unwrap_or(default_retries),
+                backoff: Duration::from_millis(step["backoff_ms"].as_u64().unwrap_or(500)),
+            })
+        })
+        .collect()
+}
+
+/// Tool calls a block makes with each loop run once.
+fn count(steps: &[Step]) -> usize {
+    steps
+        .iter()
+        .map(|step| match &step.action {
+            Action::Tool(..) => 1,
+            Action::Foreach { steps, .. } => count(steps),
+        })
+        .sum()
+}
+
+/// Runs the steps of one workflow, keeping their variables.
+struct Runner<'a> {
+    registry: &'a CapabilityRegistry,
+    call: CallContext<'a>,
+    confirm: &'a Confirm<'a>,
+    vars: HashMap<String, Value>,
+    /// Tool calls the workflow may still make.
+    budget: usize,
+    limit_reached: bool,
+}
+
+impl Runner<'_> {
+    /// Run a block of steps in order, adding their results to `results`.
+    /// True if a step failed and stopped the block; an error if the request
+    /// was cancelled.
+    fn run_block(
+        &mut self,
+        steps: &[Step],
+        label: &str,
+        results: &mut Vec<Value>,
+        report: &dyn Fn(usize, &Step),
+    ) -> Result<bool, String> {
+        for (i, step) in steps.iter().enumerate() {
+            if process::is_cancelled() {
+                return Err(format!("Workflow cancelled before {label} {}", i + 1));
+            }
+            report(i, step);
+            let result = self.run_step(i + 1, step)?;
+            let failed = result["status"] == "error";
+            results.push(result);
+            if self.limit_reached || failed && step.on_error != OnError::Continue {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    /// Run a step, unless its condition doesn't hold, and describe how it
+    /// went.
+    fn run_step(&mut self, number: usize, step: &Step) -> Result<Value, String> {
+        if let Some((text, condition)) = &step.condition
+            && !condition.holds(&self.vars)
+        {
+            debug!(step = number, tool = step.name(), condition = %text, "workflow step skipped");
+            return Ok(json!({
+                "step": number,
+                "tool": step.name(),
+                "status": "skipped",
+                "condition": text,
+                "output": "",
+                "is_error": false,
+            }));
+        }
+        match &step.action {
+            Action::Tool(tool, arguments) => Ok(self.call_tool(number, step, tool, arguments)),
+            Action::Foreach { list, var, steps } => self.run_foreach(number, list, var, steps),
+        }
+    }
+
+    fn call_tool(&mut self, number: usize, step: &Step, tool: &str, arguments: &Value) -> Value {
+        if self.budget == 0 {
+            self.limit_reached = true;
+            return json!({
+                "step": number,
+                "tool": tool,
+                "status": "error",
+                "attempts": 0,
+                "output": format!("Workflow limit of {MAX_STEPS} steps reached"),
+                "is_error": true,
+            });
+        }
+        self.budget -= 1;
+
+        // Substitute $var_name references in string arguments
+        let mut tool_args = arguments.clone();
+        substitute_vars(&mut tool_args, &self.vars);
+
+        // A dry run would only get the same plan again
+        let retries = if self.call.dry_run { 0 } else { step.retries };
+        let mut attempts = 0;
+        let (result, error) = loop {
+            attempts += 1;
+            debug!(step = number, tool, attempt = attempts, "workflow step");
+            let result = self
+                .registry
+                .call_tool(self.call, tool, &tool_args, &Progress::none(), self.confirm);
+            let (result, error) = match result {
+                Ok(result) => (result, None),
+                Err(e) => (CallToolResult::error(e.to_string()), Some(e)),
+            };
+            let failed = result.is_error.unwrap_or(false);
+            let permanent = error.as_ref().is_some_and(|e| PERMANENT_ERRORS.contains(&e.kind()));
+            if !failed || permanent || attempts > retries {
+                break (result, error);
+            }
+            if self.budget == 0 {
+                debug!(step = number, tool, "workflow limit reached before a retry");
+                self.limit_reached = true;
+                break (result, error);
+            }
+            self.budget -= 1;
+            let delay = step.backoff.saturating_mul(1 << (attempts - 1).min(16)).min(MAX_BACKOFF);
+            debug!(step = number, tool, delay_ms = delay.as_millis() as u64, "retrying workflow step");
+            if !wait(delay) {
+                break (result, error);
+            }
+        };
+
+        // Extract text from result content
+        let mut text_output = result.text_output();
+
+        let is_error = result.is_error.unwrap_or(false);
+
+        // Store in output_var if specified: the structured result when the
+        // tool has one, else the text (parsed as JSON when it is JSON)
+        if let Some(var_name) = step.output_var {
+            let var_value = match &result.structured_content {
+                Some(value) => value.clone(),
+                None => serde_json::from_str::<Value>(&text_output).unwrap_or_else(|_| json!(text_output)),
+            };
+            self.vars.insert(var_name.to_string(), var_value);
+        }
+
+        if self.limit_reached {
+            text_output = format!("{text_output}\nNot retried: workflow limit of {MAX_STEPS} steps reached");
+        }
+        let mut step_result = json!({
+            "step": number,
+            "tool": tool,
+            "status": if is_error { "error" } else { "ok" },
+            "attempts": attempts,
+            "output": text_output,
+            "is_error": is_error,
+        });
+        if let Some(error) = error.as_ref().map(DaemonError::data) {
+            step_result["error"] = error;
+        }
+        step_result
+    }
+
+    /// Run a foreach step's steps for each item of its list, giving the
+    /// results of each pass. A failing step stops the loop as it would the
+    /// workflow, and the loop then counts as a failed step.
+    fn run_foreach(
+        &mut self,
+        number: usize,
+        (text, list): &(&str, Reference),
+        var: &str,
+        steps: &[Step],
+    ) -> Result<Value, String> {
+        let items = match list.resolve(&self.vars) {
+            Some(Value::Array(items)) => items,
+            Some(Value::Null) => Vec::new(),
+            Some(item) => vec![item],
+            None => {
+                return Ok(json!({
+                    "step": number,
+                    "tool": "foreach",
+                    "status": "error",
+                    "foreach": text,
+                    "output": format!("Variable {} is not set", list.name()),
+                    "is_error": true,
+                }));
+            }
+        };
+
+        let total = items.len();
+        let outer = self.vars.remove(var);
+        let mut iterations = Vec::new();
+        let mut stopped = false;
+        for (i, item) in items.into_iter().enumerate() {
+            debug!(step = number, iteration = i + 1, of = total, "workflow foreach");
+            self.vars.insert(var.to_string(), item);
+            let mut results = Vec::new();
+            stopped = self.run_block(steps, "foreach step", &mut results, &|_, _| {})?;
+            iterations.push(Value::Array(results));
+            if stopped {
+                break;
+            }
+        }
+        match outer {
+            Some(value) => self.vars.insert(var.to_string(), value),
+            None => self.vars.remove(var),
+        };
+
+        Ok(json!({
+            "step": number,
+            "tool": "foreach",
+            "status": if stopped { "error" } else { "ok" },
+            "foreach": text,
+            "iterations": iterations,
+            "output": format!("Ran {} of {total} iterations", iterations.len()),
+            "is_error": stopped,
+        }))
+    }
+}
+
+/// Sleep for `delay`, or until the request is cancelled; false if it was.
+fn wait(delay: Duration) -> bool {
+    let until = Instant::now() + delay;
+    while !process::is_cancelled() {
+        let left = until.saturating_duration_since(Instant::now());
+        if left.is_zero() {
+            return true;
+        }
+        thread::sleep(left.min(Duration::from_millis(50)));
+    }
+    false
+}
+
+/// Recursively substitute $var_name references, and paths into them, in
+/// argument values.
+fn substitute_vars(value: &mut Value, vars: &HashMap<String, Value>) {
+    match value {
+        Value::String(s) => {
+            // A string that is just a reference takes the referenced value
+            if let Ok((reference, len)) = Reference::parse(s)
+                && len == s.len()
+                && let Some(var_val) = reference.resolve(vars)
+            {
+                *value = var_val;
+                return;
+            }
+            // Otherwise do string interpolation for references within text
+            if s.contains('$') {
+                *s = path::interpolate(s, vars);
+            }
+        }
+        Value::Object(map) => {
+            for val in map.values_mut() {
+                substitute_vars(val, vars);
+            }
+        }
+        Value::Array(arr) => {
+            for val in arr.iter_mut() {
+                substitute_vars(val, vars);
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    use crate::capabilities::{no_confirmation, CapabilityProvider, ToolResult};
+    use crate::permissions;
+
+    /// `echo` returns its arguments, `flaky` fails every time, counting
+    /// its calls.
+    struct TestTools {
+        flaky_calls: Arc<AtomicUsize>,
+    }
+
+    impl CapabilityProvider for TestTools {
+        fn id(&self) -> &str {
+            "test"
+        }
+
+        fn name(&self) -> &str {
+            "Test"
+        }
+
+        fn tools(&self) -> Vec<Tool> {
+            let tool = |name: &str| Tool {
+                name: name.into(),
+                description: String::new(),
+                input_schema: json!({ "type": "object" }),
+                output_schema: None,
+                annotations: ToolAnnotations::read_only(),
+            };
+            vec![tool("echo"), tool("flaky")]
+        }
+
+        fn call(&self, tool_name: &str, arguments: &Value, _progress: &Progress) -> Option<ToolResult> {
+            match tool_name {
+                "echo" => Some(Ok(CallToolResult::json(arguments))),
+                "flaky" => {
+                    self.flaky_calls.fetch_add(1, Ordering::SeqCst);
+                    Some(Ok(CallToolResult::error("try again")))
+                }
+                _ => None,
+            }
+        }
+    }
+
+    /// Run a workflow against `TestTools`, giving its output and how many
+    /// times `flaky` was called.
+    fn run_workflow(arguments: Value) -> (Value, usize) {
+        let flaky_calls = Arc::new(AtomicUsize::new(0));
+        let mut registry = CapabilityRegistry::new(permissions::parse("[capabilities.test]\nallowed = true").unwrap());
+        registry.register(Box::new(TestTools {
+            flaky_calls: flaky_calls.clone(),
+        }));
+        let result = run(&registry, CallContext::default(), &arguments, &Progress::none(), &no_confirmation);
+        let text = result.text_output();
+        let output = result.structured_content.unwrap_or_else(|| panic!("{text}"));
+        (output, flaky_calls.load(Ordering::SeqCst))
+    }
+
+    fn echo(n: usize) -> Value {
+        json!({ "tool": "echo", "arguments": { "n": n } })
+    }
+
+    #[test]
+    fn retries_until_they_run_out() {
+        let steps = json!([{ "tool": "flaky", "on_error": "retry", "retries": 3, "backoff_ms": 0 }]);
+        let (output, calls) = run_workflow(json!({ "steps": steps }));
+        assert_eq!(calls, 4);
+        assert_eq!(output["results"][0]["attempts"], 4);
+        assert_eq!(output["stopped_on_error"], true);
+    }
+
+    #[test]
+    fn retries_count_against_the_step_limit() {
+        let mut steps: Vec<Value> = (0..MAX_STEPS - 4).map(echo).collect();
+        steps.push(json!({ "tool": "flaky", "on_error": "retry", "retries": 10, "backoff_ms": 0 }));
+        steps.push(echo(0));
+        let (output, calls) = run_workflow(json!({ "steps": steps, "finally": [echo(0)] }));
+
+        // Three calls are left after the finally step's share, and the
+        // retries take them all, so the last step doesn't run
+        assert_eq!(calls, 3);
+        let failed = &output["results"][MAX_STEPS - 4];
+        assert_eq!(failed["attempts"], 3);
+        assert!(failed["output"].as_str().unwrap().contains("limit of 20 steps"), "{failed}");
+        assert_eq!(output["results"].as_array().unwrap().len(), MAX_STEPS - 3);
+        assert_eq!(output["finally_results"][0]["status"], "ok");
+    }
+
+    #[test]
+    fn permanent_errors_are_not_retried() {
+        let steps = json!([
+            { "tool": "missing", "on_error": "retry", "backoff_ms": 0 },
+        ]);
+        let (output, _) = run_workflow(json!({ "steps": steps }));
+        let result = &output["results"][0];
+        assert_eq!(result["attempts"], 1);
+        assert_eq!(result["error"]["kind"], "unknown_tool");
+    }
+
+    #[test]
+    fn skipped_steps_are_counted_apart_from_completed_ones() {
+        let steps = json!([
+            echo(1),
+            { "tool": "echo", "if": "$nothing == 1" },
+            { "tool": "flaky", "on_error": "continue" },
+        ]);
+        let (output, _) = run_workflow(json!({ "steps": steps }));
+        assert_eq!(output["completed_steps"], 2);
+        assert_eq!(output["skipped_steps"], 1);
+        assert_eq!(output["total_steps"], 3);
+    }
+}
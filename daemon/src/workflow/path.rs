@@ -0,0 +1,372 @@
+use std::collections::HashMap;
+
+use serde_json::Value;
+
+use super::condition::equal;
+
+/// A reference to a workflow variable or to a value inside it: `$name`
+/// followed by `.field`, `[index]` (negative counts from the end),
+/// `["field"]` or `[?field==value]` segments, e.g. `$windows.windows[0].id`.
+/// Braced as `${...}`, the segments may contain spaces. A field of a list
+/// is taken from each of its items; a filter keeps the items where the
+/// field equals (or with `!=` doesn't equal) the value, always as a list,
+/// so `$apps[?name=="Safari"][0].pid` is the pid of the first match.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Reference {
+    name: String,
+    segments: Vec<Segment>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Segment {
+    Field(String),
+    Index(i64),
+    Filter {
+        field: Vec<String>,
+        equal: bool,
+        value: Value,
+    },
+}
+
+impl Reference {
+    /// Parse the reference at the start of `text`, returning it and its
+    /// length. Without braces the reference ends before the first character
+    /// that can't continue it, so `$app.name.` in text is `$app.name`.
+    pub fn parse(text: &str) -> Result<(Self, usize), String> {
+        let Some(rest) = text.strip_prefix('$') else {
+            return Err("a reference starts with $".into());
+        };
+        if let Some(inner) = rest.strip_prefix('{') {
+            let mut scanner = Scanner { text: inner, pos: 0 };
+            scanner.skip_spaces();
+            let reference = scanner.reference(true)?;
+            scanner.skip_spaces();
+            if !scanner.eat("}") {
+                return Err(format!("expected '}}' at position {}", scanner.pos + 2));
+            }
+            return Ok((reference, scanner.pos + 2));
+        }
+        let mut scanner = Scanner { text: rest, pos: 0 };
+        let reference = scanner.reference(false)?;
+        Ok((reference, scanner.pos + 1))
+    }
+
+    /// The referenced value, or None if the variable isn't set. A path
+    /// that leads nowhere gives null.
+    pub fn resolve(&self, vars: &HashMap<String, Value>) -> Option<Value> {
+        let value = vars.get(&self.name)?;
+        Some(self.segments.iter().fold(value.clone(), |value, segment| segment.apply(value)))
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl Segment {
+    fn apply(&self, value: Value) -> Value {
+        match (self, value) {
+            (Self::Field(name), Value::Object(mut map)) => map.remove(name).unwrap_or(Value::Null),
+            (Self::Field(_), Value::Array(items)) => Value::Array(
+                items
+                    .into_iter()
+                    .map(|item| self.apply(item))
+                    .filter(|item| !item.is_null())
+                    .collect(),
+            ),
+            (Self::Index(index), Value::Array(mut items)) => {
+                let index = if *index < 0 { items.len() as i64 + index } else { *index };
+                match usize::try_from(index) {
+                    Ok(index) if index < items.len() => items.swap_remove(index),
+                    _ => Value::Null,
+                }
+            }
+            (Self::Filter { .. }, value @ (Value::Array(_) | Value::Object(_))) => {
+                let mut matches = Vec::new();
+                self.filter(value, &mut matches);
+                Value::Array(matches)
+            }
+            _ => Value::Null,
+        }
+    }
+
+    /// Collect the matching items of a list, or of the lists in an object
+    /// (so a filter works on app_list's `{"apps": [...]}` as on the list).
+    fn filter(&self, value: Value, matches: &mut Vec<Value>) {
+        let Self::Filter { field, equal: keep, value: wanted } = self else {
+            return;
+        };
+        match value {
+            Value::Array(items) => {
+                for item in items {
+                    let found = field.iter().fold(&item, |v, name| &v[name.as_str()]);
+                    if equal(found, wanted) == *keep {
+                        matches.push(item);
+                    }
+                }
+            }
+            Value::Object(map) => {
+                for value in map.into_values().filter(Value::is_array) {
+                    self.filter(value, matches);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Replace references in text by the text of their values (strings as they
+/// are, anything else as JSON). References to variables that aren't set,
+/// and a `$` that starts no reference, are left as they are.
+pub fn interpolate(text: &str, vars: &HashMap<String, Value>) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('$') {
+        out.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let Ok((reference, len)) = Reference::parse(rest) else {
+            out.push('$');
+            rest = &rest[1..];
+            continue;
+        };
+        match reference.resolve(vars) {
+            Some(Value::String(s)) => out.push_str(&s),
+            Some(value) => out.push_str(&value.to_string()),
+            None => out.push_str(&rest[..len]),
+        }
+        rest = &rest[len..];
+    }
+    out.push_str(rest);
+    out
+}
+
+struct Scanner<'a> {
+    text: &'a str,
+    pos: usize,
+}
+
+impl Scanner<'_> {
+    fn rest(&self) -> &str {
+        &self.text[self.pos..]
+    }
+
+    fn eat(&mut self, prefix: &str) -> bool {
+        let found = self.rest().starts_with(prefix);
+        if found {
+            self.pos += prefix.len();
+        }
+        found
+    }
+
+    fn skip_spaces(&mut self) {
+        let rest = self.rest();
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+
+    fn name(&mut self) -> Option<String> {
+        let rest = self.rest();
+        let len = rest
+            .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+            .unwrap_or(rest.len());
+        let name = (len > 0).then(|| rest[..len].to_string());
+        self.pos += len;
+        name
+    }
+
+    /// A variable name and its segments. Braced (`strict`), a segment that
+    /// doesn't parse is an error; otherwise the reference ends before it.
+    fn reference(&mut self, strict: bool) -> Result<Reference, String> {
+        let name = self
+            .name()
+            .ok_or_else(|| format!("expected a variable name at position {}", self.pos))?;
+        let mut segments = Vec::new();
+        loop {
+            let start = self.pos;
+            if strict {
+                self.skip_spaces();
+            }
+            let segment = match self.rest().chars().next() {
+                Some('.') => {
+                    self.pos += 1;
+                    self.name().map(Segment::Field).ok_or("expected a field name after '.'".to_string())
+                }
+                Some('[') => {
+                    self.pos += 1;
+                    self.bracket()
+                }
+                _ => {
+                    self.pos = start;
+                    break;
+                }
+            };
+            match segment {
+                Ok(segment) => segments.push(segment),
+                Err(e) if strict => return Err(format!("{e} at position {}", self.pos)),
+                Err(_) => {
+                    self.pos = start;
+                    break;
+                }
+            }
+        }
+        Ok(Reference { name, segments })
+    }
+
+    /// What follows `[`, up to and including the `]`.
+    fn bracket(&mut self) -> Result<Segment, String> {
+        self.skip_spaces();
+        let segment = if self.eat("?") {
+            self.skip_spaces();
+            let mut field = vec![self.name().ok_or("expected a field name after '?'")?];
+            while self.eat(".") {
+                field.push(self.name().ok_or("expected a field name after '.'")?);
+            }
+            self.skip_spaces();
+            let equal = if self.eat("==") {
+                true
+            } else if self.eat("!=") {
+                false
+            } else {
+                return Err("expected == or != in the filter".into());
+            };
+            self.skip_spaces();
+            Segment::Filter {
+                field,
+                equal,
+                value: self.literal()?,
+            }
+        } else if matches!(self.rest().chars().next(), Some('"' | '\'')) {
+            match self.literal()? {
+                Value::String(name) => Segment::Field(name),
+                _ => unreachable!("a quoted literal is a string"),
+            }
+        } else {
+            let rest = self.rest();
+            let len = rest
+                .char_indices()
+                .find(|&(i, c)| !(c.is_ascii_digit() || i == 0 && c == '-'))
+                .map_or(rest.len(), |(i, _)| i);
+            let index = rest[..len].parse().map_err(|_| "expected an index, a quoted field or a filter")?;
+            self.pos += len;
+            Segment::Index(index)
+        };
+        self.skip_spaces();
+        if !self.eat("]") {
+            return Err("expected ']'".into());
+        }
+        Ok(segment)
+    }
+
+    /// Quoted text, a number, true, false or null.
+    fn literal(&mut self) -> Result<Value, String> {
+        let rest = self.rest();
+        let Some(quote) = rest.chars().next() else {
+            return Err("expected a value".into());
+        };
+        if quote == '"' || quote == '\'' {
+            let mut value = String::new();
+            let mut chars = rest.char_indices().skip(1);
+            while let Some((i, c)) = chars.next() {
+                match c {
+                    '\\' => value.push(chars.next().ok_or("unterminated text")?.1),
+                    c if c == quote => {
+                        self.pos += i + 1;
+                        return Ok(Value::String(value));
+                    }
+                    c => value.push(c),
+                }
+            }
+            return Err("unterminated text".into());
+        }
+        let len = rest
+            .find(|c: char| !(c.is_alphanumeric() || c == '.' || c == '-'))
+            .unwrap_or(rest.len());
+        let value = serde_json::from_str::<Value>(&rest[..len])
+            .ok()
+            .filter(|v| !v.is_array() && !v.is_object() && len > 0)
+            .ok_or_else(|| format!("expected quoted text, a number, true, false or null, got '{}'", &rest[..len]))?;
+        self.pos += len;
+        Ok(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn vars() -> HashMap<String, Value> {
+        HashMap::from([
+            (
+                "apps".to_string(),
+                json!({ "apps": [
+                    { "name": "Safari", "pid": 1, "window": { "title": "Home" } },
+                    { "name": "Mail", "pid": 2, "window": { "title": "Inbox" } },
+                    { "name": "Safari", "pid": 3 },
+                ] }),
+            ),
+            ("windows".to_string(), json!([{ "id": 10 }, { "id": 11 }])),
+            ("name".to_string(), json!("Mail")),
+        ])
+    }
+
+    fn resolve(text: &str) -> Option<Value> {
+        let (reference, len) = Reference::parse(text).unwrap_or_else(|e| panic!("{text}: {e}"));
+        assert_eq!(len, text.len(), "{text}");
+        reference.resolve(&vars())
+    }
+
+    #[test]
+    fn resolves_fields_and_indexes() {
+        assert_eq!(resolve("$windows[0].id"), Some(json!(10)));
+        assert_eq!(resolve("$windows[-1].id"), Some(json!(11)));
+        assert_eq!(resolve("$windows[2].id"), Some(Value::Null));
+        assert_eq!(resolve("$windows.id"), Some(json!([10, 11])));
+        assert_eq!(resolve("$apps.apps.window.title"), Some(json!(["Home", "Inbox"])));
+        assert_eq!(resolve("${apps [\"apps\"] [1] .name}"), Some(json!("Mail")));
+        assert_eq!(resolve("$name.length"), Some(Value::Null));
+        assert_eq!(resolve("$missing.field"), None);
+    }
+
+    #[test]
+    fn filters_always_give_a_list() {
+        let mail = json!({ "name": "Mail", "pid": 2, "window": { "title": "Inbox" } });
+        assert_eq!(resolve("${apps[?name==\"Mail\"]}"), Some(json!([mail])));
+        assert_eq!(resolve("${apps[?name==\"Mail\"][0].pid}"), Some(json!(2)));
+        assert_eq!(resolve("${apps[?name==\"Safari\"].pid}"), Some(json!([1, 3])));
+        assert_eq!(resolve("${apps.apps[?name != 'Safari'].pid}"), Some(json!([2])));
+        assert_eq!(resolve("${apps[?window.title=='Home'][0].pid}"), Some(json!(1)));
+        assert_eq!(resolve("${apps[?name=='Notes']}"), Some(json!([])));
+        assert_eq!(resolve("${windows[?id==11.0]}"), Some(json!([{ "id": 11 }])));
+        assert_eq!(resolve("${name[?id==1]}"), Some(Value::Null));
+    }
+
+    #[test]
+    fn unbraced_references_end_where_they_stop_parsing() {
+        let len = |text| Reference::parse(text).unwrap().1;
+        assert_eq!(len("$app.name."), 9);
+        assert_eq!(len("$app[0]s"), 7);
+        assert_eq!(len("$app[x]"), 4);
+        assert_eq!(len("$app-1"), 4);
+    }
+
+    #[test]
+    fn reports_bad_braced_references() {
+        let error = |text| Reference::parse(text).err().unwrap_or_else(|| panic!("{text} parsed"));
+        assert_eq!(error("name"), "a reference starts with $");
+        assert_eq!(error("$.a"), "expected a variable name at position 0");
+        assert_eq!(error("${a"), "expected '}' at position 3");
+        assert!(error("${a[x]}").starts_with("expected an index, a quoted field or a filter"));
+        assert!(error("${a[?b=c]}").starts_with("expected == or != in the filter"));
+        assert!(error("${a[?b==[1]]}").starts_with("expected quoted text, a number, true, false or null"));
+        assert!(error("${a['b}").starts_with("unterminated text"));
+    }
+
+    #[test]
+    fn interpolates_references_in_text() {
+        let vars = vars();
+        assert_eq!(interpolate("Open $name now", &vars), "Open Mail now");
+        assert_eq!(interpolate("ids: $windows.id", &vars), "ids: [10,11]");
+        assert_eq!(interpolate("Cost: $5 for $missing.x", &vars), "Cost: $5 for $missing.x");
+        assert_eq!(interpolate("${apps[?name=='Mail'][0].pid}!", &vars), "2!");
+    }
+}